@@ -0,0 +1,342 @@
+/*!
+2D affine transformations.
+
+An `Affine2` maps a point `(x, y)` to
+
+```text
+x' = a * x + c * y + e
+y' = b * x + d * y + f
+```
+
+which is the same layout as the SVG `matrix(a b c d e f)` transform. Transforms are composed
+with `then` (apply `self` first, then the other one) or with `*` (matrix product, the right hand
+side is applied first). Angles are given in degrees, like in the other functions of this crate.
+*/
+
+use std::ops::Mul;
+
+use iced::{Point, Rectangle, Size, Vector};
+
+/// A transform counts as singular if its determinant is smaller than this fraction of the
+/// products it is computed from, i.e. if it is zero up to rounding errors.
+const SINGULAR_TOLERANCE: f32 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2 {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Affine2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine2 {
+    pub const IDENTITY: Affine2 = Affine2 {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub fn translate(translation: Vector) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, translation.x, translation.y)
+    }
+
+    /// Rotation around the origin. Positive angles rotate clockwise on screen (y points down).
+    pub fn rotate(angle_degrees: f32) -> Self {
+        let (sin, cos) = angle_degrees.to_radians().sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Rotation around the given center.
+    pub fn rotate_about(center: Point, angle_degrees: f32) -> Self {
+        let to_origin = Vector::new(-center.x, -center.y);
+        Self::translate(to_origin)
+            .then(Self::rotate(angle_degrees))
+            .then(Self::translate(-to_origin))
+    }
+
+    /// Uniform scaling around the origin.
+    pub fn scale(factor: f32) -> Self {
+        Self::scale_xy(factor, factor)
+    }
+
+    pub fn scale_xy(x: f32, y: f32) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Skew along the x axis by `x_degrees` and along the y axis by `y_degrees`.
+    pub fn skew(x_degrees: f32, y_degrees: f32) -> Self {
+        Self::new(
+            1.0,
+            y_degrees.to_radians().tan(),
+            x_degrees.to_radians().tan(),
+            1.0,
+            0.0,
+            0.0,
+        )
+    }
+
+    /// Returns the transform that applies `self` first and `next` afterwards.
+    pub fn then(self, next: Affine2) -> Self {
+        next * self
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// Returns the inverse transform, or None if the transform collapses the plane
+    /// (e.g. a scale by zero), also if it only does so up to rounding errors.
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        let magnitude = (self.a * self.d).abs().max((self.b * self.c).abs());
+        if det.abs() <= f32::EPSILON * f32::EPSILON
+            || det.abs() <= magnitude * SINGULAR_TOLERANCE
+            || !det.is_finite()
+        {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Self::new(
+            a,
+            b,
+            c,
+            d,
+            -(a * self.e + c * self.f),
+            -(b * self.e + d * self.f),
+        ))
+    }
+
+    pub fn apply(&self, point: Point) -> Point {
+        Point::new(
+            self.a * point.x + self.c * point.y + self.e,
+            self.b * point.x + self.d * point.y + self.f,
+        )
+    }
+
+    /// Applies the transform to a vector (a direction or a distance), ignoring the translation.
+    pub fn apply_vector(&self, vector: Vector) -> Vector {
+        Vector::new(
+            self.a * vector.x + self.c * vector.y,
+            self.b * vector.x + self.d * vector.y,
+        )
+    }
+
+    /// Transforms the four corners of the rectangle (upper left, upper right, lower right,
+    /// lower left).
+    pub fn apply_rectangle(&self, rectangle: &Rectangle) -> [Point; 4] {
+        [
+            Point::new(rectangle.x, rectangle.y),
+            Point::new(rectangle.x + rectangle.width, rectangle.y),
+            Point::new(
+                rectangle.x + rectangle.width,
+                rectangle.y + rectangle.height,
+            ),
+            Point::new(rectangle.x, rectangle.y + rectangle.height),
+        ]
+        .map(|corner| self.apply(corner))
+    }
+
+    /// Returns the axis aligned bounding box of the transformed rectangle.
+    pub fn bounding_rectangle(&self, rectangle: &Rectangle) -> Rectangle {
+        let corners = self.apply_rectangle(rectangle);
+        let (mut min, mut max) = (corners[0], corners[0]);
+        for corner in &corners[1..] {
+            min = Point::new(min.x.min(corner.x), min.y.min(corner.y));
+            max = Point::new(max.x.max(corner.x), max.y.max(corner.y));
+        }
+        Rectangle::new(min, Size::new(max.x - min.x, max.y - min.y))
+    }
+
+    /// Returns the transform in SVG notation, e.g. `matrix(1 0 0 1 10 20)`.
+    pub fn to_svg(&self) -> String {
+        format!(
+            "matrix({} {} {} {} {} {})",
+            self.a, self.b, self.c, self.d, self.e, self.f
+        )
+    }
+}
+
+impl Mul for Affine2 {
+    type Output = Affine2;
+
+    /// Matrix product: `(lhs * rhs).apply(p) == lhs.apply(rhs.apply(p))`.
+    fn mul(self, rhs: Affine2) -> Affine2 {
+        Affine2::new(
+            self.a * rhs.a + self.c * rhs.b,
+            self.b * rhs.a + self.d * rhs.b,
+            self.a * rhs.c + self.c * rhs.d,
+            self.b * rhs.c + self.d * rhs.d,
+            self.a * rhs.e + self.c * rhs.f + self.e,
+            self.b * rhs.e + self.d * rhs.f + self.f,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    /// Returns a random translation, rotation, (possibly mirroring) scale or skew. The parts are
+    /// well conditioned, so that a chain of them can be inverted within f32 precision.
+    fn random_part(random: &mut Random) -> Affine2 {
+        let sign = if random.fraction() < 0.2 { -1.0 } else { 1.0 };
+        match (random.range(0.0, 5.0)) as u32 {
+            0 => Affine2::translate(random.vector(1000.0)),
+            1 => Affine2::rotate(random.range(-360.0, 360.0)),
+            2 => Affine2::scale(sign * random.range(0.1, 10.0)),
+            3 => Affine2::scale_xy(random.range(0.5, 2.0), sign * random.range(0.5, 2.0)),
+            _ => Affine2::skew(random.range(-30.0, 30.0), random.range(-30.0, 30.0)),
+        }
+    }
+
+    /// Returns one to four random parts, in the order they are applied.
+    fn random_parts(random: &mut Random) -> Vec<Affine2> {
+        let count = random.range(1.0, 5.0) as usize;
+        (0..count).map(|_| random_part(random)).collect()
+    }
+
+    fn compose(parts: &[Affine2]) -> Affine2 {
+        parts
+            .iter()
+            .fold(Affine2::IDENTITY, |transform, part| transform.then(*part))
+    }
+
+    /// Compares points with an error relative to their size, f32 has about 7 digits.
+    fn assert_close(actual: Point, expected: Point, context: &impl std::fmt::Debug) {
+        let size = 1.0 + expected.x.abs().max(expected.y.abs());
+        assert!(
+            actual.distance(expected) <= 1e-4 * size,
+            "{actual:?} != {expected:?} for {context:?}"
+        );
+    }
+
+    #[test]
+    fn composition_applies_the_parts_in_order() {
+        let mut random = Random(3);
+        for _ in 0..500 {
+            let parts = random_parts(&mut random);
+            let transform = compose(&parts);
+            for _ in 0..5 {
+                let p = random.point(500.0);
+                let stepwise = parts.iter().fold(p, |p, part| part.apply(p));
+                assert_close(transform.apply(p), stepwise, &parts);
+                // vectors ignore all translations
+                let v = Vector::new(p.x, p.y);
+                let stepwise = parts.iter().fold(v, |v, part| part.apply_vector(v));
+                let actual = transform.apply_vector(v);
+                assert_close(
+                    Point::new(actual.x, actual.y),
+                    Point::new(stepwise.x, stepwise.y),
+                    &parts,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn product_applies_the_right_hand_side_first() {
+        let mut random = Random(4);
+        for _ in 0..200 {
+            let (first, second) = (
+                compose(&random_parts(&mut random)),
+                compose(&random_parts(&mut random)),
+            );
+            let p = random.point(500.0);
+            assert_close(
+                (second * first).apply(p),
+                second.apply(first.apply(p)),
+                &(first, second),
+            );
+            assert_eq!(first.then(second), second * first);
+        }
+    }
+
+    #[test]
+    fn transform_then_inverse_is_identity() {
+        let mut random = Random(5);
+        for _ in 0..500 {
+            let parts = random_parts(&mut random);
+            let transform = compose(&parts);
+            let inverse = transform
+                .invert()
+                .unwrap_or_else(|| panic!("{parts:?} is invertible"));
+            for candidate in [transform.then(inverse), inverse.then(transform)] {
+                let linear = [candidate.a, candidate.b, candidate.c, candidate.d];
+                let expected = [1.0, 0.0, 0.0, 1.0];
+                for (value, expected) in linear.iter().zip(expected) {
+                    assert!(
+                        (value - expected).abs() < 1e-3,
+                        "{candidate:?} for {parts:?}"
+                    );
+                }
+            }
+            for _ in 0..5 {
+                let p = random.point(500.0);
+                assert_close(inverse.apply(transform.apply(p)), p, &parts);
+            }
+        }
+    }
+
+    #[test]
+    fn singular_transforms_have_no_inverse() {
+        let mut random = Random(6);
+        let singular = [
+            Affine2::scale(0.0),
+            Affine2::scale_xy(0.0, 3.0),
+            Affine2::scale_xy(2.0, 0.0),
+            // the second row is a multiple of the first one
+            Affine2::new(1.0, 2.0, 2.0, 4.0, 5.0, 6.0),
+            Affine2::new(f32::NAN, 0.0, 0.0, 1.0, 0.0, 0.0),
+            Affine2::new(f32::INFINITY, 0.0, 0.0, 1.0, 0.0, 0.0),
+        ];
+        for transform in singular {
+            assert_eq!(transform.invert(), None, "{transform:?}");
+        }
+        // composing with a singular transform anywhere in the chain stays singular
+        for _ in 0..100 {
+            let mut parts = random_parts(&mut random);
+            let position = random.range(0.0, parts.len() as f32) as usize;
+            parts.insert(position, Affine2::scale_xy(0.0, 1.0));
+            assert_eq!(compose(&parts).invert(), None, "{parts:?}");
+        }
+    }
+
+    #[test]
+    fn rotations_around_a_center_keep_it_in_place() {
+        let mut random = Random(7);
+        for _ in 0..100 {
+            let center = random.point(500.0);
+            let angle = random.range(-720.0, 720.0);
+            let rotation = Affine2::rotate_about(center, angle);
+            assert_close(rotation.apply(center), center, &angle);
+            let p = random.point(500.0);
+            let distance = (p.distance(center), rotation.apply(p).distance(center));
+            assert!((distance.0 - distance.1).abs() <= 1e-3 * (1.0 + distance.0));
+        }
+        // positive angles turn clockwise on the screen: right becomes down
+        assert_close(
+            Affine2::rotate(90.0).apply(Point::new(1.0, 0.0)),
+            Point::new(0.0, 1.0),
+            &90.0,
+        );
+    }
+}
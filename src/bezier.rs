@@ -0,0 +1,170 @@
+/*!
+Evaluation and flattening of quadratic and cubic Bézier curves.
+
+Flattening turns a curve into a polyline whose maximum distance from the curve is below a given
+tolerance. The number of segments is estimated with Wang's formula, which only looks at the
+control points, so no recursion is needed.
+*/
+
+use iced::Point;
+
+/// Upper bound for the number of segments of a flattened curve.
+const MAX_SEGMENTS: usize = 1024;
+
+/// Returns the point at parameter `t` (0..=1) of a quadratic Bézier curve.
+pub fn quadratic_point(p0: Point, c: Point, p1: Point, t: f32) -> Point {
+    let mt = 1.0 - t;
+    Point::new(
+        mt * mt * p0.x + 2.0 * mt * t * c.x + t * t * p1.x,
+        mt * mt * p0.y + 2.0 * mt * t * c.y + t * t * p1.y,
+    )
+}
+
+/// Returns the point at parameter `t` (0..=1) of a cubic Bézier curve.
+pub fn cubic_point(p0: Point, c0: Point, c1: Point, p1: Point, t: f32) -> Point {
+    let mt = 1.0 - t;
+    let a = mt * mt * mt;
+    let b = 3.0 * mt * mt * t;
+    let c = 3.0 * mt * t * t;
+    let d = t * t * t;
+    Point::new(
+        a * p0.x + b * c0.x + c * c1.x + d * p1.x,
+        a * p0.y + b * c0.y + c * c1.y + d * p1.y,
+    )
+}
+
+/// Flattens a quadratic Bézier curve. The result starts with `p0` and ends with `p1`.
+pub fn flatten_quadratic(p0: Point, c: Point, p1: Point, tolerance: f32) -> Vec<Point> {
+    let dd = second_difference(p0, c, p1);
+    let segments = segment_count(2.0 / 8.0 * dd, tolerance);
+    (0..=segments)
+        .map(|i| quadratic_point(p0, c, p1, i as f32 / segments as f32))
+        .collect()
+}
+
+/// Flattens a cubic Bézier curve. The result starts with `p0` and ends with `p1`.
+pub fn flatten_cubic(p0: Point, c0: Point, c1: Point, p1: Point, tolerance: f32) -> Vec<Point> {
+    let dd = second_difference(p0, c0, c1).max(second_difference(c0, c1, p1));
+    let segments = segment_count(6.0 / 8.0 * dd, tolerance);
+    (0..=segments)
+        .map(|i| cubic_point(p0, c0, c1, p1, i as f32 / segments as f32))
+        .collect()
+}
+
+/// Splits a cubic Bézier curve at parameter `t` (de Casteljau). Returns the control points of
+/// both halves.
+pub fn split_cubic(p0: Point, c0: Point, c1: Point, p1: Point, t: f32) -> ([Point; 4], [Point; 4]) {
+    let lerp = |a: Point, b: Point| Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+    let a = lerp(p0, c0);
+    let b = lerp(c0, c1);
+    let c = lerp(c1, p1);
+    let d = lerp(a, b);
+    let e = lerp(b, c);
+    let mid = lerp(d, e);
+    ([p0, a, d, mid], [mid, e, c, p1])
+}
+
+/// Length of the second difference `a - 2b + c`, which bounds the curvature of the curve.
+fn second_difference(a: Point, b: Point, c: Point) -> f32 {
+    let x = a.x - 2.0 * b.x + c.x;
+    let y = a.y - 2.0 * b.y + c.y;
+    (x * x + y * y).sqrt()
+}
+
+// Wang's formula: n = sqrt(factor * max second difference / tolerance)
+fn segment_count(scaled_difference: f32, tolerance: f32) -> usize {
+    let tolerance = tolerance.max(f32::EPSILON);
+    ((scaled_difference / tolerance).sqrt().ceil() as usize).clamp(1, MAX_SEGMENTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit_test::distance_to_polyline;
+
+    fn assert_close(a: Point, b: Point) {
+        assert!(a.distance(b) < 1e-3, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn curves_start_and_end_at_their_end_points() {
+        let (p0, c0, c1, p1) = (
+            Point::new(1.0, 2.0),
+            Point::new(30.0, -40.0),
+            Point::new(70.0, 90.0),
+            Point::new(100.0, 5.0),
+        );
+        assert_close(quadratic_point(p0, c0, p1, 0.0), p0);
+        assert_close(quadratic_point(p0, c0, p1, 1.0), p1);
+        assert_close(cubic_point(p0, c0, c1, p1, 0.0), p0);
+        assert_close(cubic_point(p0, c0, c1, p1, 1.0), p1);
+        // a straight cubic with evenly spaced control points moves with constant speed
+        let line = |t| {
+            cubic_point(
+                Point::new(0.0, 0.0),
+                Point::new(10.0, 0.0),
+                Point::new(20.0, 0.0),
+                Point::new(30.0, 0.0),
+                t,
+            )
+        };
+        assert_close(line(0.5), Point::new(15.0, 0.0));
+        assert_close(line(0.1), Point::new(3.0, 0.0));
+    }
+
+    #[test]
+    fn flattened_curves_stay_within_the_tolerance() {
+        let (p0, c0, c1, p1) = (
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 300.0),
+            Point::new(400.0, -200.0),
+            Point::new(400.0, 100.0),
+        );
+        for tolerance in [0.05, 0.25, 1.0, 5.0] {
+            let quadratic = flatten_quadratic(p0, c0, p1, tolerance);
+            let cubic = flatten_cubic(p0, c0, c1, p1, tolerance);
+            assert_eq!(quadratic.first(), Some(&p0));
+            assert_eq!(cubic.last(), Some(&p1));
+            for i in 0..=1000 {
+                let t = i as f32 / 1000.0;
+                let error = distance_to_polyline(quadratic_point(p0, c0, p1, t), &quadratic);
+                assert!(error <= tolerance * 1.01, "quadratic {tolerance}: {error}");
+                let error = distance_to_polyline(cubic_point(p0, c0, c1, p1, t), &cubic);
+                assert!(error <= tolerance * 1.01, "cubic {tolerance}: {error}");
+            }
+        }
+        // finer tolerances need more segments, a straight curve needs only one
+        assert!(
+            flatten_cubic(p0, c0, c1, p1, 0.1).len() > flatten_cubic(p0, c0, c1, p1, 1.0).len()
+        );
+        assert_eq!(
+            flatten_quadratic(p0, Point::new(5.0, 5.0), Point::new(10.0, 10.0), 0.1).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn split_halves_trace_the_original_curve() {
+        let (p0, c0, c1, p1) = (
+            Point::new(0.0, 0.0),
+            Point::new(20.0, 80.0),
+            Point::new(90.0, -30.0),
+            Point::new(100.0, 50.0),
+        );
+        for split in [0.25, 0.5, 0.8] {
+            let (first, second) = split_cubic(p0, c0, c1, p1, split);
+            assert_close(first[3], cubic_point(p0, c0, c1, p1, split));
+            for i in 0..=10 {
+                let t = i as f32 / 10.0;
+                assert_close(
+                    cubic_point(first[0], first[1], first[2], first[3], t),
+                    cubic_point(p0, c0, c1, p1, t * split),
+                );
+                assert_close(
+                    cubic_point(second[0], second[1], second[2], second[3], t),
+                    cubic_point(p0, c0, c1, p1, split + t * (1.0 - split)),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+/**
+ * Compares the quadtree spatial index against a linear scan over all bounding boxes.
+ *
+ * Run with `cargo run --release --bin bench_spatial_index [shape count]`.
+ *
+ * The benchmark scatters rectangles over a large board and measures
+ * - point queries (hit-testing on every CursorMoved),
+ * - rectangle queries (viewport culling),
+ * - moving shapes (keeping the index in sync while dragging).
+ */
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use iced::{Point, Rectangle, Size};
+use iced_hello_world::random::Random;
+use iced_hello_world::spatial_index::SpatialIndex;
+
+const QUERIES: usize = 10_000;
+const BOARD_SIZE: f32 = 50_000.0;
+
+pub fn main() {
+    let count: usize = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(10_000);
+
+    let mut random = Random(42);
+    let shapes: Vec<Rectangle> = (0..count)
+        .map(|_| {
+            Rectangle::new(
+                Point::new(
+                    random.fraction() * BOARD_SIZE,
+                    random.fraction() * BOARD_SIZE,
+                ),
+                Size::new(
+                    20.0 + random.fraction() * 200.0,
+                    20.0 + random.fraction() * 200.0,
+                ),
+            )
+        })
+        .collect();
+    let points: Vec<Point> = (0..QUERIES)
+        .map(|_| {
+            Point::new(
+                random.fraction() * BOARD_SIZE,
+                random.fraction() * BOARD_SIZE,
+            )
+        })
+        .collect();
+    let viewports: Vec<Rectangle> = points
+        .iter()
+        .map(|p| Rectangle::new(*p, Size::new(1920.0, 1080.0)))
+        .collect();
+
+    println!("{} shapes, {} queries each", count, QUERIES);
+
+    let start = Instant::now();
+    let mut index = SpatialIndex::new();
+    for (id, bounds) in shapes.iter().enumerate() {
+        index.insert(id, *bounds);
+    }
+    report("build index", start.elapsed(), count);
+
+    // --- point queries
+    let start = Instant::now();
+    for point in &points {
+        black_box(
+            shapes
+                .iter()
+                .enumerate()
+                .filter(|(_, bounds)| bounds.contains(*point))
+                .map(|(id, _)| id)
+                .max(),
+        );
+    }
+    report("point query, linear scan", start.elapsed(), QUERIES);
+
+    let start = Instant::now();
+    for point in &points {
+        black_box(index.query_point(*point).into_iter().max());
+    }
+    report("point query, spatial index", start.elapsed(), QUERIES);
+
+    // --- viewport queries
+    let start = Instant::now();
+    for viewport in &viewports {
+        black_box(
+            shapes
+                .iter()
+                .filter(|bounds| bounds.intersects(viewport))
+                .count(),
+        );
+    }
+    report("viewport query, linear scan", start.elapsed(), QUERIES);
+
+    let start = Instant::now();
+    for viewport in &viewports {
+        black_box(index.query_rect(*viewport).len());
+    }
+    report("viewport query, spatial index", start.elapsed(), QUERIES);
+
+    // --- moving shapes around
+    let start = Instant::now();
+    for i in 0..QUERIES.min(count * QUERIES) {
+        let id = i % count;
+        let mut bounds = shapes[id];
+        bounds.x += (i / count) as f32 * 5.0;
+        bounds.y -= (i / count) as f32 * 5.0;
+        index.update(id, bounds);
+    }
+    report("move shape, spatial index", start.elapsed(), QUERIES);
+}
+
+fn report(name: &str, elapsed: Duration, operations: usize) {
+    println!(
+        "{:<32} {:>10.3} ms total {:>10.3} µs per operation",
+        name,
+        elapsed.as_secs_f64() * 1000.0,
+        elapsed.as_secs_f64() * 1_000_000.0 / operations.max(1) as f64
+    );
+}
@@ -1,9 +1,9 @@
-use core::panic::PanicMessage;
-
 /**
- * Experimental program that draws a circle and a line across it.
- * The line can be resized by dragging its corners or moved by dragging the line itself.
- * The line rotates around the center of the frame when "Play" is pressed.
+ * Experimental program that draws circles and lines across them.
+ * A line can be turned and resized by dragging its end points, made thicker or thinner by dragging its corners,
+ * or moved by dragging the line itself. A circle is moved by dragging it and resized by dragging its rim.
+ * Double clicking an empty place adds a line or a circle, Delete (or Backspace) removes the selected one.
+ * Everything rotates around the center of the frame when "Play" is pressed.
  *
  * The goal is to experiment with mouse interaction on the canvas widget.
  */
@@ -14,7 +14,11 @@ use iced::{
     Point,
     Rectangle,
     Renderer,
+    Size,
     Theme,
+    Vector,
+    advanced::mouse::{Click, click},
+    keyboard::{self, key::Named},
     mouse,
     widget::canvas::{Event, Frame, Geometry, Path, Program, Stroke, Style, gradient::Linear},
 };
@@ -24,21 +28,55 @@ use iced::mouse::Event::ButtonReleased;
 
 // use std::time::Duration;
 
-use iced_hello_world::is_point_on_horizontal_line;
+use iced_hello_world::hit_test::hit_corner;
+use iced_hello_world::is_point_on_line;
 use iced_hello_world::is_point_on_line_corner;
+use iced_hello_world::line_corners;
 use iced_hello_world::rel_to_abs_pt;
+use iced_hello_world::resize_interaction;
 use iced_hello_world::rotate_point;
 
+/// Distance in pixels within which a click hits an end point, a corner or the rim of a circle.
+const HANDLE_TOLERANCE: f32 = 8.0;
+/// Radius in pixels of the drawn end point and corner handles.
+const HANDLE_RADIUS: f32 = 5.0;
+/// The line can't get thinner than this (in pixels).
+const MIN_LINE_WIDTH: f32 = 2.0;
+/// A circle can't get smaller than this radius (in pixels).
+const MIN_CIRCLE_RADIUS: f32 = 5.0;
+
 /**
- * This little program draws a circle and a line across the circle. The line starts and ends at the default positions.
- * Originally the line is horizontal but it rotates around the center of the frame.
+ * The kind of shape a double click adds.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShapeKind {
+    #[default]
+    Line,
+    Circle,
+}
+
+impl ShapeKind {
+    pub const ALL: [ShapeKind; 2] = [ShapeKind::Line, ShapeKind::Circle];
+}
+
+impl std::fmt::Display for ShapeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ShapeKind::Line => "Line",
+            ShapeKind::Circle => "Circle",
+        })
+    }
+}
+
+/**
+ * This little program draws circles and lines. At the start there is one circle in the center and one horizontal line
+ * across it. The end points of a line can be dragged to any angle. Everything rotates around the center of the frame.
  *
  * We also draw the position where the user clicked on the canvas as a red dot. The point is rotated back to the original coordinate system.
- * If the user clicked on the line a message is printed to the console for debugging purposes.
+ * A click selects the shape under the cursor, Delete or Backspace removes it, a double click on an empty spot adds a new shape.
  *
  *  Size values are percentage of frame size.
- *  the line center is in the middle of the frame the view will rotate it around this center point.
- *  the circle is centered in the middle of the frame. It won't rotate. Maybe later when we fill it with some color pattern.
+ *  the view rotates the shapes around the center point of the frame.
  *
  * The draw function scales to the relative sizes to the absolute frame sizes.
  *
@@ -46,31 +84,161 @@ use iced_hello_world::rotate_point;
 #[derive(Debug, Default)]
 pub struct CircleAndLineProgram {
     pub rotation_angle: f32, // degrees
+    pub new_shape: ShapeKind,
 }
 
 impl CircleAndLineProgram {
-    fn rel_cursor_pos(&self, bounds: &Rectangle, cursor_position: &Point) -> Point {
+    /// The cursor position in absolute frame coordinates, rotated back to the coordinate system of the shapes.
+    fn unrotated_cursor_pos(&self, bounds: &Rectangle, cursor_position: &Point) -> Point {
         let center_point = Point::new(bounds.width / 2.0, bounds.height / 2.0);
-        let rotated_cursor_position =
-            rotate_point(cursor_position, &center_point, &-self.rotation_angle);
+        rotate_point(cursor_position, &center_point, -self.rotation_angle)
+    }
+
+    fn rel_cursor_pos(&self, bounds: &Rectangle, cursor_position: &Point) -> Point {
+        let unrotated_cursor_position = self.unrotated_cursor_pos(bounds, cursor_position);
         Point::new(
-            rotated_cursor_position.x / bounds.width,
-            rotated_cursor_position.y / bounds.height,
+            unrotated_cursor_position.x / bounds.width,
+            unrotated_cursor_position.y / bounds.height,
         )
     }
+
+    /**
+     * Finds the shape (and the part of it) under the cursor, the cursor position is relative to the bounds.
+     * Handles win over shapes, shapes drawn later (on top) win over earlier ones:
+     * end points, line corners, circle rims, lines, circles.
+     */
+    fn hit(
+        &self,
+        state: &CicleAndLineState,
+        bounds: &Rectangle,
+        cursor_position: &Point,
+    ) -> DraggingMode {
+        // hit tests in absolute coordinates, where the shapes are not rotated
+        let position = self.unrotated_cursor_pos(bounds, cursor_position);
+        let size = bounds.size();
+        let lines = || state.lines.iter().enumerate().rev();
+        let circles = || state.circles.iter().enumerate().rev();
+
+        for (index, line) in lines() {
+            let (start, end, _) = line.absolute(size);
+            if let Some(end_index) = hit_corner(position, &[start, end], HANDLE_TOLERANCE) {
+                return DraggingMode::EndPoint(index, end_index);
+            }
+        }
+        for (index, line) in lines() {
+            let (start, end, width) = line.absolute(size);
+            if let Some(corner_index) =
+                is_point_on_line_corner(&position, &start, &end, width, HANDLE_TOLERANCE)
+            {
+                return DraggingMode::Corner(index, corner_index);
+            }
+        }
+        for (index, circle) in circles() {
+            let (center, radius) = circle.absolute(size);
+            if (position.distance(center) - radius).abs() <= HANDLE_TOLERANCE {
+                return DraggingMode::Rim(index);
+            }
+        }
+        for (index, line) in lines() {
+            let (start, end, width) = line.absolute(size);
+            if is_point_on_line(&position, &start, &end, width, 0.0) {
+                return DraggingMode::Line(index);
+            }
+        }
+        for (index, circle) in circles() {
+            let (center, radius) = circle.absolute(size);
+            if position.distance(center) <= radius {
+                return DraggingMode::Circle(index);
+            }
+        }
+        DraggingMode::NoDragging
+    }
 }
 
 /**
- * Dragging mode enum
+ * A line, positions are percentage of the frame size, the width is percentage of min(frame.width, frame.height).
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Line {
+    start: Point,
+    end: Point,
+    width: f32,
+}
+
+impl Line {
+    /// Start, end and width of the line in absolute frame coordinates (before the rotation).
+    fn absolute(&self, size: Size) -> (Point, Point, f32) {
+        (
+            scale(self.start, size),
+            scale(self.end, size),
+            self.width * size.width.min(size.height),
+        )
+    }
+}
+
+/**
+ * A circle, the center is percentage of the frame size, the radius is percentage of min(frame.width, frame.height).
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Circle {
+    center: Point,
+    radius: f32,
+}
+
+impl Circle {
+    /// Center and radius of the circle in absolute frame coordinates (before the rotation).
+    fn absolute(&self, size: Size) -> (Point, f32) {
+        (
+            scale(self.center, size),
+            self.radius * size.width.min(size.height),
+        )
+    }
+}
+
+fn scale(point: Point, size: Size) -> Point {
+    Point::new(point.x * size.width, point.y * size.height)
+}
+
+/**
+ * A line or a circle, by index.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Line(usize),
+    Circle(usize),
+}
+
+/**
+ * Dragging mode enum, the first number is the index of the line or circle
  * - None: not dragging
  * - Line: dragging the line
- * - Corner: dragging a corner (number represents the corner number (0 - 3))
+ * - EndPoint: dragging the start (0) or the end (1) of the line, turns and stretches the line
+ * - Corner: dragging a corner (number represents the corner number (0 - 3)), changes the width of the line
+ * - Circle: dragging the circle
+ * - Rim: dragging the rim of the circle, changes the radius
+ *
+ * The same values describe what the cursor is over.
  */
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum DraggingMode {
     NoDragging,
-    Line,
-    Corner(usize), // corner index
+    Line(usize),
+    EndPoint(usize, usize), // 0 = start, 1 = end
+    Corner(usize, usize),   // corner index
+    Circle(usize),
+    Rim(usize),
+}
+
+impl DraggingMode {
+    fn shape(&self) -> Option<Shape> {
+        match *self {
+            DraggingMode::NoDragging => None,
+            DraggingMode::Line(index)
+            | DraggingMode::EndPoint(index, _)
+            | DraggingMode::Corner(index, _) => Some(Shape::Line(index)),
+            DraggingMode::Circle(index) | DraggingMode::Rim(index) => Some(Shape::Circle(index)),
+        }
+    }
 }
 
 /**
@@ -78,40 +246,84 @@ enum DraggingMode {
  */
 #[derive(Debug)]
 pub struct CicleAndLineState {
-    circle_radius: f32,
-    line_start: Point,         // percentage of frame size
-    line_end: Point,           // percentage of frame size
-    line_width: f32,           // percentage of min(frame.width, frame.height)
-    cursor_pos: Point,         // position of the cursor in relative frame coordinates when clicked
+    circles: Vec<Circle>,
+    lines: Vec<Line>,
+    cursor_pos: Point, // position of the cursor in relative frame coordinates when clicked
     is_dragging: DraggingMode, // user keeps the left mouse button pressed
+    selected: Option<Shape>, // the shape clicked last, removed with Delete
+    last_click: Option<Click>, // for recognizing double clicks
 }
 
 impl Default for CicleAndLineState {
     fn default() -> Self {
         Self {
-            circle_radius: 0.25, // 25% frame size
-            line_start: Point::new(0.10, 0.40),
-            line_end: Point::new(0.90, 0.40),
-            line_width: 0.20,
-            // line_rotate: 0.0, // degrees
+            circles: vec![Circle {
+                center: Point::new(0.5, 0.5),
+                radius: 0.25, // 25% frame size
+            }],
+            lines: vec![Line {
+                start: Point::new(0.10, 0.40),
+                end: Point::new(0.90, 0.40),
+                width: 0.20,
+            }],
             cursor_pos: Point::new(0.0, 0.0),
             is_dragging: DraggingMode::NoDragging,
+            selected: None,
+            last_click: None,
+        }
+    }
+}
+
+impl CicleAndLineState {
+    /// Adds a new shape of the given kind centered at the relative position.
+    fn add(&mut self, kind: ShapeKind, position: Point) {
+        match kind {
+            ShapeKind::Line => {
+                self.lines.push(Line {
+                    start: Point::new(position.x - 0.15, position.y),
+                    end: Point::new(position.x + 0.15, position.y),
+                    width: 0.05,
+                });
+                self.selected = Some(Shape::Line(self.lines.len() - 1));
+            }
+            ShapeKind::Circle => {
+                self.circles.push(Circle {
+                    center: position,
+                    radius: 0.1,
+                });
+                self.selected = Some(Shape::Circle(self.circles.len() - 1));
+            }
         }
     }
+
+    fn remove(&mut self, shape: Shape) {
+        match shape {
+            Shape::Line(index) if index < self.lines.len() => {
+                self.lines.remove(index);
+            }
+            Shape::Circle(index) if index < self.circles.len() => {
+                self.circles.remove(index);
+            }
+            _ => {}
+        }
+        // the indices after the removed shape have changed
+        self.selected = None;
+        self.is_dragging = DraggingMode::NoDragging;
+    }
 }
 
 impl<Message> Program<Message> for CircleAndLineProgram {
     type State = CicleAndLineState;
 
     /**
-     * Handles mouse events on the canvas.
-     *
-     * The method first calculates center point and the rotated relative cursor position.
+     * Handles mouse and keyboard events on the canvas.
      *
-     * It then matches the event:
-     * - on left button press it checks if the click was on a corner or on the line and sets is_dragging accordingly
+     * It matches the event:
+     * - on Delete or Backspace it removes the selected shape
+     * - on left button press it checks if the click was on an end point, a corner, a rim, a line or a circle and sets
+     *   is_dragging accordingly. A double click on an empty place adds a new shape.
      * - on left button release it resets is_dragging
-     * - on cursor move it updates the line position if is_dragging is true
+     * - on cursor move it updates the dragged shape if is_dragging is set
      *
      * Note: Function returns "captured" if the event was handled (mouse click, release, move only if in dragging
      *       mode), "ignored" otherwise.
@@ -123,6 +335,19 @@ impl<Message> Program<Message> for CircleAndLineProgram {
         bounds: Rectangle,
         cursor: mouse::Cursor,
     ) -> (iced::widget::canvas::event::Status, Option<Message>) {
+        // --- remove the selected shape, wherever the cursor is
+        if let Event::Keyboard(keyboard::Event::KeyPressed {
+            key: keyboard::Key::Named(Named::Delete | Named::Backspace),
+            ..
+        }) = event
+        {
+            if let Some(shape) = state.selected {
+                state.remove(shape);
+                return (iced::widget::canvas::event::Status::Captured, None);
+            }
+            return (iced::widget::canvas::event::Status::Ignored, None);
+        }
+
         // Get the absolute cursor position - return if not available
         let cursor_position = if let Some(position) = cursor.position_in(bounds) {
             position
@@ -136,102 +361,96 @@ impl<Message> Program<Message> for CircleAndLineProgram {
             Event::Mouse(ButtonPressed(mouse::Button::Left)) => {
                 let rel_cursor_position: Point = self.rel_cursor_pos(&bounds, &cursor_position);
                 state.cursor_pos = rel_cursor_position;
+                let click = Click::new(cursor_position, mouse::Button::Left, state.last_click);
+                state.last_click = Some(click);
 
-                // check if the click was on a corner
-                if let Some(corner_index) = is_point_on_line_corner(
-                    &rel_cursor_position,
-                    &state.line_start,
-                    &state.line_end,
-                    state.line_width,
-                ) {
-                    println!("Clicked on corner {}", corner_index);
-                    state.is_dragging = DraggingMode::Corner(corner_index);
-                    return (iced::widget::canvas::event::Status::Captured, None);
-                }
+                let hit = self.hit(state, &bounds, &cursor_position);
+                state.is_dragging = hit;
+                state.selected = hit.shape();
 
-                // check if the click was on the line (i.e. inside the line width)
-                if is_point_on_horizontal_line(
-                    &rel_cursor_position,
-                    &state.line_start,
-                    &state.line_end,
-                    state.line_width / 2.0,
-                ) {
-                    println!("Clicked on the line!");
-                    state.is_dragging = DraggingMode::Line;
-                    return (iced::widget::canvas::event::Status::Captured, None);
-                } else {
-                    println!("Clicked outside the line!");
+                if hit == DraggingMode::NoDragging && matches!(click.kind(), click::Kind::Double) {
+                    state.add(self.new_shape, rel_cursor_position);
                 }
-                println!("Canvas clicked at position: {:?}", cursor_position);
                 return (iced::widget::canvas::event::Status::Captured, None);
             }
 
             // --- left button released
             Event::Mouse(ButtonReleased(mouse::Button::Left)) => {
                 state.is_dragging = DraggingMode::NoDragging;
-                println!("Canvas release at position: {:?}", cursor_position);
                 return (iced::widget::canvas::event::Status::Captured, None);
             }
 
             // --- cursor moved
             //Event::Mouse(mouse::Event::CursorMoved { position }) => match state.is_dragging {
             Event::Mouse(mouse::Event::CursorMoved { .. }) => match state.is_dragging {
-                DraggingMode::Corner(corner_index) => {
+                DraggingMode::EndPoint(index, end_index) => {
+                    // the end point follows the cursor, the line turns and stretches around the other end
                     let rel_cursor_position: Point = self.rel_cursor_pos(&bounds, &cursor_position);
                     let delta = rel_cursor_position - state.cursor_pos;
                     state.cursor_pos = rel_cursor_position;
-                    match corner_index {
-                        0 => {
-                            // upper left
-                            state.line_start = Point::new(
-                                state.line_start.x + delta.x,
-                                state.line_start.y + delta.y / 2.0,
-                            );
-                            state.line_end = Point::new(state.line_end.x, state.line_start.y); // our rects are all horizontal
-                            state.line_width -= delta.y; // this is correct. Center moves by half but width goes in both directions so we go full delta
-                        }
-                        1 => {
-                            // upper right
-                            state.line_end = Point::new(
-                                state.line_end.x + delta.x,
-                                state.line_end.y + delta.y / 2.0,
-                            );
-                            state.line_start = Point::new(state.line_start.x, state.line_end.y); // our rects are all horizontal
-                            state.line_width -= delta.y;
-                        }
-                        2 => {
-                            // lower right
-                            state.line_end = Point::new(
-                                state.line_end.x + delta.x,
-                                state.line_end.y + delta.y / 2.0,
-                            );
-                            state.line_start = Point::new(state.line_start.x, state.line_end.y); // our rects are all horizontal
-                            state.line_width += delta.y;
-                        }
-                        3 => {
-                            // lower left
-                            state.line_start = Point::new(
-                                state.line_start.x + delta.x,
-                                state.line_start.y + delta.y / 2.0,
-                            );
-                            state.line_end = Point::new(state.line_end.x, state.line_start.y); // our rects are all horizontal
-                            state.line_width += delta.y;
-                        }
-                        _ => {}
+                    let line = &mut state.lines[index];
+                    if end_index == 0 {
+                        line.start = line.start + delta;
+                    } else {
+                        line.end = line.end + delta;
+                    }
+                    return (iced::widget::canvas::event::Status::Captured, None);
+                }
+                DraggingMode::Corner(index, corner_index) => {
+                    // the side of the dragged corner follows the cursor, perpendicular to the line, while the
+                    // opposite side stays where it is. Corners 0 and 1 are on the upper side, 2 and 3 on the lower.
+                    state.cursor_pos = self.rel_cursor_pos(&bounds, &cursor_position);
+                    let position = self.unrotated_cursor_pos(&bounds, &cursor_position);
+                    let line = &mut state.lines[index];
+                    let (line_start, line_end, line_width) = line.absolute(bounds.size());
+                    let direction = line_end - line_start;
+                    let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+                    if length > f32::EPSILON {
+                        let side = if corner_index < 2 { -1.0 } else { 1.0 };
+                        let offset = position - line_start;
+                        // distance of the cursor from the center line, positive towards the lower side
+                        let distance = (direction.x * offset.y - direction.y * offset.x) / length;
+                        let new_width = (side * distance + line_width / 2.0).max(MIN_LINE_WIDTH);
+                        // the center line moves by half the change of the width
+                        let shift = side * (new_width - line_width) / 2.0 / length;
+                        let delta = Vector::new(
+                            -direction.y * shift / bounds.width,
+                            direction.x * shift / bounds.height,
+                        );
+                        line.start = line.start + delta;
+                        line.end = line.end + delta;
+                        line.width = new_width / bounds.width.min(bounds.height);
                     }
                     return (iced::widget::canvas::event::Status::Captured, None);
                 }
-                DraggingMode::Line => {
+                DraggingMode::Line(index) => {
                     let rel_cursor_position: Point = self.rel_cursor_pos(&bounds, &cursor_position);
                     let delta = rel_cursor_position - state.cursor_pos;
-                    state.line_start =
-                        Point::new(state.line_start.x + delta.x, state.line_start.y + delta.y);
-                    state.line_end =
-                        Point::new(state.line_end.x + delta.x, state.line_end.y + delta.y);
+                    let line = &mut state.lines[index];
+                    line.start = line.start + delta;
+                    line.end = line.end + delta;
                     state.cursor_pos = rel_cursor_position;
                     return (iced::widget::canvas::event::Status::Captured, None);
                 }
-                _ => {}
+                DraggingMode::Circle(index) => {
+                    let rel_cursor_position: Point = self.rel_cursor_pos(&bounds, &cursor_position);
+                    let delta = rel_cursor_position - state.cursor_pos;
+                    let circle = &mut state.circles[index];
+                    circle.center = circle.center + delta;
+                    state.cursor_pos = rel_cursor_position;
+                    return (iced::widget::canvas::event::Status::Captured, None);
+                }
+                DraggingMode::Rim(index) => {
+                    // the rim follows the cursor, the center stays where it is
+                    state.cursor_pos = self.rel_cursor_pos(&bounds, &cursor_position);
+                    let position = self.unrotated_cursor_pos(&bounds, &cursor_position);
+                    let circle = &mut state.circles[index];
+                    let (center, _) = circle.absolute(bounds.size());
+                    circle.radius = position.distance(center).max(MIN_CIRCLE_RADIUS)
+                        / bounds.width.min(bounds.height);
+                    return (iced::widget::canvas::event::Status::Captured, None);
+                }
+                DraggingMode::NoDragging => {}
             },
             _ => {}
         }
@@ -244,27 +463,51 @@ impl<Message> Program<Message> for CircleAndLineProgram {
         &self,
         state: &Self::State,
         renderer: &Renderer,
-        _theme: &Theme,
+        theme: &Theme,
         bounds: Rectangle,
-        _cursor: mouse::Cursor,
+        cursor: mouse::Cursor,
     ) -> Vec<Geometry> {
         let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let center = frame.center();
+        let rotated = |point: &Point| rotate_point(point, &center, self.rotation_angle);
 
-        // border rectangle
-        frame.fill_rectangle(Point::ORIGIN, bounds.size(), Color::from_rgb(0.0, 0.2, 0.4));
+        // the shape under the cursor (or the dragged one) and the selected shape get an outline
+        let hovered = match state.is_dragging {
+            DraggingMode::NoDragging => cursor
+                .position_in(bounds)
+                .map(|position| self.hit(state, &bounds, &position))
+                .unwrap_or(DraggingMode::NoDragging),
+            dragging => dragging,
+        }
+        .shape();
+        let highlight = |shape: Shape| {
+            if hovered == Some(shape) {
+                Some(palette.success.base.color)
+            } else if state.selected == Some(shape) {
+                Some(palette.success.strong.color)
+            } else {
+                None
+            }
+        };
 
-        let frame_min = frame.width().min(frame.height());
+        // border rectangle
+        frame.fill_rectangle(Point::ORIGIN, bounds.size(), palette.primary.strong.color);
 
-        // the circle int the center
-        frame.fill(
-            &Path::circle(frame.center(), frame_min * state.circle_radius),
-            Color::from_rgb(0.6, 0.8, 1.0),
-        );
+        // the circles
+        for (index, circle) in state.circles.iter().enumerate() {
+            let (circle_center, radius) = circle.absolute(bounds.size());
+            let path = Path::circle(rotated(&circle_center), radius);
+            frame.fill(&path, palette.primary.weak.color);
+            if let Some(color) = highlight(Shape::Circle(index)) {
+                frame.stroke(&path, Stroke::default().with_color(color).with_width(3.0));
+            }
+        }
 
-        // gradient for the rotating rectangle:
+        // gradient for the rotating lines:
         // - we have a diagonal rainbow gradient from red via green to blue
         // - green is a very small segment because the color is so dominant
-        // Note that the rainbow will not rotate with the rectangle. That would be an interesting new challenge.
+        // Note that the rainbow will not rotate with the lines. That would be an interesting new challenge.
         let gradient = Linear::new(
             Point::new(0.0, 0.0),
             Point::new(bounds.width, bounds.height),
@@ -277,47 +520,126 @@ impl<Message> Program<Message> for CircleAndLineProgram {
         .add_stop(0.7, Color::from_rgb(0.0, 0.05, 0.75))
         .add_stop(1.0, Color::from_rgb(0.0, 0.0, 1.0));
 
-        // calculate line start and end points in absolute coordinates and draw it
-        let start_point = Point::new(
-            frame.width() * state.line_start.x,
-            frame.height() * state.line_start.y,
-        );
+        // draw the lines in non-rotated position for easier debugging
+        for line in &state.lines {
+            let (start_point, end_point, width) = line.absolute(bounds.size());
+            frame.stroke(
+                &Path::line(start_point, end_point),
+                Stroke {
+                    style: palette.background.base.text.into(),
+                    width,
+                    ..Default::default()
+                },
+            );
+        }
 
-        let end_point = Point::new(
-            frame.width() * state.line_end.x,
-            frame.height() * state.line_end.y,
-        );
+        let handle_stroke = Stroke::default()
+            .with_color(palette.background.base.text)
+            .with_width(1.0);
+        for (index, line) in state.lines.iter().enumerate() {
+            let (start_point, end_point, width) = line.absolute(bounds.size());
 
-        // draw the rotated line with gradient fill
-        frame.stroke(
-            &Path::line(
-                rotate_point(&start_point, &frame.center(), &self.rotation_angle),
-                rotate_point(&end_point, &frame.center(), &self.rotation_angle),
-            ),
-            Stroke {
-                //style: Color::WHITE.into(),
-                style: Style::Gradient(gradient.into()),
-                width: frame_min * state.line_width,
-                ..Default::default()
-            },
-        );
+            // draw the rotated line with gradient fill
+            frame.stroke(
+                &Path::line(rotated(&start_point), rotated(&end_point)),
+                Stroke {
+                    //style: Color::WHITE.into(),
+                    style: Style::Gradient(gradient.into()),
+                    width,
+                    ..Default::default()
+                },
+            );
 
-        // draw the line in non-rotated position for easier debugging
-        frame.stroke(
-            &Path::line(start_point, end_point),
-            Stroke {
-                style: Color::BLACK.into(),
-                width: frame_min * state.line_width,
-                ..Default::default()
-            },
-        );
+            let corners =
+                line_corners(&start_point, &end_point, width).map(|corner| rotated(&corner));
+            if let Some(color) = highlight(Shape::Line(index)) {
+                frame.stroke(
+                    &Path::new(|builder| {
+                        builder.move_to(corners[0]);
+                        for corner in &corners[1..] {
+                            builder.line_to(*corner);
+                        }
+                        builder.close();
+                    }),
+                    Stroke::default().with_color(color).with_width(3.0),
+                );
+            }
 
-        // draw the clicked position as a red dot
+            // draw the handles: circles at the end points, squares at the corners, rotated with the line
+            for end_point in [start_point, end_point] {
+                let handle = Path::circle(rotated(&end_point), HANDLE_RADIUS);
+                frame.fill(&handle, palette.background.base.color);
+                frame.stroke(&handle, handle_stroke);
+            }
+            for corner in corners {
+                let handle = Path::rectangle(
+                    Point::new(corner.x - HANDLE_RADIUS, corner.y - HANDLE_RADIUS),
+                    Size::new(2.0 * HANDLE_RADIUS, 2.0 * HANDLE_RADIUS),
+                );
+                frame.fill(&handle, palette.background.base.color);
+                frame.stroke(&handle, handle_stroke);
+            }
+        }
+
+        // draw the clicked position as a dot in the danger color (red)
         frame.fill(
             &Path::circle(rel_to_abs_pt(&frame, &state.cursor_pos), 10.0),
-            Color::from_rgb(1.0, 0.0, 0.0),
+            palette.danger.base.color,
         );
 
         vec![frame.into_geometry()]
     }
+
+    /**
+     * The cursor shows what dragging does: moving a line or circle, placing an end point, resizing perpendicular to
+     * the line at a corner or away from the center at the rim. While dragging the cursor stays the same.
+     */
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        // relative to the bounds, also outside of them while dragging
+        let Some(cursor_position) = cursor
+            .position()
+            .map(|position| Point::new(position.x - bounds.x, position.y - bounds.y))
+        else {
+            return mouse::Interaction::default();
+        };
+        let mode = match state.is_dragging {
+            DraggingMode::NoDragging if cursor.is_over(bounds) => {
+                self.hit(state, &bounds, &cursor_position)
+            }
+            mode => mode,
+        };
+        let rotated = |direction: Vector| {
+            rotate_point(
+                &(Point::ORIGIN + direction),
+                &Point::ORIGIN,
+                self.rotation_angle,
+            ) - Point::ORIGIN
+        };
+        match mode {
+            DraggingMode::NoDragging => mouse::Interaction::default(),
+            DraggingMode::Line(_) | DraggingMode::Circle(_) => mouse::Interaction::Move,
+            DraggingMode::EndPoint(..) => mouse::Interaction::Crosshair,
+            DraggingMode::Corner(index, _) => {
+                // the width changes perpendicular to the line
+                let (start, end, _) = state.lines[index].absolute(bounds.size());
+                let direction = end - start;
+                resize_interaction(rotated(Vector::new(-direction.y, direction.x)))
+            }
+            DraggingMode::Rim(index) => {
+                // cursor and center on the screen, both rotated
+                let (center, _) = state.circles[index].absolute(bounds.size());
+                let center = rotate_point(
+                    &center,
+                    &Point::new(bounds.width / 2.0, bounds.height / 2.0),
+                    self.rotation_angle,
+                );
+                resize_interaction(cursor_position - center)
+            }
+        }
+    }
 }
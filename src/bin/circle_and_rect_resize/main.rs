@@ -1,21 +1,48 @@
 use iced::{
-    Alignment, Length, Subscription,
-    widget::{Canvas, button, column, text},
+    Alignment, Length, Subscription, Theme,
+    widget::{Canvas, button, column, pick_list, row, text},
 };
+use iced_hello_world::theme;
 use sweeten::widget::mouse_area::MouseArea;
 
 // ...existing code...
 
+use std::path::PathBuf;
 use std::time::Duration;
 
 mod circle_and_line_program;
-use circle_and_line_program::CircleAndLineProgram;
+use circle_and_line_program::{CircleAndLineProgram, ShapeKind};
 
+/// Command line options:
+/// - `--palettes <FILE>`: JSON file with additional color palettes (default `palettes.json`)
+/// - `--theme <NAME>`: the theme at start, e.g. "Dark"
 fn main() -> iced::Result {
+    let mut palette_file = PathBuf::from(theme::DEFAULT_PALETTE_FILE);
+    let mut theme_name = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--palettes" => palette_file = args.next().map(PathBuf::from).unwrap_or(palette_file),
+            "--theme" => theme_name = args.next(),
+            _ => eprintln!("ignoring unknown argument {}", arg),
+        }
+    }
+    let (themes, theme) = theme::select_theme(&palette_file, theme_name.as_deref());
+
     //iced::run("My App", MyApp::update, MyApp::view)
     iced::application("iced svg polygon", MyApp::update, MyApp::view)
         .subscription(MyApp::subscription)
-        .run()
+        .theme(MyApp::theme)
+        .run_with(move || {
+            (
+                MyApp {
+                    themes,
+                    theme,
+                    ..MyApp::default()
+                },
+                iced::Task::none(),
+            )
+        })
 }
 
 #[derive(Clone, Debug)]
@@ -23,6 +50,8 @@ enum Message {
     _AreaClicked,
     RotateStop,
     Tick,
+    ThemeChanged(Theme),
+    NewShapeChanged(ShapeKind),
 }
 
 #[derive(Debug, Default)]
@@ -31,6 +60,9 @@ struct MyApp {
     rotation_angle: f32,
     mouse_state_text: String,
     circle_and_line_program: CircleAndLineProgram,
+    /// Light, dark and the palettes of the palette file
+    themes: Vec<Theme>,
+    theme: Theme,
 }
 
 impl MyApp {
@@ -51,16 +83,42 @@ impl MyApp {
                 self.rotation_angle = (self.rotation_angle + 0.25) % 360.0;
                 self.circle_and_line_program.rotation_angle = self.rotation_angle;
             }
+            Message::ThemeChanged(theme) => {
+                self.theme = theme;
+            }
+            Message::NewShapeChanged(kind) => {
+                self.circle_and_line_program.new_shape = kind;
+            }
             Message::_AreaClicked => {} //todo!(),
         }
     }
 
+    fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+
     fn view(&self) -> iced::Element<'_, Message> {
         column![
-            button(text(if !self.rotating { "Play" } else { "Pause" }))
-                .on_press(Message::RotateStop),
+            row![
+                button(text(if !self.rotating { "Play" } else { "Pause" }))
+                    .on_press(Message::RotateStop),
+                pick_list(
+                    self.themes.as_slice(),
+                    Some(&self.theme),
+                    Message::ThemeChanged
+                ),
+                text("Double click adds:"),
+                pick_list(
+                    ShapeKind::ALL,
+                    Some(self.circle_and_line_program.new_shape),
+                    Message::NewShapeChanged
+                ),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
             text(format!("Rotation Angle: {:.2}°", self.rotation_angle)),
             text(self.mouse_state_text.clone()),
+            text("Drag shapes, end points, corners and rims. Delete removes the selected shape."),
             "A Canvas",
             MouseArea::new(
                 Canvas::new(&self.circle_and_line_program)
@@ -0,0 +1,508 @@
+//
+// Saving and loading the board as a JSON file.
+//
+// Concept:
+//  - The file format is independent of the in-memory structs: only the geometry is written,
+//    transient state like drag positions or the selected node is not.
+//  - Paths are stored as SVG path data, so a file can be inspected (and edited) by hand.
+//  - Shape ids are not stored. Loading assigns new ids in file order, which keeps the z-order.
+//  - Images are either embedded (the original file content as base64) or refer to the image
+//    file. Paths are stored relative to the board file if the image lies in the same directory
+//    or below, so the directory can be moved as a whole.
+//  - SVG assets are stored with their original source.
+//  - Frames are stored with their name, the shapes inside are not linked to them.
+//  - Comment threads refer to the shape they are pinned to by its position in the shape list.
+//  - Stencil libraries use the same shape records, one list of shapes per stencil.
+//
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use iced::{Point, Rectangle, Vector};
+use iced_hello_world::ink::InkPoint;
+use iced_hello_world::vector_path::VectorPath;
+use serde::{Deserialize, Serialize};
+
+use crate::comment::{Anchor, Comment, Thread};
+use crate::document::{Document, ShapeId};
+use crate::placement::Placement;
+use crate::shape::Shape;
+use crate::shape_frame::FrameState;
+use crate::shape_image::{ImageData, ImageSource, ImageState};
+use crate::shape_ink::InkState;
+use crate::shape_path::PathState;
+use crate::shape_rectangle::RectangleState;
+use crate::shape_svg::SvgState;
+use crate::stencil::{Stencil, StencilLibrary};
+
+/// Version of the file format, increased on incompatible changes.
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct BoardFile {
+    version: u32,
+    shapes: Vec<ShapeRecord>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    threads: Vec<ThreadRecord>,
+}
+
+/// Comment thread. With `shape` (an index into the shape list) `x` and `y` are the offset from
+/// the upper left corner of the shape's bounding box, otherwise a world point.
+#[derive(Serialize, Deserialize)]
+struct ThreadRecord {
+    x: f32,
+    y: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shape: Option<usize>,
+    #[serde(default)]
+    resolved: bool,
+    comments: Vec<CommentRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CommentRecord {
+    author: String,
+    text: String,
+    /// Seconds since the Unix epoch
+    time: u64,
+}
+
+impl ThreadRecord {
+    /// `shapes` are the ids of the shapes in file order.
+    fn new(thread: &Thread, shapes: &[ShapeId]) -> Self {
+        let (position, shape) = match thread.anchor {
+            Anchor::Point(point) => (Vector::new(point.x, point.y), None),
+            Anchor::Shape { shape, offset } => match shapes.iter().position(|id| *id == shape) {
+                Some(index) => (offset, Some(index)),
+                None => (offset, None),
+            },
+        };
+        ThreadRecord {
+            x: position.x,
+            y: position.y,
+            shape,
+            resolved: thread.resolved,
+            comments: thread
+                .comments
+                .iter()
+                .map(|comment| CommentRecord {
+                    author: comment.author.clone(),
+                    text: comment.text.clone(),
+                    time: comment.time,
+                })
+                .collect(),
+        }
+    }
+
+    fn into_thread(self, shapes: &[ShapeId]) -> Thread {
+        let anchor = match self.shape.and_then(|index| shapes.get(index)) {
+            Some(shape) => Anchor::Shape {
+                shape: *shape,
+                offset: Vector::new(self.x, self.y),
+            },
+            None => Anchor::Point(Point::new(self.x, self.y)),
+        };
+        Thread {
+            anchor,
+            comments: self
+                .comments
+                .into_iter()
+                .map(|comment| Comment {
+                    author: comment.author,
+                    text: comment.text,
+                    time: comment.time,
+                })
+                .collect(),
+            resolved: self.resolved,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LibraryFile {
+    version: u32,
+    name: String,
+    stencils: Vec<StencilRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StencilRecord {
+    name: String,
+    shapes: Vec<ShapeRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ShapeRecord {
+    Rectangle {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    Path {
+        d: String,
+    },
+    /// Freehand stroke, every point is `[x, y, width]`
+    Ink {
+        points: Vec<[f32; 3]>,
+    },
+    /// Raster image, `crop` is `[x, y, width, height]` in pixels. Exactly one of `data`
+    /// (base64) and `path` is set.
+    Image {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        #[serde(default)]
+        rotation: f32,
+        crop: [f32; 4],
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+    },
+    /// SVG asset with its original source
+    Svg {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        #[serde(default)]
+        rotation: f32,
+        source: String,
+    },
+    /// Named region of the board
+    Frame {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        name: String,
+    },
+}
+
+impl ShapeRecord {
+    /// `directory` is the directory of the board file, image paths are relative to it.
+    fn new(shape: &Shape, directory: &Path) -> Self {
+        match shape {
+            Shape::Rectangle(state) => ShapeRecord::Rectangle {
+                x: state.rect.x,
+                y: state.rect.y,
+                width: state.rect.width,
+                height: state.rect.height,
+            },
+            Shape::Path(state) => ShapeRecord::Path {
+                d: state.path.to_svg_data(),
+            },
+            Shape::Ink(state) => ShapeRecord::Ink {
+                points: state
+                    .points
+                    .iter()
+                    .map(|p| [p.position.x, p.position.y, p.width])
+                    .collect(),
+            },
+            Shape::Image(state) => {
+                let (data, path) = match &state.data.source {
+                    ImageSource::Embedded => (Some(BASE64.encode(&state.data.bytes)), None),
+                    ImageSource::File(path) => (None, Some(relative_path(path, directory))),
+                };
+                let rect = state.placement.rect;
+                ShapeRecord::Image {
+                    x: rect.x,
+                    y: rect.y,
+                    width: rect.width,
+                    height: rect.height,
+                    rotation: state.placement.rotation_degrees,
+                    crop: [
+                        state.crop.x,
+                        state.crop.y,
+                        state.crop.width,
+                        state.crop.height,
+                    ],
+                    data,
+                    path,
+                }
+            }
+            Shape::Svg(state) => {
+                let rect = state.placement.rect;
+                ShapeRecord::Svg {
+                    x: rect.x,
+                    y: rect.y,
+                    width: rect.width,
+                    height: rect.height,
+                    rotation: state.placement.rotation_degrees,
+                    source: state.source.to_string(),
+                }
+            }
+            Shape::Frame(state) => ShapeRecord::Frame {
+                x: state.rect.x,
+                y: state.rect.y,
+                width: state.rect.width,
+                height: state.rect.height,
+                name: state.name.clone(),
+            },
+        }
+    }
+
+    fn into_shape(self, directory: &Path) -> io::Result<Shape> {
+        Ok(match self {
+            ShapeRecord::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } => Shape::Rectangle(RectangleState {
+                rect: Rectangle {
+                    x,
+                    y,
+                    width,
+                    height,
+                },
+                ..RectangleState::default()
+            }),
+            ShapeRecord::Path { d } => Shape::Path(PathState::new(
+                VectorPath::from_svg_data(&d)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            )),
+            ShapeRecord::Ink { points } => Shape::Ink(InkState::new(
+                points
+                    .into_iter()
+                    .map(|[x, y, width]| InkPoint {
+                        position: iced::Point::new(x, y),
+                        width,
+                    })
+                    .collect(),
+            )),
+            ShapeRecord::Image {
+                x,
+                y,
+                width,
+                height,
+                rotation,
+                crop: [crop_x, crop_y, crop_width, crop_height],
+                data,
+                path,
+            } => {
+                let data = match (data, path) {
+                    (Some(data), _) => ImageData::decode(
+                        BASE64
+                            .decode(data)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+                        ImageSource::Embedded,
+                    )?,
+                    (None, Some(path)) => {
+                        let path = directory.join(path);
+                        ImageData::open(&path, false).map_err(|e| {
+                            io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+                        })?
+                    }
+                    (None, None) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "image without data or path",
+                        ));
+                    }
+                };
+                Shape::Image(ImageState::with_crop(
+                    Arc::new(data),
+                    Placement {
+                        rect: Rectangle {
+                            x,
+                            y,
+                            width,
+                            height,
+                        },
+                        rotation_degrees: rotation,
+                    },
+                    Rectangle {
+                        x: crop_x,
+                        y: crop_y,
+                        width: crop_width,
+                        height: crop_height,
+                    },
+                ))
+            }
+            ShapeRecord::Svg {
+                x,
+                y,
+                width,
+                height,
+                rotation,
+                source,
+            } => Shape::Svg(SvgState::new(
+                source,
+                Placement {
+                    rect: Rectangle {
+                        x,
+                        y,
+                        width,
+                        height,
+                    },
+                    rotation_degrees: rotation,
+                },
+            )),
+            ShapeRecord::Frame {
+                x,
+                y,
+                width,
+                height,
+                name,
+            } => Shape::Frame(FrameState::new(
+                name,
+                Rectangle {
+                    x,
+                    y,
+                    width,
+                    height,
+                },
+            )),
+        })
+    }
+}
+
+/// Returns `path` relative to `directory` if it lies inside, otherwise the absolute path.
+fn relative_path(path: &Path, directory: &Path) -> PathBuf {
+    let absolute = |path: &Path| std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let path = absolute(path);
+    match path.strip_prefix(absolute(directory)) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => path,
+    }
+}
+
+/// The directory of the board file, image paths are relative to it.
+fn board_directory(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Writes all shapes of the document to the given file.
+pub fn save(document: &Document, path: &Path) -> io::Result<()> {
+    let directory = board_directory(path);
+    let ids: Vec<ShapeId> = document.iter().map(|(id, _)| id).collect();
+    let board = BoardFile {
+        version: VERSION,
+        shapes: document
+            .iter()
+            .map(|(_, shape)| ShapeRecord::new(shape, directory))
+            .collect(),
+        threads: document
+            .threads()
+            .map(|(_, thread)| ThreadRecord::new(thread, &ids))
+            .collect(),
+    };
+    let json = serde_json::to_string_pretty(&board)?;
+    std::fs::write(path, json)
+}
+
+/// Reads a document from the given file.
+pub fn load(path: &Path) -> io::Result<Document> {
+    let json = std::fs::read_to_string(path)?;
+    let board: BoardFile = serde_json::from_str(&json)?;
+    if board.version > VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported file version {}", board.version),
+        ));
+    }
+    let mut document = board
+        .shapes
+        .into_iter()
+        .map(|record| record.into_shape(board_directory(path)))
+        .collect::<io::Result<Vec<Shape>>>()
+        .map(Document::from_iter)?;
+    let ids: Vec<ShapeId> = document.iter().map(|(id, _)| id).collect();
+    for record in board.threads {
+        document.add_thread(record.into_thread(&ids));
+    }
+    Ok(document)
+}
+
+/// Writes a stencil library to the given file.
+pub fn save_library(library: &StencilLibrary, path: &Path) -> io::Result<()> {
+    let directory = board_directory(path);
+    let file = LibraryFile {
+        version: VERSION,
+        name: library.name.clone(),
+        stencils: library
+            .stencils
+            .iter()
+            .map(|stencil| StencilRecord {
+                name: stencil.name.clone(),
+                shapes: stencil
+                    .shapes
+                    .iter()
+                    .map(|shape| ShapeRecord::new(shape, directory))
+                    .collect(),
+            })
+            .collect(),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&file)?)
+}
+
+/// Reads a stencil library from the given file.
+pub fn load_library(path: &Path) -> io::Result<StencilLibrary> {
+    let json = std::fs::read_to_string(path)?;
+    let file: LibraryFile = serde_json::from_str(&json)?;
+    if file.version > VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported file version {}", file.version),
+        ));
+    }
+    let directory = board_directory(path);
+    let stencils = file
+        .stencils
+        .into_iter()
+        .map(|stencil| {
+            let shapes = stencil
+                .shapes
+                .into_iter()
+                .map(|record| record.into_shape(directory))
+                .collect::<io::Result<Vec<Shape>>>()?;
+            Ok(Stencil::new(stencil.name, shapes))
+        })
+        .collect::<io::Result<Vec<Stencil>>>()?;
+    Ok(StencilLibrary {
+        name: file.name,
+        stencils,
+        file: Some(path.to_path_buf()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_survive_save_and_load() {
+        let path = VectorPath::from_svg_data(
+            "M 0.5 -3 L 100 0 Q 150 50 100 100 C 60 140 20 100 0 100 Z M 200 200 L 210.25 1e5",
+        )
+        .expect("valid path data");
+        let mut document = Document::default();
+        document.add(PathState::new(path.clone()));
+
+        let file =
+            std::env::temp_dir().join(format!("board_file_test_{}.json", std::process::id()));
+        save(&document, &file).expect("saved");
+        let loaded = load(&file);
+        let _ = std::fs::remove_file(&file);
+        let loaded = loaded.expect("loaded");
+
+        let shapes: Vec<&Shape> = loaded.iter().map(|(_, shape)| shape).collect();
+        match shapes.as_slice() {
+            [Shape::Path(state)] => assert_eq!(state.path, path),
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//
+// Comment threads pinned to the board, for asynchronous review.
+//
+// Concept:
+//  - A thread is a list of comments (author, time, text) at a pin. The pin is either at a fixed
+//    world point or attached to a shape: then it keeps its offset from the upper left corner of
+//    the shape's bounding box and follows the shape when it is moved.
+//  - Threads are part of the document (see Document::add_thread), so they are saved and loaded
+//    with the shapes. When a shape is removed, its threads stay where the shape was.
+//  - Resolved threads are hidden on the board unless the user asks to see them.
+//  - Pins are drawn in screen size, like the handles, so they can be found at every zoom level.
+//
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use iced::widget::canvas::{Frame, Path, Stroke, Text};
+use iced::{Color, Pixels, Point, Vector, alignment};
+
+use crate::document::ShapeId;
+
+/// Radius of a pin in screen pixels.
+pub const PIN_RADIUS: f32 = 10.0;
+const OPEN_COLOR: Color = Color::from_rgb(1.0, 0.85, 0.3);
+const RESOLVED_COLOR: Color = Color::from_rgb(0.75, 0.75, 0.75);
+
+/// Where a thread is pinned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    /// A fixed world point
+    Point(Point),
+    /// Offset from the upper left corner of the shape's bounding box
+    Shape { shape: ShapeId, offset: Vector },
+}
+
+#[derive(Clone, Debug)]
+pub struct Comment {
+    pub author: String,
+    pub text: String,
+    /// Seconds since the Unix epoch
+    pub time: u64,
+}
+
+impl Comment {
+    /// A comment written now.
+    pub fn new(author: impl Into<String>, text: impl Into<String>) -> Self {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        Self {
+            author: author.into(),
+            text: text.into(),
+            time,
+        }
+    }
+
+    /// The time as "YYYY-MM-DD HH:MM UTC".
+    pub fn timestamp(&self) -> String {
+        let days = (self.time / 86_400) as i64;
+        let seconds = self.time % 86_400;
+        let (year, month, day) = civil_from_days(days);
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds % 3600 / 60
+        )
+    }
+}
+
+/// Converts days since 1970-01-01 to a date of the Gregorian calendar (Howard Hinnant's
+/// `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[derive(Clone, Debug)]
+pub struct Thread {
+    pub anchor: Anchor,
+    /// The first comment and the replies, oldest first
+    pub comments: Vec<Comment>,
+    pub resolved: bool,
+}
+
+impl Thread {
+    pub fn new(anchor: Anchor) -> Self {
+        Self {
+            anchor,
+            comments: Vec::new(),
+            resolved: false,
+        }
+    }
+}
+
+/// Draws a pin at the given world position with the number of comments in it. `open` marks the
+/// thread shown in the thread panel.
+pub fn draw_pin(frame: &mut Frame, position: Point, thread: &Thread, open: bool, scale: f32) {
+    let radius = PIN_RADIUS / scale;
+    let circle = Path::circle(position, radius);
+    frame.fill(
+        &circle,
+        if thread.resolved {
+            RESOLVED_COLOR
+        } else {
+            OPEN_COLOR
+        },
+    );
+    frame.stroke(
+        &circle,
+        Stroke::default()
+            .with_color(if open {
+                Color::from_rgb(1.0, 0.6, 0.0)
+            } else {
+                Color::from_rgb(0.3, 0.3, 0.3)
+            })
+            .with_width(if open { 2.0 } else { 1.0 } / scale),
+    );
+    frame.fill_text(Text {
+        content: thread.comments.len().to_string(),
+        position,
+        color: Color::BLACK,
+        size: Pixels(12.0 / scale),
+        horizontal_alignment: alignment::Horizontal::Center,
+        vertical_alignment: alignment::Vertical::Center,
+        ..Text::default()
+    });
+}
@@ -0,0 +1,264 @@
+//
+// The document of the creator: all shapes on the board.
+//
+// Concept:
+//  - Every shape gets a unique ShapeId when it is added. Ids are never reused.
+//  - Ids grow monotonically, so ordering shapes by id gives the drawing order (z-order): shapes
+//    added later are drawn on top.
+//  - A spatial index over the bounding boxes is kept in sync with the shapes. It answers
+//    "which shape is under the cursor" and "which shapes are visible" without scanning all shapes.
+//    Shapes the index rejects (bounds with NaN, infinity or far beyond any sensible coordinate)
+//    are kept in a separate list that is always scanned, so they can still be selected.
+//  - Comment threads live in the document as well, with their own ids. Threads pinned to a shape
+//    are moved to a fixed point when the shape is removed.
+//
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use iced::{Point, Rectangle, Size};
+use iced_hello_world::spatial_index::SpatialIndex;
+
+use crate::comment::{Anchor, Thread};
+use crate::shape::Shape;
+use crate::shape_frame::FrameState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShapeId(u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreadId(u64);
+
+#[derive(Debug, Default)]
+pub struct Document {
+    shapes: BTreeMap<ShapeId, Shape>,
+    index: SpatialIndex<ShapeId>,
+    /// Shapes whose bounds the index rejected
+    unindexed: BTreeSet<ShapeId>,
+    next_id: u64,
+    threads: BTreeMap<ThreadId, Thread>,
+    next_thread_id: u64,
+}
+
+impl Document {
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Adds a shape on top of all other shapes and returns its id.
+    pub fn add(&mut self, shape: impl Into<Shape>) -> ShapeId {
+        let shape = shape.into();
+        let id = ShapeId(self.next_id);
+        self.next_id += 1;
+        self.shapes.insert(id, shape);
+        self.update_index(id);
+        id
+    }
+
+    /// Replaces the shape with the given id. Does nothing if there is no such shape.
+    pub fn update(&mut self, id: ShapeId, shape: Shape) {
+        if let Some(existing) = self.shapes.get_mut(&id) {
+            let moved = existing.bounds() != shape.bounds();
+            *existing = shape;
+            if moved {
+                self.update_index(id);
+            }
+        }
+    }
+
+    /// Puts the shape into the spatial index, or into the list of unindexed shapes if the index
+    /// rejects its bounds.
+    fn update_index(&mut self, id: ShapeId) {
+        let bounds = self.shapes[&id].bounds();
+        if self.index.update(id, bounds) {
+            self.unindexed.remove(&id);
+        } else {
+            self.unindexed.insert(id);
+        }
+    }
+
+    pub fn remove(&mut self, id: ShapeId) -> Option<Shape> {
+        // threads pinned to the shape stay where they are
+        let origin = self.get(id).map(|shape| shape.bounds().position());
+        for thread in self.threads.values_mut() {
+            if let (Anchor::Shape { shape, offset }, Some(origin)) = (thread.anchor, origin)
+                && shape == id
+            {
+                thread.anchor = Anchor::Point(origin + offset);
+            }
+        }
+        self.index.remove(&id);
+        self.unindexed.remove(&id);
+        self.shapes.remove(&id)
+    }
+
+    pub fn get(&self, id: ShapeId) -> Option<&Shape> {
+        self.shapes.get(&id)
+    }
+
+    /// Iterates over all shapes in drawing order (bottom to top).
+    pub fn iter(&self) -> impl Iterator<Item = (ShapeId, &Shape)> {
+        self.shapes.iter().map(|(id, shape)| (*id, shape))
+    }
+
+    /// Returns the topmost shape at most `tolerance` away from the given world point.
+    pub fn shape_at(&self, point: Point, tolerance: f32) -> Option<ShapeId> {
+        let area = Rectangle::new(point, Size::ZERO).expand(tolerance);
+        self.index
+            .query_rect(area)
+            .into_iter()
+            .chain(self.unindexed.iter().copied())
+            .filter(|id| self.shapes[id].hit(point, tolerance))
+            .max()
+    }
+
+    /// Returns the shapes whose bounding box intersects the given world rectangle, in drawing
+    /// order. Used to skip shapes outside the viewport. Unindexed shapes are always returned,
+    /// their bounds can't tell.
+    pub fn shapes_in(&self, rect: Rectangle) -> Vec<ShapeId> {
+        let mut ids = self.index.query_rect(rect);
+        ids.extend(&self.unindexed);
+        ids.sort();
+        ids
+    }
+
+    /// Returns the shapes that lie completely inside the given world rectangle, in drawing order.
+    pub fn shapes_within(&self, rect: Rectangle) -> Vec<ShapeId> {
+        let mut ids = self.index.query_within(rect);
+        ids.sort();
+        ids
+    }
+
+    /// Iterates over the frames in drawing order.
+    pub fn frames(&self) -> impl Iterator<Item = (ShapeId, &FrameState)> {
+        self.iter().filter_map(|(id, shape)| match shape {
+            Shape::Frame(state) => Some((id, state)),
+            _ => None,
+        })
+    }
+
+    /// Returns the bounding box of the given shapes (or None if the list is empty).
+    pub fn bounds_of(&self, ids: impl IntoIterator<Item = ShapeId>) -> Option<Rectangle> {
+        ids.into_iter()
+            .filter_map(|id| self.get(id))
+            .map(Shape::bounds)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Returns the bounding box of all shapes (or None if the document is empty).
+    pub fn bounds(&self) -> Option<Rectangle> {
+        self.bounds_of(self.shapes.keys().copied())
+    }
+
+    /// Adds a comment thread and returns its id.
+    pub fn add_thread(&mut self, thread: Thread) -> ThreadId {
+        let id = ThreadId(self.next_thread_id);
+        self.next_thread_id += 1;
+        self.threads.insert(id, thread);
+        id
+    }
+
+    pub fn remove_thread(&mut self, id: ThreadId) -> Option<Thread> {
+        self.threads.remove(&id)
+    }
+
+    pub fn thread(&self, id: ThreadId) -> Option<&Thread> {
+        self.threads.get(&id)
+    }
+
+    pub fn thread_mut(&mut self, id: ThreadId) -> Option<&mut Thread> {
+        self.threads.get_mut(&id)
+    }
+
+    /// Iterates over all threads in the order they were added.
+    pub fn threads(&self) -> impl Iterator<Item = (ThreadId, &Thread)> {
+        self.threads.iter().map(|(id, thread)| (*id, thread))
+    }
+
+    /// Returns an anchor for a pin at the given world point, attached to the shape if there is
+    /// one.
+    pub fn anchor(&self, position: Point, shape: Option<ShapeId>) -> Anchor {
+        match shape.and_then(|id| Some((id, self.get(id)?.bounds()))) {
+            Some((shape, bounds)) => Anchor::Shape {
+                shape,
+                offset: position - bounds.position(),
+            },
+            None => Anchor::Point(position),
+        }
+    }
+
+    /// The world position of a thread's pin.
+    pub fn thread_position(&self, thread: &Thread) -> Point {
+        match thread.anchor {
+            Anchor::Point(point) => point,
+            Anchor::Shape { shape, offset } => self
+                .get(shape)
+                .map(|shape| shape.bounds().position() + offset)
+                .unwrap_or(Point::ORIGIN + offset),
+        }
+    }
+
+    /// Returns the last added thread whose pin is at most `radius` away from the point.
+    /// Resolved threads are only found with `include_resolved`.
+    pub fn thread_at(&self, point: Point, radius: f32, include_resolved: bool) -> Option<ThreadId> {
+        self.threads()
+            .filter(|(_, thread)| include_resolved || !thread.resolved)
+            .filter(|(_, thread)| self.thread_position(thread).distance(point) <= radius)
+            .map(|(id, _)| id)
+            .last()
+    }
+}
+
+impl<S: Into<Shape>> FromIterator<S> for Document {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut document = Document::default();
+        for shape in iter {
+            document.add(shape);
+        }
+        document
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(x: f32, y: f32) -> FrameState {
+        FrameState::new(
+            "Frame",
+            Rectangle::new(Point::new(x, y), Size::new(1.0e7, 1.0e7)),
+        )
+    }
+
+    #[test]
+    fn shapes_with_non_finite_bounds_are_not_lost() {
+        let mut document = Document::default();
+        let other = document.add(frame(0.0, 0.0));
+        let id = document.add(frame(f32::NAN, 0.0));
+        assert!(document.get(id).is_some());
+        // culling can't tell where the shape is, so it is always returned
+        let far_away = Rectangle::new(Point::new(-1.0e8, -1.0e8), Size::new(10.0, 10.0));
+        assert_eq!(document.shapes_in(far_away), vec![id]);
+
+        // a valid position puts the shape back into the index
+        document.update(id, frame(2.0e7, 0.0).into());
+        assert_eq!(document.shape_at(Point::new(2.0e7, 0.0), 1.0), Some(id));
+        assert_eq!(document.shapes_in(far_away), vec![]);
+
+        document.update(id, frame(f32::INFINITY, 0.0).into());
+        assert_eq!(document.shapes_in(far_away), vec![id]);
+        assert_eq!(document.shape_at(Point::new(0.0, 0.0), 1.0), Some(other));
+
+        document.remove(id);
+        assert_eq!(document.shapes_in(far_away), vec![]);
+        assert_eq!(document.len(), 1);
+    }
+
+    #[test]
+    fn shapes_beyond_the_index_can_be_selected() {
+        let mut document = Document::default();
+        let corner = Point::new(1.0e13, -1.0e13);
+        let id = document.add(frame(corner.x, corner.y));
+        assert_eq!(document.shape_at(corner, 1.0), Some(id));
+        assert_eq!(document.shape_at(Point::ORIGIN, 1.0), None);
+    }
+}
@@ -0,0 +1,184 @@
+//
+// Export of a single frame as PNG, SVG or PDF.
+//
+// Concept:
+//  - The SVG export of the frame (see svg_export::frame_to_svg) is the source for all formats,
+//    so the files look the same. PNG and PDF rasterize it with resvg.
+//  - Rasters are rendered at `RASTER_SCALE` pixels per world unit, so they stay sharp on high
+//    resolution screens and in print.
+//  - The PDF is written by hand: one page the size of the frame (one world unit is one point)
+//    with the raster as a compressed image. This avoids a PDF library for a single image page.
+//  - The file is named after the number and the name of the frame and written next to the board
+//    file. The number keeps frames with the same name from overwriting each other's exports.
+//
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use iced::Rectangle;
+use resvg::{tiny_skia, usvg};
+
+use crate::document::Document;
+use crate::svg_export;
+
+/// Pixels per world unit of PNG and PDF exports.
+const RASTER_SCALE: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Png,
+    Svg,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Png, ExportFormat::Svg, ExportFormat::Pdf];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Svg => "svg",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.extension().to_uppercase())
+    }
+}
+
+/// The file for a frame export: the number of the frame (1 for the first frame) and its name
+/// (reduced to characters that are safe in file names) in the directory of the board file.
+pub fn export_path(board_file: &Path, number: usize, name: &str, format: ExportFormat) -> PathBuf {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let stem = if stem.is_empty() { "frame" } else { &stem };
+    board_file.with_file_name(format!("{}-{}.{}", number, stem, format.extension()))
+}
+
+/// Writes the part of the document inside the frame rectangle to `path`.
+pub fn export(
+    document: &Document,
+    rect: Rectangle,
+    format: ExportFormat,
+    path: &Path,
+) -> io::Result<()> {
+    let svg = svg_export::frame_to_svg(document, rect);
+    match format {
+        ExportFormat::Svg => std::fs::write(path, svg),
+        ExportFormat::Png => {
+            let png = rasterize(&svg, rect)?
+                .encode_png()
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            std::fs::write(path, png)
+        }
+        ExportFormat::Pdf => std::fs::write(path, pdf(&rasterize(&svg, rect)?, rect)),
+    }
+}
+
+/// Renders the frame SVG at `RASTER_SCALE`.
+fn rasterize(svg: &str, rect: Rectangle) -> io::Result<tiny_skia::Pixmap> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(svg, &options)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let width = (rect.width * RASTER_SCALE).ceil().max(1.0) as u32;
+    let height = (rect.height * RASTER_SCALE).ceil().max(1.0) as u32;
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "frame too large to export"))?;
+    // the tree is in the units of the width and height attributes, i.e. the frame size
+    let scale = width as f32 / tree.size().width();
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    Ok(pixmap)
+}
+
+/// A PDF with one page of the frame size showing the raster.
+fn pdf(pixmap: &tiny_skia::Pixmap, rect: Rectangle) -> Vec<u8> {
+    // the background is white, so dropping the alpha channel doesn't change the colors
+    let rgb: Vec<u8> = pixmap
+        .data()
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect();
+    let image = miniz_oxide::deflate::compress_to_vec_zlib(&rgb, 6);
+
+    let (width, height) = (rect.width, rect.height);
+    let mut content = String::new();
+    let _ = write!(content, "q {} 0 0 {} 0 0 cm /Im0 Do Q", width, height);
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, dictionary: String, stream: Option<&[u8]>| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\n", offsets.len(), dictionary).as_bytes());
+        if let Some(stream) = stream {
+            pdf.extend_from_slice(b"stream\n");
+            pdf.extend_from_slice(stream);
+            pdf.extend_from_slice(b"\nendstream\n");
+        }
+        pdf.extend_from_slice(b"endobj\n");
+    };
+    object(&mut pdf, "<< /Type /Catalog /Pages 2 0 R >>".into(), None);
+    object(
+        &mut pdf,
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".into(),
+        None,
+    );
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>",
+            width, height
+        ),
+        None,
+    );
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+             /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>",
+            pixmap.width(),
+            pixmap.height(),
+            image.len()
+        ),
+        Some(&image),
+    );
+    object(
+        &mut pdf,
+        format!("<< /Length {} >>", content.len()),
+        Some(content.as_bytes()),
+    );
+
+    let xref = pdf.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+    for offset in &offsets {
+        // every entry is 20 bytes, including the space before the newline
+        let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        trailer,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        offsets.len() + 1,
+        xref
+    );
+    pdf.extend_from_slice(trailer.as_bytes());
+    pdf
+}
@@ -25,44 +25,978 @@ Longterm concept:
 
 */
 
+use std::collections::BTreeSet;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-use iced::widget::{Canvas, column, text};
-use iced::{Alignment, Length, Point};
+use iced::futures::channel::oneshot;
+use iced::widget::{
+    Canvas, Column, button, column, mouse_area, pick_list, row, scrollable, text, text_input,
+    toggler,
+};
+use iced::{
+    Alignment, Length, Point, Rectangle, Size, Subscription, Task, Theme, Vector, keyboard, mouse,
+};
+use iced_hello_world::boolean_ops::{BooleanOp, boolean_paths};
+use iced_hello_world::ink;
+use iced_hello_world::recognize::{Recognized, recognize};
+use iced_hello_world::theme;
 
+mod board_file;
+mod comment;
+mod document;
+use comment::{Comment, Thread};
+use document::{Document, ShapeId, ThreadId};
+mod frame_export;
 mod message;
-use message::Message;
+use message::{Message, Tool, ZoomPreset};
+mod minimap;
+mod placement;
 mod program_canvas;
+mod render_layers;
+mod shape;
+mod shape_frame;
+mod shape_image;
+mod shape_ink;
+mod shape_path;
 mod shape_rectangle;
-use program_canvas::CanvasProgram;
+mod shape_svg;
+mod stencil;
+mod svg_export;
+mod zoom_animation;
+use frame_export::ExportFormat;
+use minimap::Minimap;
+use program_canvas::{CanvasProgram, PanZoomState};
+use render_layers::RenderLayers;
+use shape::Shape;
+use shape_frame::FrameState;
+use shape_image::{ImageData, ImageState};
+use shape_ink::InkState;
+use shape_path::PathState;
+use shape_rectangle::RectangleState;
+use shape_svg::SvgState;
+use stencil::{COMPONENTS_FILE, Stencil, StencilLibrary, StencilPreview};
+use zoom_animation::ZoomAnimation;
 
 // use sweeten::widget::mouse_area;
 
+/// Free space in pixels around the shapes when zooming to fit.
+const FIT_MARGIN: f32 = 40.0;
+/// Recognized rectangles rotated less than this (in degrees) become plain rectangles.
+const AXIS_ALIGNED_DEGREES: f32 = 3.0;
+/// Maximum distance (in world units) between curves and their flattened outlines in boolean
+/// operations.
+const FLATTEN_TOLERANCE: f32 = 0.25;
+/// File used by Open and Save if no `--file` is given.
+const DEFAULT_FILE: &str = "creator_board.json";
+/// Directory with additional stencil libraries if no `--library-dir` is given.
+const DEFAULT_LIBRARY_DIR: &str = "stencils";
+/// Width of the stencil panel in pixels.
+const STENCIL_PANEL_WIDTH: f32 = 160.0;
+/// Inserted images cover at most this part of the view.
+const MAX_IMAGE_FRACTION: f32 = 0.8;
+/// Width of the frames panel in pixels.
+const FRAMES_PANEL_WIDTH: f32 = 220.0;
+/// Free space in world units between the selected shapes and a new frame around them.
+const FRAME_MARGIN: f32 = 20.0;
+/// Size in pixels of a new frame if nothing is selected (16:9).
+const DEFAULT_FRAME_SIZE: Size = Size::new(640.0, 360.0);
+/// Free space in pixels around the frame shown in presentation mode.
+const PRESENTATION_MARGIN: f32 = 10.0;
+/// Width of the comment thread panel in pixels.
+const THREAD_PANEL_WIDTH: f32 = 240.0;
+
+/// Command line options:
+/// - `--bench-scene <N>`: start with N rectangles instead of the default scene
+/// - `--no-cache`: redraw the static layer on every frame (to compare the draw times)
+/// - `--frame-stats`: write the average draw time per frame to stderr
+/// - `--file <PATH>`: the board file for Open and Save (the SVG export goes next to it)
+/// - `--library-dir <DIR>`: directory with additional stencil libraries (JSON files), saved
+///   components go to `components.json` in it
+/// - `--author <NAME>`: the name written to comments (defaults to the user name)
+/// - `--palettes <FILE>`: JSON file with additional color palettes (default `palettes.json`)
+/// - `--theme <NAME>`: the theme at start, e.g. "Dark"
 fn main() -> iced::Result {
+    let mut bench_scene = None;
+    let mut caching = true;
+    let mut frame_stats = false;
+    let mut file = PathBuf::from(DEFAULT_FILE);
+    let mut library_dir = PathBuf::from(DEFAULT_LIBRARY_DIR);
+    let mut author = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    let mut palette_file = PathBuf::from(theme::DEFAULT_PALETTE_FILE);
+    let mut theme_name = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bench-scene" => bench_scene = args.next().and_then(|n| n.parse().ok()),
+            "--no-cache" => caching = false,
+            "--frame-stats" => frame_stats = true,
+            "--file" => file = args.next().map(PathBuf::from).unwrap_or(file),
+            "--library-dir" => library_dir = args.next().map(PathBuf::from).unwrap_or(library_dir),
+            "--author" => author = args.next().unwrap_or(author),
+            "--palettes" => palette_file = args.next().map(PathBuf::from).unwrap_or(palette_file),
+            "--theme" => theme_name = args.next(),
+            _ => eprintln!("ignoring unknown argument {}", arg),
+        }
+    }
+
+    let (themes, theme) = theme::select_theme(&palette_file, theme_name.as_deref());
     // CreatorApp::run(Settings::default())
     iced::application("Creator", CreatorApp::update, CreatorApp::view)
-        //    // .subscription(MyApp::subscription)
-        .run()
+        .subscription(CreatorApp::subscription)
+        .theme(CreatorApp::theme)
+        .run_with(move || {
+            let mut app = match bench_scene {
+                Some(count) => CreatorApp::with_bench_scene(count),
+                None => CreatorApp::default(),
+            };
+            app.layers = RenderLayers::new(caching).report_stats(frame_stats);
+            app.file = file;
+            app.libraries = stencil::libraries(&library_dir);
+            app.library_dir = library_dir;
+            app.author = author;
+            app.themes = themes;
+            app.theme = theme;
+            (app, Task::none())
+        })
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct CreatorApp {
     // canvas_cache: Cache,
     // canvas_programs: Vec<Box<dyn Program<Message, State = ()>>>,
     cursor_pos: Point,
+    pan_zoom_state: PanZoomState,
+    document: Document,
+    selection: BTreeSet<ShapeId>,
+    viewport_size: Size,
+    zoom_animation: Option<ZoomAnimation>,
+    show_minimap: bool,
+    /// Replace pen strokes that look like lines, arrows or simple shapes by these shapes
+    smart_ink: bool,
+    minimap: Minimap,
+    layers: RenderLayers,
+    tool: Tool,
+    /// The board file for Open and Save
+    file: PathBuf,
+    /// Content of the image file field
+    image_path: String,
+    /// Embed inserted images in the board file instead of referring to the image files
+    embed_images: bool,
+    show_stencils: bool,
+    /// The built-in stencil libraries and the ones loaded from `library_dir`
+    libraries: Vec<StencilLibrary>,
+    /// Index of the library shown in the stencil panel
+    library: usize,
+    /// Library and stencil index of the stencil dragged from the panel
+    dragged_stencil: Option<(usize, usize)>,
+    /// Name for the next component saved from the selection
+    component_name: String,
+    library_dir: PathBuf,
+    show_frames: bool,
+    /// The frame that is moved and the shapes inside of it when the move started
+    frame_drag: Option<(ShapeId, Vec<ShapeId>)>,
+    /// Index of the frame shown in presentation mode (frames in drawing order)
+    presentation: Option<usize>,
+    /// Name written to new comments
+    author: String,
+    /// The comment thread shown in the thread panel
+    open_thread: Option<ThreadId>,
+    /// Content of the comment field of the thread panel
+    comment_draft: String,
+    /// Show the pins of resolved threads
+    show_resolved: bool,
+    /// Light, dark and the palettes of the palette file
+    themes: Vec<Theme>,
+    theme: Theme,
+    /// Outcome of the last file operation, shown in the status line
+    status: Option<Status>,
+}
+
+/// A message in the status line.
+#[derive(Debug, Clone)]
+enum Status {
+    Info(String),
+    Error(String),
+}
+
+impl Default for CreatorApp {
+    fn default() -> Self {
+        Self {
+            cursor_pos: Point::ORIGIN,
+            pan_zoom_state: PanZoomState::default(),
+            document: Document::from_iter([
+                RectangleState::default(),
+                RectangleState::with_dimensions(Point::new(400.0, 300.0), 150.0, 80.0),
+                RectangleState::with_dimensions(Point::new(-250.0, 500.0), 60.0, 120.0),
+            ]),
+            selection: BTreeSet::new(),
+            viewport_size: Size::ZERO,
+            zoom_animation: None,
+            show_minimap: true,
+            smart_ink: false,
+            minimap: Minimap::default(),
+            layers: RenderLayers::default(),
+            tool: Tool::default(),
+            file: PathBuf::from(DEFAULT_FILE),
+            image_path: String::new(),
+            embed_images: true,
+            show_stencils: true,
+            libraries: vec![
+                stencil::flowchart(),
+                stencil::architecture(),
+                stencil::curves(),
+            ],
+            library: 0,
+            dragged_stencil: None,
+            component_name: String::new(),
+            library_dir: PathBuf::from(DEFAULT_LIBRARY_DIR),
+            show_frames: true,
+            frame_drag: None,
+            presentation: None,
+            author: String::new(),
+            open_thread: None,
+            comment_draft: String::new(),
+            show_resolved: false,
+            themes: vec![Theme::Light, Theme::Dark],
+            theme: Theme::Light,
+            status: None,
+        }
+    }
 }
 
 impl CreatorApp {
-    fn update(&mut self, _message: Message) {
+    /// Creates a board with `count` rectangles laid out in a square grid, to measure the
+    /// rendering performance on large boards.
+    fn with_bench_scene(count: usize) -> Self {
+        let columns = (count as f32).sqrt().ceil().max(1.0) as usize;
+        let document = (0..count)
+            .map(|i| {
+                let position = Point::new((i % columns) as f32 * 80.0, (i / columns) as f32 * 60.0);
+                RectangleState::with_dimensions(position, 60.0, 40.0)
+            })
+            .collect();
+        Self {
+            document,
+            ..Self::default()
+        }
+    }
+
+    /// Shows the outcome of a successful operation in the status line.
+    fn show_info(&mut self, message: String) {
+        self.status = Some(Status::Info(message));
+    }
+
+    /// Shows an error in the status line and writes it to stderr.
+    fn show_error(&mut self, message: String) {
+        eprintln!("{}", message);
+        self.status = Some(Status::Error(message));
+    }
+
+    fn update(&mut self, _message: Message) -> Task<Message> {
         match _message {
             Message::CanvasMouseMoved(cursor_pos) => {
                 self.cursor_pos = cursor_pos;
             }
+            Message::ViewportResized(size) => {
+                self.viewport_size = size;
+                // the canvas grows when the controls are hidden, keep the frame filling it
+                if let Some(index) = self.presentation {
+                    self.present_frame(index);
+                }
+            }
+            Message::PanZoomChanged(pan_zoom_state) => {
+                // user interaction wins over a running animation
+                self.zoom_animation = None;
+                if pan_zoom_state.viewport.translation != self.pan_zoom_state.viewport.translation
+                    || pan_zoom_state.viewport.scale != self.pan_zoom_state.viewport.scale
+                {
+                    self.layers.invalidate();
+                }
+                self.pan_zoom_state = pan_zoom_state;
+            }
+            Message::ShapeChanged(id, shape) => {
+                self.move_frame_contents(id, &shape);
+                self.document.update(id, shape);
+                // the selected shapes are drawn on the dynamic layer, so moving one of them
+                // doesn't touch the static layer - but selecting another one does
+                if !self.selection.contains(&id) {
+                    self.selection = BTreeSet::from([id]);
+                    self.layers.invalidate();
+                }
+                self.minimap.invalidate();
+            }
+            Message::SelectShapes(ids) => {
+                self.selection = ids.into_iter().collect();
+                self.layers.invalidate();
+            }
+            Message::SelectTool(tool) => {
+                self.tool = tool;
+            }
+            Message::AddRectangle => {
+                let center = self.pan_zoom_state.viewport.center(self.viewport_size);
+                let id = self.document.add(RectangleState::with_dimensions(
+                    Point::new(center.x - 50.0, center.y - 25.0),
+                    100.0,
+                    50.0,
+                ));
+                self.selection = BTreeSet::from([id]);
+                self.layers.invalidate();
+                self.minimap.invalidate();
+            }
+            Message::AddPath => {
+                let center = self.pan_zoom_state.viewport.center(self.viewport_size);
+                let id = self.document.add(PathState::sample(center));
+                self.selection = BTreeSet::from([id]);
+                self.layers.invalidate();
+                self.minimap.invalidate();
+            }
+            Message::AddInk(stroke) => {
+                let shape = if self.smart_ink {
+                    recognized_shape(&stroke).unwrap_or(Shape::Ink(stroke))
+                } else {
+                    Shape::Ink(stroke)
+                };
+                self.document.add(shape);
+                self.layers.invalidate();
+                self.minimap.invalidate();
+            }
+            Message::Erase {
+                center,
+                radius,
+                whole_strokes,
+            } => self.erase(center, radius, whole_strokes),
+            Message::DeleteSelection => {
+                // with the node tool the delete key removes the selected node
+                if self.tool == Tool::Node
+                    && self
+                        .selected_path()
+                        .is_some_and(|(_, path)| path.selected_node.is_some())
+                {
+                    return self.update(Message::DeleteNode);
+                }
+                for id in std::mem::take(&mut self.selection) {
+                    self.document.remove(id);
+                }
+                self.layers.invalidate();
+                self.minimap.invalidate();
+            }
+            Message::DeleteNode => {
+                self.edit_selected_path(|state| {
+                    if let Some(index) = state.selected_node.take() {
+                        state.path.delete_node(index);
+                    }
+                });
+            }
+            Message::ToggleSmooth => {
+                self.edit_selected_path(|state| {
+                    if let Some(index) = state.selected_node {
+                        state.path.toggle_smooth(index);
+                    }
+                });
+            }
+            Message::ToggleClosed => {
+                self.edit_selected_path(|state| {
+                    let closed = state.path.is_closed();
+                    state.path.set_closed(!closed);
+                });
+            }
+            Message::Combine(op) => self.combine_selection(op),
+            Message::Open => match board_file::load(&self.file) {
+                Ok(document) => {
+                    self.show_info(format!("Opened {}", self.file.display()));
+                    self.document = document;
+                    self.selection.clear();
+                    self.open_thread = None;
+                    self.layers.invalidate();
+                    self.minimap.invalidate();
+                }
+                Err(e) => self.show_error(format!("Could not open {}: {}", self.file.display(), e)),
+            },
+            Message::Save => match board_file::save(&self.document, &self.file) {
+                Ok(()) => self.show_info(format!("Saved {}", self.file.display())),
+                Err(e) => self.show_error(format!("Could not save {}: {}", self.file.display(), e)),
+            },
+            Message::ExportSvg => {
+                let path = self.file.with_extension("svg");
+                match std::fs::write(&path, svg_export::to_svg(&self.document)) {
+                    Ok(()) => self.show_info(format!("Exported {}", path.display())),
+                    Err(e) => {
+                        self.show_error(format!("Could not export {}: {}", path.display(), e))
+                    }
+                }
+            }
+            Message::ImagePathChanged(image_path) => {
+                self.image_path = image_path;
+            }
+            Message::ChooseImage => {
+                let dialog = rfd::AsyncFileDialog::new()
+                    .set_title("Insert image")
+                    .add_filter("Images", &["png", "jpg", "jpeg", "svg"])
+                    .pick_file();
+                return Task::perform(dialog, |file| file.map(|file| file.path().to_path_buf()))
+                    .and_then(|path| Task::done(Message::InsertImage(path)));
+            }
+            Message::InsertImage(path) => {
+                self.show_info(format!("Loading {}", path.display()));
+                return Task::perform(load_image(path.clone(), self.embed_images), move |result| {
+                    Message::ImageLoaded(path.clone(), result)
+                });
+            }
+            Message::ImageLoaded(path, result) => match result {
+                Ok(mut shape) => {
+                    match &mut shape {
+                        Shape::Image(state) => {
+                            state.placement.rect = self.insert_rect(state.placement.rect.size())
+                        }
+                        Shape::Svg(state) => {
+                            state.placement.rect = self.insert_rect(state.placement.rect.size())
+                        }
+                        _ => {}
+                    }
+                    let id = self.document.add(shape);
+                    self.selection = BTreeSet::from([id]);
+                    self.layers.invalidate();
+                    self.minimap.invalidate();
+                    self.show_info(format!("Inserted {}", path.display()));
+                }
+                Err(e) => self.show_error(format!("Could not open {}: {}", path.display(), e)),
+            },
+            Message::ToggleEmbedImages => {
+                self.embed_images = !self.embed_images;
+            }
+            Message::ToggleStencils => {
+                self.show_stencils = !self.show_stencils;
+            }
+            Message::SelectLibrary(name) => {
+                if let Some(index) = self.libraries.iter().position(|l| l.name == name) {
+                    self.library = index;
+                }
+            }
+            Message::StartStencilDrag { library, stencil } => {
+                self.dragged_stencil = Some((library, stencil));
+            }
+            Message::DropStencil(position) => {
+                if let Some(stencil) = self.dragged_stencil.take().and_then(|(l, s)| {
+                    self.libraries
+                        .get(l)
+                        .and_then(|library| library.stencils.get(s))
+                }) {
+                    let shapes = stencil.placed_at(position);
+                    self.selection = shapes
+                        .into_iter()
+                        .map(|shape| self.document.add(shape))
+                        .collect();
+                    self.layers.invalidate();
+                    self.minimap.invalidate();
+                }
+            }
+            Message::CancelStencilDrag => {
+                self.dragged_stencil = None;
+            }
+            Message::ComponentNameChanged(name) => {
+                self.component_name = name;
+            }
+            Message::SaveComponent => self.save_component(),
+            Message::AddFrame => {
+                let rect = match self.document.bounds_of(self.selection.iter().copied()) {
+                    Some(bounds) => bounds.expand(FRAME_MARGIN),
+                    None => self.insert_rect(DEFAULT_FRAME_SIZE),
+                };
+                let name = format!("Frame {}", self.document.frames().count() + 1);
+                let id = self.document.add(FrameState::new(name, rect));
+                self.selection = BTreeSet::from([id]);
+                self.layers.invalidate();
+                self.minimap.invalidate();
+            }
+            Message::ToggleFrames => {
+                self.show_frames = !self.show_frames;
+            }
+            Message::RenameFrame(id, name) => {
+                if let Some(Shape::Frame(frame)) = self.document.get(id) {
+                    let mut frame = frame.clone();
+                    frame.name = name;
+                    self.document.update(id, Shape::Frame(frame));
+                    self.layers.invalidate();
+                }
+            }
+            Message::EditFrameName(id) => {
+                self.show_frames = true;
+                let input = frame_name_input(id);
+                return text_input::focus(input.clone()).chain(text_input::select_all(input));
+            }
+            Message::ZoomToFrame(id) => {
+                let rect = match self.document.get(id) {
+                    Some(Shape::Frame(frame)) => Some(frame.rect),
+                    _ => None,
+                };
+                self.zoom_to_rect(rect);
+            }
+            Message::ExportFrame(id, format) => {
+                let exported = self
+                    .document
+                    .frames()
+                    .enumerate()
+                    .find(|(_, (frame_id, _))| *frame_id == id)
+                    .map(|(index, (_, frame))| {
+                        let path =
+                            frame_export::export_path(&self.file, index + 1, &frame.name, format);
+                        let result =
+                            frame_export::export(&self.document, frame.rect, format, &path);
+                        (path, result)
+                    });
+                match exported {
+                    Some((path, Ok(()))) => self.show_info(format!("Exported {}", path.display())),
+                    Some((path, Err(e))) => {
+                        self.show_error(format!("Could not export {}: {}", path.display(), e))
+                    }
+                    None => {}
+                }
+            }
+            Message::StartPresentation => {
+                if self.document.frames().next().is_some() {
+                    self.selection.clear();
+                    self.layers.invalidate();
+                    self.present_frame(0);
+                }
+            }
+            Message::NextFrame => {
+                if let Some(index) = self.presentation {
+                    let last = self.document.frames().count().saturating_sub(1);
+                    self.present_frame((index + 1).min(last));
+                }
+            }
+            Message::PreviousFrame => {
+                if let Some(index) = self.presentation {
+                    self.present_frame(index.saturating_sub(1));
+                }
+            }
+            Message::EndPresentation => {
+                self.presentation = None;
+                self.layers.invalidate();
+            }
+            Message::PlaceComment { position, shape } => {
+                self.close_thread();
+                let anchor = self.document.anchor(position, shape);
+                self.open_thread = Some(self.document.add_thread(Thread::new(anchor)));
+            }
+            Message::OpenThread(id) => {
+                if self.open_thread != Some(id) {
+                    self.close_thread();
+                    self.open_thread = Some(id);
+                }
+                return text_input::focus(comment_input());
+            }
+            Message::CloseThread => self.close_thread(),
+            Message::AuthorChanged(author) => {
+                self.author = author;
+            }
+            Message::CommentDraftChanged(draft) => {
+                self.comment_draft = draft;
+            }
+            Message::PostComment => {
+                let text = self.comment_draft.trim();
+                let author = match self.author.trim() {
+                    "" => "Anonymous",
+                    author => author,
+                };
+                if !text.is_empty()
+                    && let Some(thread) =
+                        self.open_thread.and_then(|id| self.document.thread_mut(id))
+                {
+                    thread.comments.push(Comment::new(author, text));
+                    self.comment_draft.clear();
+                }
+            }
+            Message::ResolveThread(id, resolved) => {
+                if let Some(thread) = self.document.thread_mut(id) {
+                    thread.resolved = resolved;
+                }
+            }
+            Message::DeleteThread(id) => {
+                self.document.remove_thread(id);
+                if self.open_thread == Some(id) {
+                    self.open_thread = None;
+                    self.comment_draft.clear();
+                }
+            }
+            Message::ToggleResolvedComments => {
+                self.show_resolved = !self.show_resolved;
+            }
+            Message::ThemeChanged(theme) => {
+                self.theme = theme;
+                // the cached layers are drawn with the colors of the old theme
+                self.layers.invalidate();
+                self.minimap.invalidate();
+            }
+            Message::ZoomIn => {
+                let scale = self.pan_zoom_state.viewport.scale * 1.25;
+                self.zoom_centered(scale);
+            }
+            Message::ZoomOut => {
+                let scale = self.pan_zoom_state.viewport.scale / 1.25;
+                self.zoom_centered(scale);
+            }
+            Message::ZoomTo(preset) => {
+                self.zoom_centered(preset.scale());
+            }
+            Message::ZoomToFit => {
+                self.zoom_to_rect(self.document.bounds());
+            }
+            Message::ZoomToSelection => {
+                let bounds = self.document.bounds_of(self.selection.iter().copied());
+                self.zoom_to_rect(bounds);
+            }
+            Message::ToggleMinimap => {
+                self.show_minimap = !self.show_minimap;
+            }
+            Message::ToggleSmartInk => {
+                self.smart_ink = !self.smart_ink;
+            }
+            Message::AnimationTick(now) => {
+                if let Some(animation) = self.zoom_animation {
+                    let (pan_zoom_state, finished) = animation.state_at(now, self.viewport_size);
+                    self.pan_zoom_state = pan_zoom_state;
+                    self.layers.invalidate();
+                    if finished {
+                        self.zoom_animation = None;
+                    }
+                } else if self.pan_zoom_state.glide(now) {
+                    self.layers.invalidate();
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Returns the first selected path.
+    fn selected_path(&self) -> Option<(ShapeId, &PathState)> {
+        self.selection
+            .iter()
+            .find_map(|id| match self.document.get(*id) {
+                Some(Shape::Path(state)) => Some((*id, state)),
+                _ => None,
+            })
+    }
+
+    /// Applies an edit to the first selected path. Does nothing if no path is selected.
+    fn edit_selected_path(&mut self, edit: impl FnOnce(&mut PathState)) {
+        if let Some((id, state)) = self.selected_path() {
+            let mut state = state.clone();
+            edit(&mut state);
+            self.document.update(id, Shape::Path(state));
+            self.minimap.invalidate();
+        }
+    }
+
+    /// Removes the parts of the strokes inside the circle. The first remaining piece of a split
+    /// stroke keeps its id, the others are added on top.
+    fn erase(&mut self, center: Point, radius: f32, whole_strokes: bool) {
+        let area = Rectangle::new(center, Size::ZERO).expand(radius);
+        let mut changed = false;
+        for id in self.document.shapes_in(area) {
+            let Some(Shape::Ink(stroke)) = self.document.get(id) else {
+                continue;
+            };
+            if !stroke.hit(center, radius) {
+                continue;
+            }
+            let pieces = if whole_strokes {
+                Vec::new()
+            } else {
+                ink::erase(&stroke.points, center, radius)
+            };
+            let mut pieces = pieces.into_iter().map(InkState::new);
+            match pieces.next() {
+                Some(first) => self.document.update(id, Shape::Ink(first)),
+                None => {
+                    self.document.remove(id);
+                    self.selection.remove(&id);
+                }
+            }
+            for piece in pieces {
+                self.document.add(piece);
+            }
+            changed = true;
+        }
+        if changed {
+            self.layers.invalidate();
+            self.minimap.invalidate();
+        }
+    }
+
+    /// Replaces the selected shapes by one path. The shapes are combined in drawing order, so
+    /// for a difference the bottom shape is the one that gets cut.
+    fn combine_selection(&mut self, op: BooleanOp) {
+        if self.selection.len() < 2 {
+            return;
+        }
+        let combined = self
+            .selection
+            .iter()
+            .filter_map(|id| self.document.get(*id))
+            .map(Shape::to_path)
+            .reduce(|result, path| boolean_paths(&result, &path, op, FLATTEN_TOLERANCE));
+        for id in std::mem::take(&mut self.selection) {
+            self.document.remove(id);
+        }
+        // an empty result (e.g. the intersection of disjoint shapes) just removes the shapes
+        if let Some(path) = combined.filter(|path| !path.segments.is_empty()) {
+            let id = self.document.add(PathState::new(path));
+            self.selection.insert(id);
+        }
+        self.layers.invalidate();
+        self.minimap.invalidate();
+    }
+
+    /// Adds the selected shapes as a component to the user library and saves the library. The
+    /// user library is created on the first component.
+    fn save_component(&mut self) {
+        let shapes: Vec<Shape> = self
+            .selection
+            .iter()
+            .filter_map(|id| self.document.get(*id))
+            .cloned()
+            .collect();
+        if shapes.is_empty() {
+            return;
+        }
+        let path = self.library_dir.join(COMPONENTS_FILE);
+        let index = match self
+            .libraries
+            .iter()
+            .position(|library| library.file.as_deref() == Some(path.as_path()))
+        {
+            Some(index) => index,
+            None => {
+                self.libraries.push(StencilLibrary {
+                    name: "Components".to_string(),
+                    stencils: Vec::new(),
+                    file: Some(path.clone()),
+                });
+                self.libraries.len() - 1
+            }
+        };
+        let library = &mut self.libraries[index];
+        let name = match self.component_name.trim() {
+            "" => format!("Component {}", library.stencils.len() + 1),
+            name => name.to_string(),
+        };
+        library.stencils.push(Stencil::new(name, shapes));
+        match board_file::save_library(library, &path) {
+            Ok(()) => self.show_info(format!("Saved component to {}", path.display())),
+            Err(e) => self.show_error(format!("Could not save {}: {}", path.display(), e)),
+        }
+        self.library = index;
+        self.component_name.clear();
+    }
+
+    /// Moves the shapes inside a frame along with it. The shapes are collected when the move
+    /// starts, so shapes the frame passes over on the way are not picked up.
+    fn move_frame_contents(&mut self, id: ShapeId, shape: &Shape) {
+        let Shape::Frame(frame) = shape else {
+            return;
+        };
+        if !frame.is_moving() {
+            self.frame_drag = None;
+            return;
+        }
+        let Some(Shape::Frame(old)) = self.document.get(id) else {
+            return;
+        };
+        let old_rect = old.rect;
+        if self
+            .frame_drag
+            .as_ref()
+            .is_none_or(|(frame_id, _)| *frame_id != id)
+        {
+            let contents = self
+                .document
+                .shapes_within(old_rect)
+                .into_iter()
+                .filter(|other| *other != id)
+                .collect();
+            self.frame_drag = Some((id, contents));
+        }
+        let delta = frame.rect.position() - old_rect.position();
+        if delta == Vector::ZERO {
+            return;
+        }
+        if let Some((_, contents)) = &self.frame_drag {
+            for other in contents {
+                if let Some(mut shape) = self.document.get(*other).cloned() {
+                    shape.translate(delta);
+                    self.document.update(*other, shape);
+                }
+            }
+        }
+        self.layers.invalidate();
+    }
+
+    /// Closes the thread panel. A new thread without comments is removed again.
+    fn close_thread(&mut self) {
+        if let Some(id) = self.open_thread.take()
+            && self
+                .document
+                .thread(id)
+                .is_some_and(|thread| thread.comments.is_empty())
+        {
+            self.document.remove_thread(id);
         }
+        self.comment_draft.clear();
+    }
+
+    /// Shows the frame with the given index (in drawing order) in presentation mode.
+    fn present_frame(&mut self, index: usize) {
+        let rect = self
+            .document
+            .frames()
+            .nth(index)
+            .map(|(_, frame)| frame.rect);
+        if let Some(rect) = rect {
+            let target = PanZoomState::fitting(rect, self.viewport_size, PRESENTATION_MARGIN);
+            self.presentation = Some(index);
+            self.animate_to(target);
+        }
+    }
+
+    /// Returns the rectangle for an inserted image or SVG asset of the given size in pixels: in
+    /// the center of the view, one pixel per screen pixel unless it doesn't fit into the view.
+    fn insert_rect(&self, pixels: Size) -> Rectangle {
+        let mut factor = 1.0 / self.pan_zoom_state.viewport.scale;
+        if self.viewport_size.width > 0.0 && self.viewport_size.height > 0.0 {
+            let fit = (MAX_IMAGE_FRACTION * self.viewport_size.width / pixels.width)
+                .min(MAX_IMAGE_FRACTION * self.viewport_size.height / pixels.height);
+            factor *= fit.min(1.0);
+        }
+        let size = Size::new(pixels.width * factor, pixels.height * factor);
+        let center = self.pan_zoom_state.viewport.center(self.viewport_size);
+        Rectangle::new(
+            Point::new(center.x - size.width / 2.0, center.y - size.height / 2.0),
+            size,
+        )
+    }
+
+    /// Zooms to the given scale, keeping the world point in the center of the view in place.
+    fn zoom_centered(&mut self, scale: f32) {
+        let center = self.pan_zoom_state.viewport.center(self.viewport_size);
+        let target = PanZoomState::centered_on(center, scale, self.viewport_size);
+        self.animate_to(target);
+    }
+
+    /// Zooms so that the given world rectangle fills the view. Does nothing if there is no
+    /// rectangle (e.g. nothing selected).
+    fn zoom_to_rect(&mut self, world_rect: Option<Rectangle>) {
+        if let Some(world_rect) = world_rect {
+            let target = PanZoomState::fitting(world_rect, self.viewport_size, FIT_MARGIN);
+            self.animate_to(target);
+        }
+    }
+
+    fn animate_to(&mut self, target: PanZoomState) {
+        self.zoom_animation = Some(ZoomAnimation::new(
+            &self.pan_zoom_state,
+            &target,
+            self.viewport_size,
+        ));
+    }
+
+    fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let ticks = (self.zoom_animation.is_some() || self.pan_zoom_state.is_gliding())
+            .then(|| iced::time::every(Duration::from_millis(16)).map(Message::AnimationTick));
+
+        // in presentation mode only the keys that step through the frames are handled
+        if self.presentation.is_some() {
+            let keys = keyboard::on_key_press(|key, _modifiers| match key.as_ref() {
+                keyboard::Key::Named(
+                    keyboard::key::Named::ArrowRight
+                    | keyboard::key::Named::Space
+                    | keyboard::key::Named::PageDown,
+                ) => Some(Message::NextFrame),
+                keyboard::Key::Named(
+                    keyboard::key::Named::ArrowLeft | keyboard::key::Named::PageUp,
+                ) => Some(Message::PreviousFrame),
+                keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                    Some(Message::EndPresentation)
+                }
+                _ => None,
+            });
+            return Subscription::batch([Some(keys), ticks].into_iter().flatten());
+        }
+
+        let keys = keyboard::on_key_press(|key, modifiers| {
+            if let keyboard::Key::Named(
+                keyboard::key::Named::Delete | keyboard::key::Named::Backspace,
+            ) = key.as_ref()
+            {
+                return Some(Message::DeleteSelection);
+            }
+            if !modifiers.command() {
+                return None;
+            }
+            match key.as_ref() {
+                keyboard::Key::Character("+") | keyboard::Key::Character("=") => {
+                    Some(Message::ZoomIn)
+                }
+                keyboard::Key::Character("-") => Some(Message::ZoomOut),
+                keyboard::Key::Character("0") => Some(Message::ZoomTo(ZoomPreset(100))),
+                keyboard::Key::Character("1") => Some(Message::ZoomToFit),
+                keyboard::Key::Character("2") => Some(Message::ZoomToSelection),
+                keyboard::Key::Character("m") => Some(Message::ToggleMinimap),
+                keyboard::Key::Character("o") => Some(Message::Open),
+                keyboard::Key::Character("s") => Some(Message::Save),
+                _ => None,
+            }
+        });
+
+        // image and SVG files dropped onto the window are placed on the board
+        let dropped_files = iced::event::listen_with(|event, _status, _window| match event {
+            iced::Event::Window(iced::window::Event::FileDropped(path)) => {
+                Some(Message::InsertImage(path))
+            }
+            _ => None,
+        });
+
+        Subscription::batch(
+            [Some(keys), Some(dropped_files), ticks]
+                .into_iter()
+                .flatten(),
+        )
     }
 
     fn view(&self) -> iced::Element<'_, Message> {
-        let c = Canvas::new(CanvasProgram::new())
+        let mut program = CanvasProgram::new(
+            &self.layers,
+            &self.pan_zoom_state,
+            &self.document,
+            &self.selection,
+            self.viewport_size,
+        );
+        // presentation mode shows nothing but the board
+        if self.presentation.is_some() {
+            return Canvas::new(program.presenting(true))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into();
+        }
+        if self.show_minimap {
+            program = program.minimap(&self.minimap);
+        }
+        program = program
+            .comments(self.show_resolved, self.open_thread)
+            .tool(self.tool)
+            .stencil(
+                self.dragged_stencil
+                    .and_then(|(l, s)| self.libraries.get(l)?.stencils.get(s)),
+            );
+        let c = Canvas::new(program)
             .width(Length::Fill)
             .height(Length::Fill);
 
@@ -72,12 +1006,332 @@ impl CreatorApp {
         //    .on_release(Message::CanvaseMouseReleased)
         //    .on_move(Message::CanvasMouseMoved);
 
+        let zoom_percent = (self.pan_zoom_state.viewport.scale * 100.0).round() as u32;
+        let selected_path = self.selected_path().map(|(_, state)| state);
+        let selected_node = selected_path.and_then(|state| state.selected_node);
+        let edit_controls = row![
+            button(text("Open")).on_press(Message::Open),
+            button(text("Save")).on_press(Message::Save),
+            button(text("Export SVG")).on_press(Message::ExportSvg),
+            button(text("Select")).on_press_maybe(
+                (self.tool != Tool::Select).then_some(Message::SelectTool(Tool::Select))
+            ),
+            button(text("Node")).on_press_maybe(
+                (self.tool != Tool::Node).then_some(Message::SelectTool(Tool::Node))
+            ),
+            button(text("Pen"))
+                .on_press_maybe((self.tool != Tool::Pen).then_some(Message::SelectTool(Tool::Pen))),
+            button(text("Eraser")).on_press_maybe(
+                (self.tool != Tool::Eraser).then_some(Message::SelectTool(Tool::Eraser))
+            ),
+            button(text("Comment")).on_press_maybe(
+                (self.tool != Tool::Comment).then_some(Message::SelectTool(Tool::Comment))
+            ),
+            button(text("Delete node")).on_press_maybe(selected_node.map(|_| Message::DeleteNode)),
+            button(text("Smooth/Corner"))
+                .on_press_maybe(selected_node.map(|_| Message::ToggleSmooth)),
+            button(text(
+                if selected_path.is_some_and(|state| state.path.is_closed()) {
+                    "Open path"
+                } else {
+                    "Close path"
+                }
+            ))
+            .on_press_maybe(selected_path.map(|_| Message::ToggleClosed)),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center);
+
+        let can_combine = self.selection.len() >= 2;
+        let combine = |label, op| {
+            button(text(label)).on_press_maybe(can_combine.then_some(Message::Combine(op)))
+        };
+        let combine_controls = row![
+            combine("Union", BooleanOp::Union),
+            combine("Intersect", BooleanOp::Intersection),
+            combine("Subtract", BooleanOp::Difference),
+            combine("Exclude", BooleanOp::Xor),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center);
+
+        let zoom_controls = row![
+            button(text("Rectangle")).on_press(Message::AddRectangle),
+            button(text("Path")).on_press(Message::AddPath),
+            button(text("Frame")).on_press(Message::AddFrame),
+            button(text("-")).on_press(Message::ZoomOut),
+            pick_list(
+                ZoomPreset::ALL,
+                ZoomPreset::ALL.into_iter().find(|p| p.0 == zoom_percent),
+                Message::ZoomTo
+            )
+            .placeholder(format!("{}%", zoom_percent)),
+            button(text("+")).on_press(Message::ZoomIn),
+            button(text("100%")).on_press(Message::ZoomTo(ZoomPreset(100))),
+            button(text("Fit")).on_press(Message::ZoomToFit),
+            button(text("Selection"))
+                .on_press_maybe((!self.selection.is_empty()).then_some(Message::ZoomToSelection)),
+            toggler(self.show_minimap)
+                .label("Minimap")
+                .on_toggle(|_| Message::ToggleMinimap)
+                .width(Length::Shrink),
+            toggler(self.smart_ink)
+                .label("Smart ink")
+                .on_toggle(|_| Message::ToggleSmartInk)
+                .width(Length::Shrink),
+            toggler(self.show_stencils)
+                .label("Stencils")
+                .on_toggle(|_| Message::ToggleStencils)
+                .width(Length::Shrink),
+            toggler(self.show_frames)
+                .label("Frames")
+                .on_toggle(|_| Message::ToggleFrames)
+                .width(Length::Shrink),
+            pick_list(
+                self.themes.as_slice(),
+                Some(&self.theme),
+                Message::ThemeChanged
+            ),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center);
+
+        let image_controls = row![
+            text_input(
+                "Image or SVG file (or drop it onto the window)",
+                &self.image_path
+            )
+            .on_input(Message::ImagePathChanged)
+            .on_submit(Message::InsertImage(PathBuf::from(&self.image_path)))
+            .width(Length::Fixed(320.0)),
+            button(text("Insert image")).on_press_maybe(
+                (!self.image_path.is_empty())
+                    .then(|| Message::InsertImage(PathBuf::from(&self.image_path)))
+            ),
+            button(text("Choose image...")).on_press(Message::ChooseImage),
+            toggler(self.embed_images)
+                .label("Embed images")
+                .on_toggle(|_| Message::ToggleEmbedImages)
+                .width(Length::Shrink),
+            toggler(self.show_resolved)
+                .label("Resolved comments")
+                .on_toggle(|_| Message::ToggleResolvedComments)
+                .width(Length::Shrink),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center);
+
         column![
             "Creator Canvas",
-            text(format!("({},{})", self.cursor_pos.x, self.cursor_pos.y)),
-            c,
+            edit_controls,
+            row![zoom_controls, combine_controls].spacing(20),
+            image_controls,
+            row![text(format!(
+                "({},{}) - {} shapes, {} selected",
+                self.cursor_pos.x,
+                self.cursor_pos.y,
+                self.document.len(),
+                self.selection.len()
+            ))]
+            .push_maybe(self.status.as_ref().map(|status| match status {
+                Status::Info(message) => text(message),
+                Status::Error(message) => text(message).style(text::danger),
+            }))
+            .spacing(20),
+            row![]
+                .push_maybe(self.show_stencils.then(|| self.stencil_panel()))
+                .push(c)
+                .push_maybe(self.open_thread.and_then(|id| self.thread_panel(id)))
+                .push_maybe(self.show_frames.then(|| self.frames_panel())),
         ]
         .align_x(Alignment::Center)
         .into()
     }
+
+    /// The stencil panel: the stencils of the current library, to be dragged onto the board,
+    /// and the controls to save the selection as a component.
+    fn stencil_panel(&self) -> iced::Element<'_, Message> {
+        let names: Vec<String> = self.libraries.iter().map(|l| l.name.clone()).collect();
+        let current = self.libraries.get(self.library);
+        let stencils = current.map(|l| l.stencils.as_slice()).unwrap_or_default();
+        let items = stencils.iter().enumerate().map(|(index, stencil)| {
+            mouse_area(
+                column![
+                    Canvas::new(StencilPreview { stencil })
+                        .width(Length::Fill)
+                        .height(Length::Fixed(48.0)),
+                    text(&stencil.name).size(12),
+                ]
+                .align_x(Alignment::Center),
+            )
+            .on_press(Message::StartStencilDrag {
+                library: self.library,
+                stencil: index,
+            })
+            .interaction(mouse::Interaction::Grab)
+            .into()
+        });
+
+        column![
+            pick_list(
+                names,
+                current.map(|l| l.name.clone()),
+                Message::SelectLibrary
+            ),
+            scrollable(Column::with_children(items).spacing(8)).height(Length::Fill),
+            text_input("Component name", &self.component_name)
+                .on_input(Message::ComponentNameChanged)
+                .on_submit(Message::SaveComponent),
+            button(text("Save as component"))
+                .on_press_maybe((!self.selection.is_empty()).then_some(Message::SaveComponent)),
+        ]
+        .spacing(5)
+        .padding(5)
+        .width(Length::Fixed(STENCIL_PANEL_WIDTH))
+        .into()
+    }
+
+    /// The thread panel: the comments of the open thread and the fields to reply. Returns None
+    /// if the thread doesn't exist (anymore).
+    fn thread_panel(&self, id: ThreadId) -> Option<iced::Element<'_, Message>> {
+        let thread = self.document.thread(id)?;
+        let comments = thread.comments.iter().map(|comment| {
+            column![
+                row![
+                    text(&comment.author).size(12),
+                    text(comment.timestamp()).size(10)
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+                text(&comment.text),
+            ]
+            .spacing(2)
+            .into()
+        });
+        let label = if thread.comments.is_empty() {
+            "Comment"
+        } else {
+            "Reply"
+        };
+
+        Some(
+            column![
+                row![
+                    text(if thread.resolved { "Resolved" } else { "Open" }),
+                    button(text(if thread.resolved { "Reopen" } else { "Resolve" }).size(12))
+                        .on_press_maybe(
+                            (!thread.comments.is_empty())
+                                .then_some(Message::ResolveThread(id, !thread.resolved))
+                        ),
+                    button(text("Delete").size(12)).on_press(Message::DeleteThread(id)),
+                    button(text("Close").size(12)).on_press(Message::CloseThread),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+                scrollable(Column::with_children(comments).spacing(8)).height(Length::Fill),
+                text_input("Your name", &self.author).on_input(Message::AuthorChanged),
+                text_input(label, &self.comment_draft)
+                    .id(comment_input())
+                    .on_input(Message::CommentDraftChanged)
+                    .on_submit(Message::PostComment),
+                button(text(label)).on_press_maybe(
+                    (!self.comment_draft.trim().is_empty()).then_some(Message::PostComment)
+                ),
+            ]
+            .spacing(5)
+            .padding(5)
+            .width(Length::Fixed(THREAD_PANEL_WIDTH))
+            .into(),
+        )
+    }
+
+    /// The frames panel: all frames with their names, a button to zoom to each of them and
+    /// the export buttons, and the button to start the presentation.
+    fn frames_panel(&self) -> iced::Element<'_, Message> {
+        let items = self.document.frames().map(|(id, frame)| {
+            let buttons = ExportFormat::ALL.into_iter().fold(
+                row![button(text("Go").size(12)).on_press(Message::ZoomToFrame(id))],
+                |buttons, format| {
+                    buttons.push(
+                        button(text(format.to_string()).size(12))
+                            .on_press(Message::ExportFrame(id, format)),
+                    )
+                },
+            );
+            column![
+                text_input("Frame name", &frame.name)
+                    .id(frame_name_input(id))
+                    .on_input(move |name| Message::RenameFrame(id, name)),
+                buttons.spacing(2),
+            ]
+            .spacing(2)
+            .into()
+        });
+
+        column![
+            button(text("Present")).on_press_maybe(
+                self.document
+                    .frames()
+                    .next()
+                    .map(|_| Message::StartPresentation)
+            ),
+            scrollable(Column::with_children(items).spacing(8)).height(Length::Fill),
+        ]
+        .spacing(5)
+        .padding(5)
+        .width(Length::Fixed(FRAMES_PANEL_WIDTH))
+        .into()
+    }
+}
+
+/// The text field for new comments in the thread panel.
+fn comment_input() -> text_input::Id {
+    text_input::Id::new("comment")
+}
+
+/// The text field with the name of the frame in the frames panel.
+fn frame_name_input(id: ShapeId) -> text_input::Id {
+    text_input::Id::new(format!("frame-name-{id:?}"))
+}
+
+/// Reads and decodes an image or SVG file on a separate thread, so that large images don't
+/// block the user interface. The shape gets the size of the image, at the origin.
+async fn load_image(path: PathBuf, embed: bool) -> Result<Shape, String> {
+    let (sender, receiver) = oneshot::channel();
+    std::thread::spawn(move || {
+        let is_svg = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+        let shape = if is_svg {
+            SvgState::open(&path).map(Shape::Svg)
+        } else {
+            ImageData::open(&path, embed).map(|data| {
+                let rect = Rectangle::with_size(data.size());
+                Shape::Image(ImageState::new(Arc::new(data), rect))
+            })
+        };
+        let _ = sender.send(shape.map_err(|e| e.to_string()));
+    });
+    receiver
+        .await
+        .unwrap_or_else(|_| Err("the image loader stopped".to_string()))
+}
+
+/// Returns the shape sketched by the stroke, or None if it isn't recognized. Axis aligned
+/// rectangles become rectangles, everything else a path.
+fn recognized_shape(stroke: &InkState) -> Option<Shape> {
+    let points: Vec<Point> = stroke.points.iter().map(|p| p.position).collect();
+    Some(match recognize(&points)? {
+        Recognized::Rectangle {
+            center,
+            size,
+            rotation_degrees,
+        } if rotation_degrees.abs() < AXIS_ALIGNED_DEGREES => RectangleState::with_dimensions(
+            Point::new(center.x - size.width / 2.0, center.y - size.height / 2.0),
+            size.width,
+            size.height,
+        )
+        .into(),
+        recognized => PathState::new(recognized.to_path()).into(),
+    })
 }
@@ -1,8 +1,169 @@
-use iced::Point;
+use std::time::Instant;
+
+use std::path::PathBuf;
+
+use iced::{Point, Size, Theme};
+
+use iced_hello_world::boolean_ops::BooleanOp;
+
+use crate::document::{ShapeId, ThreadId};
+use crate::frame_export::ExportFormat;
+use crate::program_canvas::PanZoomState;
+use crate::shape::Shape;
+use crate::shape_ink::InkState;
 
 #[derive(Clone, Debug)]
 pub enum Message {
     //    _CanvasMousePressed(Point),
     //    _CanvaseMouseReleased,
     CanvasMouseMoved(Point),
+    /// The canvas widget got a new size.
+    ViewportResized(Size),
+    /// The user panned or zoomed the canvas.
+    PanZoomChanged(PanZoomState),
+    /// The user moved or edited the shape with the given id.
+    ShapeChanged(ShapeId, Shape),
+    /// The user selected shapes with the rubber band.
+    SelectShapes(Vec<ShapeId>),
+    SelectTool(Tool),
+    AddRectangle,
+    AddPath,
+    /// The user finished a stroke with the pen.
+    AddInk(InkState),
+    /// The eraser passed over the board: cut the strokes inside the circle (or remove them
+    /// completely if `whole_strokes` is set).
+    Erase {
+        center: Point,
+        radius: f32,
+        whole_strokes: bool,
+    },
+    DeleteSelection,
+    /// Node tool: remove the selected node of the selected path.
+    DeleteNode,
+    /// Node tool: turn the selected node into a smooth node or a corner.
+    ToggleSmooth,
+    /// Close the selected path or open it again.
+    ToggleClosed,
+    /// Replace the selected shapes by their combination.
+    Combine(BooleanOp),
+    Open,
+    Save,
+    ExportSvg,
+    /// The user edited the image file field.
+    ImagePathChanged(String),
+    /// Choose an image or SVG file in the file dialog and insert it.
+    ChooseImage,
+    /// Load an image or SVG file (from the file dialog, the image file field or dropped onto the
+    /// window) in the background.
+    InsertImage(PathBuf),
+    /// The image or SVG file was loaded: place it in the center of the view.
+    ImageLoaded(PathBuf, Result<Shape, String>),
+    /// Embed inserted images in the board file, or refer to the image files.
+    ToggleEmbedImages,
+    ToggleStencils,
+    /// Show the stencils of the library with the given name in the panel.
+    SelectLibrary(String),
+    /// The user pressed the mouse button on a stencil in the panel.
+    StartStencilDrag {
+        library: usize,
+        stencil: usize,
+    },
+    /// The dragged stencil was released over the board at the given world point.
+    DropStencil(Point),
+    /// The dragged stencil was released outside of the board.
+    CancelStencilDrag,
+    /// The user edited the name for the next component.
+    ComponentNameChanged(String),
+    /// Add the selected shapes as a component to the user library.
+    SaveComponent,
+    /// Add a frame around the selected shapes, or in the center of the view.
+    AddFrame,
+    ToggleFrames,
+    /// The user edited the name of a frame in the frames panel.
+    RenameFrame(ShapeId, String),
+    /// Show the frames panel with the cursor in the name field of the frame.
+    EditFrameName(ShapeId),
+    ZoomToFrame(ShapeId),
+    /// Write the contents of a frame to a file next to the board file.
+    ExportFrame(ShapeId, ExportFormat),
+    /// Hide the controls and show the frames one after the other.
+    StartPresentation,
+    NextFrame,
+    PreviousFrame,
+    EndPresentation,
+    /// The comment tool was clicked at the given world point, on top of `shape` if it is set.
+    PlaceComment {
+        position: Point,
+        shape: Option<ShapeId>,
+    },
+    /// Show the thread in the thread panel.
+    OpenThread(ThreadId),
+    CloseThread,
+    /// The user edited the author name used for new comments.
+    AuthorChanged(String),
+    /// The user edited the comment or reply for the open thread.
+    CommentDraftChanged(String),
+    /// Add the draft as a comment to the open thread.
+    PostComment,
+    /// Mark the thread as resolved (true) or reopen it (false).
+    ResolveThread(ThreadId, bool),
+    DeleteThread(ThreadId),
+    /// Show or hide the pins of resolved threads.
+    ToggleResolvedComments,
+    ThemeChanged(Theme),
+    ZoomIn,
+    ZoomOut,
+    /// Zoom to the given percentage, keeping the center of the view in place.
+    ZoomTo(ZoomPreset),
+    ZoomToFit,
+    ZoomToSelection,
+    ToggleMinimap,
+    /// Turn recognition of sketched shapes in pen strokes on or off.
+    ToggleSmartInk,
+    AnimationTick(Instant),
+}
+
+/// What a click on the canvas does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tool {
+    /// Select and move whole shapes
+    #[default]
+    Select,
+    /// Edit the nodes of the selected path, crop the selected image
+    Node,
+    /// Draw freehand strokes
+    Pen,
+    /// Erase freehand strokes (split them, or remove them completely with shift)
+    Eraser,
+    /// Pin comment threads to the board or to shapes
+    Comment,
+}
+
+/// Zoom levels offered in the zoom dropdown (in percent).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZoomPreset(pub u32);
+
+impl ZoomPreset {
+    pub const ALL: [ZoomPreset; 10] = [
+        ZoomPreset(10),
+        ZoomPreset(25),
+        ZoomPreset(50),
+        ZoomPreset(75),
+        ZoomPreset(100),
+        ZoomPreset(150),
+        ZoomPreset(200),
+        ZoomPreset(400),
+        ZoomPreset(800),
+        ZoomPreset(1000),
+    ];
+
+    pub fn scale(&self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+}
+
+impl std::fmt::Display for ZoomPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.0)
+    }
 }
@@ -0,0 +1,178 @@
+//
+// Minimap overlay for the creator canvas.
+//
+// Concept:
+//  - The minimap is drawn in the lower right corner of the canvas, in screen coordinates.
+//  - It shows the bounding boxes of all shapes, scaled so that all of them fit into the minimap.
+//  - The part of the world that is currently visible (the viewport) is drawn as a rectangle on top.
+//  - The shapes are drawn into a cache, which only has to be invalidated when the document changes.
+//    The viewport rectangle is cheap and drawn on every frame.
+//  - Clicking or dragging inside the minimap centers the viewport on the world point below the cursor.
+//
+
+use iced::widget::canvas::{Cache, Frame, Geometry, Path, Stroke};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme, Vector};
+
+use crate::program_canvas::PanZoomState;
+
+/// Size of the minimap in pixels
+const MINIMAP_SIZE: Size = Size::new(200.0, 150.0);
+/// Distance of the minimap from the canvas border in pixels
+const MINIMAP_MARGIN: f32 = 10.0;
+/// Free space around the shapes in world units
+const WORLD_PADDING: f32 = 100.0;
+
+#[derive(Debug, Default)]
+pub struct Minimap {
+    cache: Cache,
+}
+
+impl Minimap {
+    /// Forces the shapes to be redrawn on the next frame. Call this whenever a shape changes.
+    pub fn invalidate(&self) {
+        self.cache.clear();
+    }
+
+    /// Draws the minimap for a canvas of the given size.
+    pub fn draw(
+        &self,
+        renderer: &Renderer,
+        canvas_size: Size,
+        shape_bounds: &[Rectangle],
+        pan_zoom_state: &PanZoomState,
+        theme: &Theme,
+    ) -> Vec<Geometry> {
+        let projection = Projection::new(canvas_size, shape_bounds);
+        let palette = theme.extended_palette();
+
+        let shapes = self.cache.draw(renderer, canvas_size, |frame| {
+            frame.fill_rectangle(
+                projection.area.position(),
+                projection.area.size(),
+                Color {
+                    a: 0.85,
+                    ..palette.background.base.color
+                },
+            );
+            for bounds in shape_bounds {
+                let top_left = projection.to_minimap(Point::new(bounds.x, bounds.y));
+                let size = Size::new(
+                    (bounds.width * projection.scale).max(1.0),
+                    (bounds.height * projection.scale).max(1.0),
+                );
+                frame.fill_rectangle(top_left, size, palette.background.strong.color);
+            }
+            frame.stroke(
+                &Path::rectangle(projection.area.position(), projection.area.size()),
+                Stroke::default().with_color(Color {
+                    a: 0.6,
+                    ..palette.background.base.text
+                }),
+            );
+        });
+
+        // the viewport, clipped to the minimap area
+        let mut frame = Frame::new(renderer, canvas_size);
+        let visible = pan_zoom_state.viewport.visible_world_rect(canvas_size);
+        let viewport = Rectangle::new(
+            projection.to_minimap(visible.position()),
+            visible.size() * projection.scale,
+        );
+        if let Some(viewport) = viewport.intersection(&projection.area) {
+            frame.stroke(
+                &Path::rectangle(viewport.position(), viewport.size()),
+                Stroke::default()
+                    .with_color(Color::from_rgb(1.0, 0.4, 0.0))
+                    .with_width(2.0),
+            );
+        }
+
+        vec![shapes, frame.into_geometry()]
+    }
+
+    /// Returns true if the given point (canvas coordinates) is inside the minimap.
+    pub fn contains(canvas_size: Size, position: Point) -> bool {
+        minimap_area(canvas_size).contains(position)
+    }
+
+    /// Returns the view that centers the world point shown below the given position (canvas
+    /// coordinates) of the minimap. Positions outside the minimap are clamped to its border.
+    pub fn jump_to(
+        canvas_size: Size,
+        shape_bounds: &[Rectangle],
+        position: Point,
+        pan_zoom_state: &PanZoomState,
+    ) -> PanZoomState {
+        let projection = Projection::new(canvas_size, shape_bounds);
+        let area = projection.area;
+        let position = Point::new(
+            position.x.clamp(area.x, area.x + area.width),
+            position.y.clamp(area.y, area.y + area.height),
+        );
+        PanZoomState::centered_on(
+            projection.to_world(position),
+            pan_zoom_state.viewport.scale,
+            canvas_size,
+        )
+    }
+}
+
+/// The area of the minimap in canvas coordinates (lower right corner)
+fn minimap_area(canvas_size: Size) -> Rectangle {
+    Rectangle::new(
+        Point::new(
+            canvas_size.width - MINIMAP_SIZE.width - MINIMAP_MARGIN,
+            canvas_size.height - MINIMAP_SIZE.height - MINIMAP_MARGIN,
+        ),
+        MINIMAP_SIZE,
+    )
+}
+
+/// Maps world coordinates into the minimap and back.
+struct Projection {
+    area: Rectangle,
+    scale: f32,
+    offset: Vector,
+}
+
+impl Projection {
+    fn new(canvas_size: Size, shape_bounds: &[Rectangle]) -> Self {
+        let area = minimap_area(canvas_size);
+
+        // the world extent covers all shapes (or the origin if there are none)
+        let extent = shape_bounds
+            .iter()
+            .copied()
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or(Rectangle::new(Point::ORIGIN, Size::ZERO))
+            .expand(WORLD_PADDING);
+
+        let scale = (area.width / extent.width).min(area.height / extent.height);
+
+        // center the extent in the minimap area
+        let offset = Vector::new(
+            area.x + (area.width - extent.width * scale) / 2.0 - extent.x * scale,
+            area.y + (area.height - extent.height * scale) / 2.0 - extent.y * scale,
+        );
+
+        Self {
+            area,
+            scale,
+            offset,
+        }
+    }
+
+    fn to_minimap(&self, world: Point) -> Point {
+        Point::new(
+            world.x * self.scale + self.offset.x,
+            world.y * self.scale + self.offset.y,
+        )
+    }
+
+    fn to_world(&self, minimap: Point) -> Point {
+        Point::new(
+            (minimap.x - self.offset.x) / self.scale,
+            (minimap.y - self.offset.y) / self.scale,
+        )
+    }
+}
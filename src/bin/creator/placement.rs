@@ -0,0 +1,257 @@
+//
+// Position, size and rotation of box shaped content (images, SVG assets) on the board.
+//
+// Concept:
+//  - The content fills `rect` (world coordinates, before rotation) and is rotated around the
+//    center of `rect`.
+//  - "Local" coordinates are the coordinates before the rotation. Edits are computed in local
+//    coordinates and applied with `set_local_rect`, which keeps the rotation pivot in place, so
+//    nothing jumps when the size changes.
+//  - The selection frame shows four corner handles (resize) and a handle above the top edge
+//    (rotate). The handles have the same size on screen at every zoom level.
+//
+
+use iced::{
+    Color, Point, Rectangle, Size, Vector, mouse,
+    widget::canvas::{Frame, Path, Stroke},
+};
+use iced_hello_world::affine::Affine2;
+use iced_hello_world::hit_test::{hit_corner, hit_rotated_rectangle};
+use iced_hello_world::resize_interaction;
+
+/// Size of the handles in pixels
+pub const HANDLE_SIZE: f32 = 8.0;
+/// Distance of the rotate handle from the top edge in pixels
+const ROTATE_HANDLE_DISTANCE: f32 = 24.0;
+/// Smallest width or height in pixels when resizing
+const MIN_SIZE: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub rect: Rectangle,
+    pub rotation_degrees: f32,
+}
+
+/// A handle of the selection frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementHandle {
+    /// Corner: 0=upper left, 1=upper right, 2=lower right, 3=lower left (before rotation)
+    Corner(usize),
+    Rotate,
+}
+
+/// What is dragged with the select tool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlacementDrag {
+    /// Last cursor position
+    Move(Point),
+    Resize(usize),
+    Rotate,
+}
+
+impl Placement {
+    pub fn new(rect: Rectangle) -> Self {
+        Self {
+            rect,
+            rotation_degrees: 0.0,
+        }
+    }
+
+    pub fn center(&self) -> Point {
+        self.rect.center()
+    }
+
+    /// Transform from local (unrotated) to world coordinates.
+    pub fn local_to_world(&self) -> Affine2 {
+        Affine2::rotate_about(self.center(), self.rotation_degrees)
+    }
+
+    pub fn world_to_local(&self) -> Affine2 {
+        self.local_to_world().invert().unwrap_or_default()
+    }
+
+    /// The corners in world coordinates (upper left, upper right, lower right, lower left).
+    pub fn corners(&self) -> [Point; 4] {
+        self.local_to_world().apply_rectangle(&self.rect)
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.local_to_world().bounding_rectangle(&self.rect)
+    }
+
+    pub fn hit(&self, point: Point, tolerance: f32) -> bool {
+        hit_rotated_rectangle(
+            point,
+            self.center(),
+            self.rect.size(),
+            self.rotation_degrees,
+            tolerance,
+        )
+    }
+
+    /// Position of the rotate handle in world coordinates.
+    pub fn rotate_handle(&self, scale: f32) -> Point {
+        let top_center = Point::new(
+            self.rect.center_x(),
+            self.rect.y - ROTATE_HANDLE_DISTANCE / scale,
+        );
+        self.local_to_world().apply(top_center)
+    }
+
+    /// Returns the handle of the selection frame at the given point.
+    pub fn handle_at(&self, point: Point, tolerance: f32, scale: f32) -> Option<PlacementHandle> {
+        let reach = tolerance + HANDLE_SIZE / scale / 2.0;
+        if point.distance(self.rotate_handle(scale)) <= reach {
+            return Some(PlacementHandle::Rotate);
+        }
+        hit_corner(point, &self.corners(), reach).map(PlacementHandle::Corner)
+    }
+
+    pub fn translate(&mut self, delta: Vector) {
+        self.rect = self.rect + delta;
+    }
+
+    /// Replaces the rectangle by one given in the current local coordinates. Every local point
+    /// stays at the same world position.
+    pub fn set_local_rect(&mut self, local: Rectangle) {
+        let new_center = self.local_to_world().apply(local.center());
+        self.rect = local + (new_center - local.center());
+    }
+
+    /// Drags the given corner to the cursor, keeping the opposite corner in place. With
+    /// `keep_aspect` the proportions of the rectangle don't change.
+    pub fn resize(&mut self, corner: usize, cursor: Point, keep_aspect: bool, scale: f32) {
+        let local = self.world_to_local().apply(cursor);
+        let corners = [
+            Point::new(self.rect.x, self.rect.y),
+            Point::new(self.rect.x + self.rect.width, self.rect.y),
+            Point::new(
+                self.rect.x + self.rect.width,
+                self.rect.y + self.rect.height,
+            ),
+            Point::new(self.rect.x, self.rect.y + self.rect.height),
+        ];
+        let fixed = corners[(corner + 2) % 4];
+        let min_size = MIN_SIZE / scale;
+        let mut width = (local.x - fixed.x).abs().max(min_size);
+        let mut height = (local.y - fixed.y).abs().max(min_size);
+        if keep_aspect && self.rect.width > 0.0 && self.rect.height > 0.0 {
+            let factor = (width / self.rect.width).max(height / self.rect.height);
+            width = self.rect.width * factor;
+            height = self.rect.height * factor;
+        }
+        // the dragged corner stays on its side of the fixed corner
+        let dragged = corners[corner];
+        let x = if dragged.x < fixed.x {
+            fixed.x - width
+        } else {
+            fixed.x
+        };
+        let y = if dragged.y < fixed.y {
+            fixed.y - height
+        } else {
+            fixed.y
+        };
+        self.set_local_rect(Rectangle::new(Point::new(x, y), Size::new(width, height)));
+    }
+
+    /// Rotates so that the rotate handle points to the cursor.
+    pub fn rotate_to(&mut self, cursor: Point) {
+        let direction = cursor - self.center();
+        // the handle sits above the center, i.e. at -90 degrees
+        self.rotation_degrees = direction.y.atan2(direction.x).to_degrees() + 90.0;
+    }
+
+    /// The rotated rectangle as a closed path.
+    pub fn outline(&self) -> Path {
+        let corners = self.corners();
+        Path::new(|builder| {
+            builder.move_to(corners[0]);
+            for corner in &corners[1..] {
+                builder.line_to(*corner);
+            }
+            builder.close();
+        })
+    }
+
+    /// Starts dragging a handle or (if the point is inside) the whole content.
+    pub fn start_drag(&self, point: Point, tolerance: f32, scale: f32) -> Option<PlacementDrag> {
+        match self.handle_at(point, tolerance, scale) {
+            Some(PlacementHandle::Rotate) => Some(PlacementDrag::Rotate),
+            Some(PlacementHandle::Corner(corner)) => Some(PlacementDrag::Resize(corner)),
+            None => self
+                .hit(point, tolerance)
+                .then_some(PlacementDrag::Move(point)),
+        }
+    }
+
+    /// The cursor over a handle: a corner resizes in its direction (following the rotation),
+    /// the rotate handle can be grabbed.
+    pub fn handle_interaction(&self, handle: PlacementHandle) -> mouse::Interaction {
+        match handle {
+            PlacementHandle::Corner(corner) => {
+                resize_interaction(self.corners()[corner] - self.center())
+            }
+            PlacementHandle::Rotate => mouse::Interaction::Grab,
+        }
+    }
+
+    /// The cursor while dragging.
+    pub fn drag_interaction(&self, drag: PlacementDrag) -> mouse::Interaction {
+        match drag {
+            PlacementDrag::Move(_) => mouse::Interaction::Move,
+            PlacementDrag::Resize(corner) => {
+                self.handle_interaction(PlacementHandle::Corner(corner))
+            }
+            PlacementDrag::Rotate => mouse::Interaction::Grabbing,
+        }
+    }
+
+    /// Continues a drag started with `start_drag`.
+    pub fn drag(&mut self, drag: &mut PlacementDrag, cursor: Point, keep_aspect: bool, scale: f32) {
+        match drag {
+            PlacementDrag::Move(last) => {
+                self.translate(cursor - *last);
+                *last = cursor;
+            }
+            PlacementDrag::Resize(corner) => self.resize(*corner, cursor, keep_aspect, scale),
+            PlacementDrag::Rotate => self.rotate_to(cursor),
+        }
+    }
+
+    /// Draws the outline, the corner handles and the rotate handle.
+    pub fn draw_frame(&self, frame: &mut Frame, scale: f32) {
+        let outline = Stroke::default()
+            .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+            .with_width(1.0 / scale);
+        let corners = self.corners();
+        frame.stroke(&self.outline(), outline);
+
+        let top_center = Point::new(
+            (corners[0].x + corners[1].x) / 2.0,
+            (corners[0].y + corners[1].y) / 2.0,
+        );
+        let rotate_handle = self.rotate_handle(scale);
+        frame.stroke(&Path::line(top_center, rotate_handle), outline);
+        let circle = Path::circle(rotate_handle, HANDLE_SIZE / scale / 2.0);
+        frame.fill(&circle, Color::WHITE);
+        frame.stroke(&circle, outline);
+
+        draw_corner_handles(frame, &corners, scale, Color::WHITE);
+    }
+}
+
+/// Draws square handles at the given points.
+pub fn draw_corner_handles(frame: &mut Frame, corners: &[Point], scale: f32, fill: Color) {
+    let size = HANDLE_SIZE / scale;
+    for corner in corners {
+        let top_left = Point::new(corner.x - size / 2.0, corner.y - size / 2.0);
+        frame.fill_rectangle(top_left, Size::new(size, size), fill);
+        frame.stroke(
+            &Path::rectangle(top_left, Size::new(size, size)),
+            Stroke::default()
+                .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+                .with_width(1.0 / scale),
+        );
+    }
+}
@@ -1,37 +1,150 @@
-use crate::message::Message;
-use crate::shape_rectangle::{RectangleShape, RectangleState};
+use crate::comment::{self, PIN_RADIUS};
+use crate::document::{Document, ShapeId, ThreadId};
+use crate::message::{Message, Tool};
+use crate::minimap::Minimap;
+use crate::render_layers::RenderLayers;
+use crate::shape::Shape;
+use crate::shape_ink::{InkRecorder, InkState};
+use crate::stencil::Stencil;
+use iced::advanced::mouse::{Click, click};
 use iced::widget::canvas::path::{Builder, Path};
-use iced::widget::canvas::{self, Cache, Event, Frame, Geometry, Program, stroke};
-use iced::{Point, Rectangle, Renderer, Theme, Vector, mouse};
+use iced::widget::canvas::{Event, Frame, Geometry, Program, stroke};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme, Vector, keyboard, mouse};
+use iced_hello_world::hit_test::tolerance_for_zoom;
+use iced_hello_world::viewport::{Inertia, Viewport};
+use std::collections::BTreeSet;
+use std::time::Instant;
 
-pub struct CanvasProgram {
-    _canvas_cache: Cache,
-    // canvas_programs: Vec<Box<dyn Program<Message, State = ()>>>,
+/// Zoom factor applied per wheel "line" (one notch on a classic mouse wheel).
+const ZOOM_STEP: f32 = 1.1;
+/// Number of pixels of a pixel-based scroll delta that count as one wheel notch.
+const PIXELS_PER_LINE: f32 = 40.0;
+/// How far (in screen pixels) the cursor may be away from a shape to still grab it.
+const HIT_TOLERANCE: f32 = 4.0;
+/// Radius of the eraser in screen pixels.
+const ERASER_RADIUS: f32 = 10.0;
+
+/// The canvas program of the creator.
+///
+/// The program does not own the document or the view. Both live in the `CreatorApp` and are
+/// borrowed here for drawing. Interactions are reported back as messages with the updated
+/// values, so that the application can also change the view (e.g. zoom to fit).
+pub struct CanvasProgram<'a> {
+    layers: &'a RenderLayers,
+    pan_zoom_state: &'a PanZoomState,
+    document: &'a Document,
+    selection: &'a BTreeSet<ShapeId>,
+    /// The size of the canvas as last reported to the application.
+    viewport_size: Size,
+    /// The minimap overlay, if it is shown.
+    minimap: Option<&'a Minimap>,
+    tool: Tool,
+    /// The stencil that is dragged from the stencil panel, if any.
+    stencil: Option<&'a Stencil>,
+    /// Presentation mode: only the contents are drawn and the board can't be edited
+    presenting: bool,
+    /// Show the pins of resolved comment threads
+    show_resolved: bool,
+    /// The thread shown in the thread panel
+    open_thread: Option<ThreadId>,
 }
 
-impl Default for CanvasProgram {
-    fn default() -> Self {
+impl<'a> CanvasProgram<'a> {
+    pub fn new(
+        layers: &'a RenderLayers,
+        pan_zoom_state: &'a PanZoomState,
+        document: &'a Document,
+        selection: &'a BTreeSet<ShapeId>,
+        viewport_size: Size,
+    ) -> Self {
         Self {
-            _canvas_cache: Cache::new(),
-            // canvas_programs: Vec::new(),
+            layers,
+            pan_zoom_state,
+            document,
+            selection,
+            viewport_size,
+            minimap: None,
+            tool: Tool::default(),
+            stencil: None,
+            presenting: false,
+            show_resolved: false,
+            open_thread: None,
         }
     }
-}
 
-impl CanvasProgram {
-    pub fn new() -> Self {
-        Self::default()
+    /// Shows the given minimap in the lower right corner of the canvas.
+    pub fn minimap(mut self, minimap: &'a Minimap) -> Self {
+        self.minimap = Some(minimap);
+        self
+    }
+
+    /// Sets the tool that handles clicks on the shapes.
+    pub fn tool(mut self, tool: Tool) -> Self {
+        self.tool = tool;
+        self
+    }
+
+    /// Sets the stencil that is dragged from the stencil panel. It follows the cursor over the
+    /// board and is dropped when the mouse button is released.
+    pub fn stencil(mut self, stencil: Option<&'a Stencil>) -> Self {
+        self.stencil = stencil;
+        self
+    }
+
+    /// Presentation mode: hides the grid and the frames and ignores all input, the application
+    /// moves the view from frame to frame.
+    pub fn presenting(mut self, presenting: bool) -> Self {
+        self.presenting = presenting;
+        self
+    }
+
+    /// Sets which comment pins are shown and which one is highlighted.
+    pub fn comments(mut self, show_resolved: bool, open_thread: Option<ThreadId>) -> Self {
+        self.show_resolved = show_resolved;
+        self.open_thread = open_thread;
+        self
+    }
+
+    fn shape_bounds(&self) -> Vec<Rectangle> {
+        self.document
+            .iter()
+            .map(|(_, shape)| shape.bounds())
+            .collect()
+    }
+
+    /// The handles of the selected shapes are grabbed first, even if another shape lies on top
+    /// of them.
+    fn handle_target(&self, cursor_position: Point, tolerance: f32) -> Option<ShapeId> {
+        let scale = self.pan_zoom_state.viewport.scale;
+        self.selection.iter().rev().copied().find(|id| {
+            self.document
+                .get(*id)
+                .is_some_and(|shape| shape.hit_handle(cursor_position, tolerance, scale, self.tool))
+        })
     }
 }
 
+/// Widget-local state of the canvas (things the application doesn't need to know about).
 #[derive(Clone, Debug, Default)]
 pub struct CanvasState {
-    pan_zoom_state: PanZoomState,
-    rectangle_shape: RectangleShape,
-    rectangle_state: RectangleState,
+    modifiers: keyboard::Modifiers,
+    /// The user keeps the left mouse button pressed in the minimap
+    minimap_dragging: bool,
+    /// The shape that is currently dragged
+    dragging: Option<ShapeId>,
+    /// Start and current corner (world coordinates) of the rubber band selection
+    rubber_band: Option<(Point, Point)>,
+    /// The stroke that is drawn with the pen
+    ink: Option<InkRecorder>,
+    /// The user keeps the left mouse button pressed with the eraser
+    erasing: bool,
+    /// Position (world coordinates) of the stencil dragged from the stencil panel
+    stencil_position: Option<Point>,
+    /// The last click on the board, to detect double clicks
+    last_click: Option<Click>,
 }
 
-impl Program<Message> for CanvasProgram {
+impl Program<Message> for CanvasProgram<'_> {
     type State = CanvasState;
 
     fn update(
@@ -41,116 +154,609 @@ impl Program<Message> for CanvasProgram {
         bounds: iced::Rectangle,
         cursor: iced::mouse::Cursor,
     ) -> (iced::widget::canvas::event::Status, Option<Message>) {
-        // Get the absolute cursor position - return if not available
-        let cursor_position = if let Some(position) = cursor.position_in(bounds) {
-            position - state.pan_zoom_state.translation // * state.pan_zoom_state.scale
+        let (status, message) = self.handle_event(state, event, bounds, cursor);
+
+        // The application needs the canvas size to compute "zoom to fit" and friends. The new
+        // size goes out instead of a plain cursor move (or no message at all), so the event that
+        // arrives together with a resize is never lost. Until the application knows the size,
+        // every event tries again.
+        if bounds.size() != self.viewport_size
+            && matches!(message, None | Some(Message::CanvasMouseMoved(_)))
+        {
+            return (status, Some(Message::ViewportResized(bounds.size())));
+        }
+        (status, message)
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let start = Instant::now();
+        let visible = self
+            .pan_zoom_state
+            .viewport
+            .visible_world_rect(bounds.size());
+
+        // static layer: grid and unselected shapes
+        let static_layer = self.layers.draw_static(renderer, bounds.size(), |frame| {
+            frame.translate(self.pan_zoom_state.viewport.translation);
+            frame.scale(self.pan_zoom_state.viewport.scale);
+
+            if !self.presenting {
+                draw_grid(frame, visible, theme);
+            }
+
+            for id in self.document.shapes_in(visible) {
+                if !self.selection.contains(&id)
+                    && let Some(shape) = self.document.get(id)
+                    && !(self.presenting && matches!(shape, Shape::Frame(_)))
+                {
+                    shape.draw(
+                        frame,
+                        theme,
+                        cursor,
+                        self.pan_zoom_state.viewport.scale,
+                        false,
+                    );
+                }
+            }
+        });
+
+        // dynamic layer: the selected shape and its handles
+        let mut frame = Frame::new(renderer, bounds.size());
+        frame.translate(self.pan_zoom_state.viewport.translation);
+        frame.scale(self.pan_zoom_state.viewport.scale);
+        let selected = || {
+            self.selection
+                .iter()
+                .filter_map(|id| self.document.get(*id))
+        };
+        for shape in selected() {
+            shape.draw(
+                &mut frame,
+                theme,
+                cursor,
+                self.pan_zoom_state.viewport.scale,
+                true,
+            );
+            shape.draw_handles(&mut frame, self.pan_zoom_state.viewport.scale, self.tool);
+        }
+        let dynamic_layer = frame.into_geometry();
+
+        // overlay layer: alignment guides while a shape is dragged
+        let mut frame = Frame::new(renderer, bounds.size());
+        frame.translate(self.pan_zoom_state.viewport.translation);
+        frame.scale(self.pan_zoom_state.viewport.scale);
+        if let Some(shape) = selected().find(|shape| shape.is_dragging()) {
+            draw_guides(
+                &mut frame,
+                shape.bounds(),
+                visible,
+                self.pan_zoom_state.viewport.scale,
+            );
+        }
+        if let Some((start, end)) = state.rubber_band {
+            draw_rubber_band(
+                &mut frame,
+                start,
+                end,
+                self.pan_zoom_state.viewport.scale,
+                theme,
+            );
+        }
+        if let Some(ink) = &state.ink {
+            ink.draw(&mut frame, self.pan_zoom_state.viewport.scale, theme);
+        }
+        if !self.presenting {
+            for (id, thread) in self.document.threads() {
+                if self.show_resolved || !thread.resolved {
+                    let position = self.document.thread_position(thread);
+                    let open = self.open_thread == Some(id);
+                    comment::draw_pin(
+                        &mut frame,
+                        position,
+                        thread,
+                        open,
+                        self.pan_zoom_state.viewport.scale,
+                    );
+                }
+            }
+        }
+        if let (Some(stencil), Some(position)) = (self.stencil, state.stencil_position) {
+            for shape in stencil.placed_at(position) {
+                shape.draw(
+                    &mut frame,
+                    theme,
+                    cursor,
+                    self.pan_zoom_state.viewport.scale,
+                    true,
+                );
+            }
+        }
+        if self.tool == Tool::Eraser
+            && let Some(position) = cursor.position_in(bounds)
+        {
+            let center = self.pan_zoom_state.viewport.to_world(position);
+            let scale = self.pan_zoom_state.viewport.scale;
+            frame.stroke(
+                &Path::circle(center, ERASER_RADIUS / scale),
+                stroke::Stroke::default()
+                    .with_color(Color::from_rgb(0.5, 0.5, 0.5))
+                    .with_width(1.0 / scale),
+            );
+        }
+        let overlay_layer = frame.into_geometry();
+
+        let mut geometries = vec![static_layer, dynamic_layer, overlay_layer];
+        if let Some(minimap) = self.minimap {
+            geometries.extend(minimap.draw(
+                renderer,
+                bounds.size(),
+                &self.shape_bounds(),
+                self.pan_zoom_state,
+                theme,
+            ));
+        }
+
+        self.layers.record_frame(start.elapsed());
+        geometries
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if self.presenting {
+            return mouse::Interaction::default();
+        }
+        // drags that continue outside of the canvas
+        if self.stencil.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+        if state.minimap_dragging || self.pan_zoom_state.is_panning() {
+            return mouse::Interaction::Grabbing;
+        }
+        let Some(position) = cursor.position_in(bounds) else {
+            return mouse::Interaction::default();
+        };
+        if self.minimap.is_some() && Minimap::contains(bounds.size(), position) {
+            return mouse::Interaction::Pointer;
+        }
+        // the drawing tools work everywhere on the board
+        if matches!(self.tool, Tool::Pen | Tool::Eraser) || state.rubber_band.is_some() {
+            return mouse::Interaction::Crosshair;
+        }
+
+        // the same order as in `update`: dragged shape, comment pins, handles, shapes
+        let cursor_position = self.pan_zoom_state.viewport.to_world(position);
+        let scale = self.pan_zoom_state.viewport.scale;
+        let tolerance = tolerance_for_zoom(HIT_TOLERANCE, scale);
+        let shape_interaction = |id: ShapeId| {
+            self.document.get(id).and_then(|shape| {
+                shape.mouse_interaction(
+                    cursor_position,
+                    tolerance,
+                    scale,
+                    self.tool,
+                    self.selection.contains(&id),
+                )
+            })
+        };
+        if let Some(interaction) = state.dragging.and_then(shape_interaction) {
+            return interaction;
+        }
+        let radius = PIN_RADIUS / scale + tolerance;
+        if self
+            .document
+            .thread_at(cursor_position, radius, self.show_resolved)
+            .is_some()
+        {
+            // a click opens the thread with the cursor in the reply field
+            return mouse::Interaction::Text;
+        }
+        if self.tool == Tool::Comment {
+            return mouse::Interaction::Crosshair;
+        }
+        if let Some(interaction) = self
+            .handle_target(cursor_position, tolerance)
+            .and_then(shape_interaction)
+        {
+            return interaction;
+        }
+        if let Some(id) = self.document.shape_at(cursor_position, tolerance) {
+            // the node tool selects a shape with the first click
+            if self.tool == Tool::Node && !self.selection.contains(&id) {
+                return mouse::Interaction::Pointer;
+            }
+            if let Some(interaction) = shape_interaction(id) {
+                return interaction;
+            }
+        }
+
+        // the empty board: shift + drag selects, every other drag pans
+        if state.modifiers.shift() {
+            mouse::Interaction::Crosshair
         } else {
+            mouse::Interaction::Grab
+        }
+    }
+}
+
+impl CanvasProgram<'_> {
+    /// Handles an event of the canvas, see `update`.
+    fn handle_event(
+        &self,
+        state: &mut CanvasState,
+        event: iced::widget::canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: iced::mouse::Cursor,
+    ) -> (iced::widget::canvas::event::Status, Option<Message>) {
+        if self.presenting {
+            return (iced::widget::canvas::event::Status::Ignored, None);
+        }
+
+        if let Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) = event {
+            state.modifiers = modifiers;
+            return (iced::widget::canvas::event::Status::Ignored, None);
+        }
+
+        // Step 0: the minimap sits on top of everything else
+        if self.minimap.is_some()
+            && let Some(message) = self.update_minimap(state, &event, bounds, cursor)
+        {
+            return (iced::widget::canvas::event::Status::Captured, message);
+        }
+
+        // Step 0a: a stencil dragged from the panel gets all mouse events, also the release
+        // outside of the canvas
+        if let Some(message) = self.update_stencil_drag(state, &event, bounds, cursor) {
+            return (iced::widget::canvas::event::Status::Captured, message);
+        }
+
+        // Get the cursor position on the canvas (for panning and zooming) and in the world (for
+        // the shapes) - return if not available
+        let Some(screen_position) = cursor.position_in(bounds) else {
             return (iced::widget::canvas::event::Status::Ignored, None);
         };
+        let cursor_position = self.pan_zoom_state.viewport.to_world(screen_position);
 
-        let cursor_position = Point::new(
-            cursor_position.x / state.pan_zoom_state.scale,
-            cursor_position.y / state.pan_zoom_state.scale,
-        );
-
-        // Step 1: first check if one of the shapes captures the event
-        // t.b.d
-        let is_handled = state.rectangle_shape.update(
-            &mut state.rectangle_state,
-            event.clone(),
-            cursor_position,
-        );
-        if is_handled {
-            println!("rect handled");
-            let message = Message::CanvasMouseMoved(cursor_position);
-            return (iced::widget::canvas::event::Status::Captured, Some(message));
+        // Step 0b: the pen and the eraser work everywhere on the board, the shapes don't get
+        // the mouse events
+        if let Some(message) = self.update_ink(state, &event, cursor_position) {
+            return (iced::widget::canvas::event::Status::Captured, message);
+        }
+
+        // Step 0c: comment pins lie on top of the shapes, the comment tool drops new ones
+        let tolerance = tolerance_for_zoom(HIT_TOLERANCE, self.pan_zoom_state.viewport.scale);
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
+            let radius = PIN_RADIUS / self.pan_zoom_state.viewport.scale + tolerance;
+            if let Some(id) = self
+                .document
+                .thread_at(cursor_position, radius, self.show_resolved)
+            {
+                let message = Message::OpenThread(id);
+                return (iced::widget::canvas::event::Status::Captured, Some(message));
+            }
+            if self.tool == Tool::Comment {
+                let message = Message::PlaceComment {
+                    position: cursor_position,
+                    shape: self.document.shape_at(cursor_position, tolerance),
+                };
+                return (iced::widget::canvas::event::Status::Captured, Some(message));
+            }
+
+            // a double click on the name of a frame edits the name in the frames panel
+            let click = Click::new(screen_position, mouse::Button::Left, state.last_click);
+            state.last_click = Some(click);
+            if matches!(click.kind(), click::Kind::Double)
+                && self.tool == Tool::Select
+                && let Some(id) = self.document.shape_at(cursor_position, tolerance)
+                && let Some(Shape::Frame(frame)) = self.document.get(id)
+                && frame.hit_title(cursor_position, tolerance)
+            {
+                let message = Message::EditFrameName(id);
+                return (iced::widget::canvas::event::Status::Captured, Some(message));
+            }
+        }
+
+        // Step 1: first check if one of the shapes captures the event. Only the shape that is
+        // dragged or the topmost shape under the cursor can be interested.
+        let target = match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => self
+                .handle_target(cursor_position, tolerance)
+                .or_else(|| self.document.shape_at(cursor_position, tolerance)),
+            _ => state.dragging,
+        };
+        if let Some((id, shape)) = target.and_then(|id| Some((id, self.document.get(id)?.clone())))
+        {
+            // the node tool only edits selected shapes, the first click selects the shape
+            if self.tool == Tool::Node && !self.selection.contains(&id) {
+                let message = Message::ShapeChanged(id, shape);
+                return (iced::widget::canvas::event::Status::Captured, Some(message));
+            }
+            let mut shape = shape;
+            let is_handled = shape.update(
+                event.clone(),
+                cursor_position,
+                tolerance,
+                self.pan_zoom_state.viewport.scale,
+                self.tool,
+            );
+            if is_handled {
+                state.dragging = shape.is_dragging().then_some(id);
+                let message = Message::ShapeChanged(id, shape);
+                return (iced::widget::canvas::event::Status::Captured, Some(message));
+            }
+        }
+
+        // Step 1b: shift + drag on the empty board selects all shapes inside the rubber band
+        if let Some(message) = self.update_rubber_band(state, &event, cursor_position) {
+            return (iced::widget::canvas::event::Status::Captured, message);
         }
 
         // Step 2: check if PanZoomState handles it
-        let pan_zoom_handle_result = state
-            .pan_zoom_state
-            .handle_message(event.clone(), cursor_position);
+        let mut pan_zoom_state = *self.pan_zoom_state;
+        let pan_zoom_handle_result =
+            pan_zoom_state.handle_message(event.clone(), screen_position, state.modifiers);
         if pan_zoom_handle_result {
-            let message = Message::CanvasMouseMoved(cursor_position);
+            let message = Message::PanZoomChanged(pan_zoom_state);
             return (iced::widget::canvas::event::Status::Captured, Some(message));
         }
         let message = Message::CanvasMouseMoved(cursor_position);
         (iced::widget::canvas::event::Status::Ignored, Some(message))
     }
 
-    fn draw(
+    /// Handles a stencil dragged from the stencil panel. Returns `Some` if the event was
+    /// captured (with an optional message for the application).
+    fn update_stencil_drag(
         &self,
-        state: &Self::State,
-        renderer: &Renderer,
-        theme: &Theme,
+        state: &mut CanvasState,
+        event: &Event,
         bounds: Rectangle,
         cursor: mouse::Cursor,
-    ) -> Vec<Geometry> {
-        let mut frame = Frame::new(renderer, bounds.size());
-        frame.translate(state.pan_zoom_state.translation);
-        frame.scale(state.pan_zoom_state.scale);
-
-        draw_grid(&mut frame, bounds);
-
-        state.rectangle_shape.draw(
-            // &mut self.canvas_cache,
-            &state.rectangle_state,
-            &mut frame,
-            // renderer,
-            theme,
-            // bounds,
-            cursor,
-        );
-        vec![frame.into_geometry()]
+    ) -> Option<Option<Message>> {
+        self.stencil?;
+        let position = cursor
+            .position_in(bounds)
+            .map(|position| self.pan_zoom_state.viewport.to_world(position));
+        match event {
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                state.stencil_position = position;
+                Some(None)
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                state.stencil_position = None;
+                Some(Some(match position {
+                    Some(position) => Message::DropStencil(position),
+                    None => Message::CancelStencilDrag,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Handles the pen and the eraser tool. Returns `Some` if the event was captured (with an
+    /// optional message for the application).
+    fn update_ink(
+        &self,
+        state: &mut CanvasState,
+        event: &Event,
+        cursor_position: Point,
+    ) -> Option<Option<Message>> {
+        let Event::Mouse(mouse_event) = event else {
+            return None;
+        };
+        let scale = self.pan_zoom_state.viewport.scale;
+        let erase = |state: &CanvasState| Message::Erase {
+            center: cursor_position,
+            radius: ERASER_RADIUS / scale,
+            whole_strokes: state.modifiers.shift(),
+        };
+        match (self.tool, mouse_event) {
+            (Tool::Pen, mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                state.ink = Some(InkRecorder::new(cursor_position));
+                Some(None)
+            }
+            (Tool::Pen, mouse::Event::CursorMoved { .. }) => {
+                state.ink.as_mut()?.record(cursor_position);
+                Some(None)
+            }
+            (Tool::Pen, mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                let ink = state.ink.take()?;
+                let stroke = InkState::from_samples(&ink.samples, scale);
+                Some(Some(Message::AddInk(stroke)))
+            }
+            (Tool::Eraser, mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                state.erasing = true;
+                Some(Some(erase(state)))
+            }
+            (Tool::Eraser, mouse::Event::CursorMoved { .. }) if state.erasing => {
+                Some(Some(erase(state)))
+            }
+            (Tool::Eraser, mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                state.erasing = false;
+                Some(None)
+            }
+            _ => None,
+        }
+    }
+
+    /// Handles the rubber band selection (shift + drag on the empty board). Returns `Some` if
+    /// the event was captured (with an optional message for the application).
+    fn update_rubber_band(
+        &self,
+        state: &mut CanvasState,
+        event: &Event,
+        cursor_position: Point,
+    ) -> Option<Option<Message>> {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if state.modifiers.shift() =>
+            {
+                state.rubber_band = Some((cursor_position, cursor_position));
+                Some(None)
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                let (start, _) = state.rubber_band?;
+                state.rubber_band = Some((start, cursor_position));
+                Some(None)
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                let (start, end) = state.rubber_band.take()?;
+                let ids = self
+                    .document
+                    .shapes_within(rectangle_from_corners(start, end));
+                Some(Some(Message::SelectShapes(ids)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Handles clicking and dragging inside the minimap. Returns `Some` if the event was
+    /// captured by the minimap (with an optional message for the application).
+    fn update_minimap(
+        &self,
+        state: &mut CanvasState,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<Option<Message>> {
+        let position = cursor.position_in(bounds);
+        let jump_to = |position: Point| {
+            Message::PanZoomChanged(Minimap::jump_to(
+                bounds.size(),
+                &self.shape_bounds(),
+                position,
+                self.pan_zoom_state,
+            ))
+        };
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let position = position.filter(|p| Minimap::contains(bounds.size(), *p))?;
+                state.minimap_dragging = true;
+                Some(Some(jump_to(position)))
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if state.minimap_dragging => {
+                Some(position.map(jump_to))
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+                if state.minimap_dragging =>
+            {
+                state.minimap_dragging = false;
+                Some(None)
+            }
+            _ => None,
+        }
     }
 }
 
-// Helper function to draw a simple grid
-fn draw_grid(frame: &mut Frame, bounds: Rectangle) {
-    let min_x = (bounds.x / 50.0).floor() * 50.0;
-    let max_x = (bounds.x + bounds.width / 50.0).ceil() * 50.0;
-    let min_y = (bounds.y / 50.0).floor() * 50.0;
-    let max_y = (bounds.y + bounds.height / 50.0).ceil() * 50.0;
+// Helper function to draw a simple grid covering the visible world rectangle
+fn draw_grid(frame: &mut Frame, visible: Rectangle, theme: &Theme) {
+    let min_x = (visible.x / 50.0).floor() * 50.0;
+    let max_x = ((visible.x + visible.width) / 50.0).ceil() * 50.0;
+    let min_y = (visible.y / 50.0).floor() * 50.0;
+    let max_y = ((visible.y + visible.height) / 50.0).ceil() * 50.0;
 
     let grid = Path::new(|path_builder: &mut Builder| {
-        for x in (min_x as i32..max_x as i32).step_by(50) {
-            path_builder.move_to(Point::new(x as f32, bounds.y));
-            path_builder.line_to(Point::new(x as f32, bounds.y + bounds.height));
+        for x in (min_x as i32..=max_x as i32).step_by(50) {
+            path_builder.move_to(Point::new(x as f32, min_y));
+            path_builder.line_to(Point::new(x as f32, max_y));
         }
-        for y in (min_y as i32..max_y as i32).step_by(50) {
-            path_builder.move_to(Point::new(bounds.x, y as f32));
-            path_builder.line_to(Point::new(bounds.x + bounds.width, y as f32));
+        for y in (min_y as i32..=max_y as i32).step_by(50) {
+            path_builder.move_to(Point::new(min_x, y as f32));
+            path_builder.line_to(Point::new(max_x, y as f32));
         }
     });
 
-    frame.stroke(&grid, stroke::Stroke::default());
+    frame.stroke(
+        &grid,
+        stroke::Stroke::default().with_color(theme.extended_palette().background.strong.color),
+    );
 }
 
-#[derive(Debug, Clone, Copy)]
-struct PanZoomState {
-    translation: Vector,
-    scale: f32,
-    /// The point in screen coordinates where dragging started
-    drag_start: Option<Point>,
+fn rectangle_from_corners(a: Point, b: Point) -> Rectangle {
+    Rectangle::new(
+        Point::new(a.x.min(b.x), a.y.min(b.y)),
+        Size::new((a.x - b.x).abs(), (a.y - b.y).abs()),
+    )
 }
 
-impl Default for PanZoomState {
-    fn default() -> Self {
-        Self {
-            translation: Vector::new(0.0, 0.0),
-            scale: 1.0,
-            drag_start: None,
+// Helper function to draw the rubber band selection rectangle
+fn draw_rubber_band(frame: &mut Frame, start: Point, end: Point, scale: f32, theme: &Theme) {
+    let rect = rectangle_from_corners(start, end);
+    let color = theme.palette().primary;
+    frame.fill_rectangle(rect.position(), rect.size(), Color { a: 0.15, ..color });
+    frame.stroke(
+        &Path::rectangle(rect.position(), rect.size()),
+        stroke::Stroke::default()
+            .with_color(color)
+            .with_width(1.0 / scale),
+    );
+}
+
+// Helper function to draw dashed guide lines along the edges of the given rectangle
+fn draw_guides(frame: &mut Frame, rect: Rectangle, visible: Rectangle, scale: f32) {
+    let guides = Path::new(|path_builder: &mut Builder| {
+        for x in [rect.x, rect.x + rect.width] {
+            path_builder.move_to(Point::new(x, visible.y));
+            path_builder.line_to(Point::new(x, visible.y + visible.height));
         }
-    }
+        for y in [rect.y, rect.y + rect.height] {
+            path_builder.move_to(Point::new(visible.x, y));
+            path_builder.line_to(Point::new(visible.x + visible.width, y));
+        }
+    });
+
+    // keep the guides one pixel wide at all zoom levels
+    let dash = 4.0 / scale;
+    frame.stroke(
+        &guides,
+        stroke::Stroke {
+            line_dash: stroke::LineDash {
+                segments: &[dash, dash],
+                offset: 0,
+            },
+            ..stroke::Stroke::default()
+                .with_color(Color::from_rgb(0.9, 0.2, 0.6))
+                .with_width(1.0 / scale)
+        },
+    );
+}
+
+/// The view of the canvas and the state of panning it with the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PanZoomState {
+    pub viewport: Viewport,
+    /// The cursor position in screen coordinates where dragging started
+    drag_start: Option<Point>,
+    inertia: Inertia,
 }
 
 // --- PanZoom State Logic (Event Handling) ---
 impl PanZoomState {
-    /// Handles a canvas event and returns true if the canvas view was modified
+    pub fn new(viewport: Viewport) -> Self {
+        Self {
+            viewport,
+            ..Self::default()
+        }
+    }
+
+    /// Handles a canvas event and returns true if the canvas view was modified. The cursor
+    /// position is in screen coordinates: panning follows the cursor pixel by pixel and
+    /// zooming keeps the world point under the cursor in place.
     fn handle_message(
         &mut self,
         event: iced::widget::canvas::Event,
-        cursor_position: Point,
+        screen_position: Point,
+        modifiers: keyboard::Modifiers,
     ) -> bool {
         // we might add more patterns in the future so kill clippy
         #[allow(clippy::single_match)]
@@ -158,64 +764,85 @@ impl PanZoomState {
             Event::Mouse(mouse_event) => {
                 match mouse_event {
                     mouse::Event::ButtonPressed(mouse::Button::Left) => {
-                        // Start dragging if cursor is in bounds
-                        println!("CanvasMousePressed at {:?}", cursor_position);
-                        self.drag_start = Some(cursor_position);
-                        true // Mark as handled to capture mouse
+                        // Start dragging if cursor is in bounds, a click stops gliding
+                        self.drag_start = Some(screen_position);
+                        self.inertia.stop();
+                        self.inertia.track(Vector::new(0.0, 0.0), Instant::now());
+                        return true; // Mark as handled to capture mouse
                     }
                     mouse::Event::ButtonReleased(mouse::Button::Left) => {
-                        // Stop dragging
-                        println!("CanvaseMouseReleased");
+                        // Stop dragging, a fast drag glides on
                         self.drag_start = None;
-                        true // Mark as handled to capture mouse
+                        self.inertia.release(Instant::now());
+                        return true; // Mark as handled to capture mouse
                     }
                     mouse::Event::CursorMoved { .. } => {
                         // Continue dragging
                         if let Some(start_pos) = self.drag_start {
-                            let delta = cursor_position - start_pos;
-                            self.translation = self.translation + delta;
-                            self.drag_start = Some(cursor_position); // Update start position for smooth dragging
-                            println!(
-                                "CanvasMouseMoved to {:?}, translation now {:?}",
-                                cursor_position, self.translation
-                            );
+                            let delta = screen_position - start_pos;
+                            self.viewport.pan(delta);
+                            self.inertia.track(delta, Instant::now());
+                            self.drag_start = Some(screen_position); // Update start position for smooth dragging
                             return true; // Mark as handled to capture mouse
                         }
-                        false // Mark as not handled
+                        return false; // Mark as not handled
                     }
                     mouse::Event::WheelScrolled { delta } => {
-                        match delta {
-                            mouse::ScrollDelta::Lines { x, y } => {
-                                println!("Lines Scrolled - x:{} y:{}", x, y);
-                            }
+                        self.inertia.stop();
+                        let zoom_factor = match delta {
+                            // Classic mouse wheels (and most wheels on Linux) report lines.
+                            mouse::ScrollDelta::Lines { y, .. } => ZOOM_STEP.powf(y),
+                            // Touchpads report pixels. A pinch gesture arrives as a Ctrl + scroll,
+                            // everything else is a two finger pan.
                             mouse::ScrollDelta::Pixels { x, y } => {
-                                let scale_change = if y > 0.0 { 1.1 } else { 0.9 }; // zom by 10% in or out
-                                let new_scale = (self.scale * scale_change).clamp(0.1, 10.0); // Constrain zoom levels
-                                if self.scale != new_scale {
-                                    // Zoom relative to the cursor position (if available)
-                                    // This math ensures the point under the cursor stays under the cursor after scaling/translation
-                                    let zoom_factor = new_scale / self.scale;
-                                    // Adjust translation based on cursor position
-                                    let pos_as_vec =
-                                        Vector::new(cursor_position.x, cursor_position.y);
-                                    self.translation =
-                                        pos_as_vec + (self.translation - pos_as_vec) * zoom_factor;
-                                    self.scale = new_scale;
-                                    println!(
-                                        "Pixels Scrolled - x:{} y:{} scale: {}",
-                                        x, y, self.scale
-                                    );
+                                if !modifiers.control() {
+                                    self.viewport.pan(Vector::new(x, y));
                                     return true;
                                 }
+                                ZOOM_STEP.powf(y / PIXELS_PER_LINE)
                             }
-                        }
-                        false
+                        };
+                        let new_scale = self.viewport.scale * zoom_factor;
+                        return self.viewport.zoom_about(screen_position, new_scale);
                     }
-                    _ => false,
+                    _ => {}
                 };
             }
             _ => {}
         }
         false
     }
+
+    /// Returns true while the view is dragged with the mouse.
+    pub fn is_panning(&self) -> bool {
+        self.drag_start.is_some()
+    }
+
+    /// Returns true while the view glides on after a drag.
+    pub fn is_gliding(&self) -> bool {
+        self.inertia.is_gliding()
+    }
+
+    /// Moves a gliding view on to the given time. Returns true if the view moved.
+    pub fn glide(&mut self, now: Instant) -> bool {
+        match self.inertia.step(now) {
+            Some(delta) => {
+                self.viewport.pan(delta);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a view that shows the given world point in the center of the viewport at the
+    /// given scale.
+    pub fn centered_on(center: Point, scale: f32, viewport_size: Size) -> Self {
+        Self::new(Viewport::centered_on(center, scale, viewport_size))
+    }
+
+    /// Returns a view that fits the given world rectangle into the viewport, leaving `margin`
+    /// pixels free on each side.
+    pub fn fitting(world_rect: Rectangle, viewport_size: Size, margin: f32) -> Self {
+        Self::new(Viewport::fitting(world_rect, viewport_size, margin))
+    }
 }
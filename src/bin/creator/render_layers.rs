@@ -0,0 +1,113 @@
+//
+// Layered rendering for the creator canvas.
+//
+// Concept:
+//  - static layer: the grid and all shapes that are not selected. It is kept in a cache and only
+//    redrawn when the document or the viewport changes.
+//  - dynamic layer: the selected shape and its handles. Redrawn on every frame, which is cheap
+//    because it only contains a few paths.
+//  - overlay layer: guides, cursors and the minimap. Also redrawn on every frame.
+//
+// Dragging a shape around therefore only redraws the dragged shape, not the whole board.
+//
+// Measuring: `--frame-stats` writes the average time spent in `draw` to stderr every
+// `STATS_INTERVAL` frames. Compare the cached static layer with a redraw on every frame on the same
+// generated board, e.g. with 10000 shapes:
+//
+//   cargo run --release --bin creator -- --bench-scene 10000 --frame-stats
+//   cargo run --release --bin creator -- --bench-scene 10000 --frame-stats --no-cache
+//
+// Zoom to fit so that the whole scene is in view, deselect everything and move the mouse over the
+// canvas to get frames drawn.
+//
+// With the cache the draw time should no longer depend on the size of the board, only on the
+// selection.
+//
+
+use std::cell::RefCell;
+use std::time::Duration;
+
+use iced::widget::canvas::{Cache, Frame, Geometry};
+use iced::{Renderer, Size};
+
+/// Number of frames after which the average draw time is reported (with `--frame-stats`).
+const STATS_INTERVAL: u32 = 120;
+
+#[derive(Debug)]
+pub struct RenderLayers {
+    static_layer: Cache,
+    /// If false the static layer is rebuilt on every frame (used to compare performance).
+    caching: bool,
+    /// If true the average draw time is written to stderr every `STATS_INTERVAL` frames.
+    report_stats: bool,
+    stats: RefCell<FrameStats>,
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl RenderLayers {
+    pub fn new(caching: bool) -> Self {
+        Self {
+            static_layer: Cache::new(),
+            caching,
+            report_stats: false,
+            stats: RefCell::new(FrameStats::default()),
+        }
+    }
+
+    /// Enables or disables the report of the average draw time.
+    pub fn report_stats(mut self, enabled: bool) -> Self {
+        self.report_stats = enabled;
+        self
+    }
+
+    /// Forces the static layer to be redrawn. Call this whenever the document or the viewport changes.
+    pub fn invalidate(&self) {
+        self.static_layer.clear();
+    }
+
+    /// Returns the static layer, drawing it with `draw_fn` only if it isn't cached.
+    pub fn draw_static(
+        &self,
+        renderer: &Renderer,
+        size: Size,
+        draw_fn: impl FnOnce(&mut Frame),
+    ) -> Geometry {
+        if self.caching {
+            self.static_layer.draw(renderer, size, draw_fn)
+        } else {
+            let mut frame = Frame::new(renderer, size);
+            draw_fn(&mut frame);
+            frame.into_geometry()
+        }
+    }
+
+    /// Records the time it took to draw a frame and reports the average from time to time.
+    pub fn record_frame(&self, duration: Duration) {
+        if !self.report_stats {
+            return;
+        }
+        let mut stats = self.stats.borrow_mut();
+        stats.frames += 1;
+        stats.total += duration;
+        if stats.frames == STATS_INTERVAL {
+            eprintln!(
+                "draw: {:.3} ms per frame on average over {} frames (static layer cache {})",
+                stats.total.as_secs_f64() * 1000.0 / stats.frames as f64,
+                stats.frames,
+                if self.caching { "on" } else { "off" }
+            );
+            *stats = FrameStats::default();
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FrameStats {
+    frames: u32,
+    total: Duration,
+}
@@ -0,0 +1,325 @@
+//
+// All kinds of shapes that can be placed on the board.
+//
+// Concept:
+//  - Every kind of shape has a state struct (the data) and a shape struct (the behaviour:
+//    update, draw, draw_handles), like RectangleState and RectangleShape.
+//  - The Shape enum holds the state of one shape and dispatches to the right shape struct, so
+//    the document and the canvas don't need to know about the different kinds.
+//
+
+use iced::{Point, Rectangle, Theme, Vector, mouse, widget::canvas::Event, widget::canvas::Frame};
+
+use iced_hello_world::affine::Affine2;
+use iced_hello_world::resize_interaction;
+use iced_hello_world::vector_path::VectorPath;
+
+use crate::message::Tool;
+use crate::placement::PlacementHandle;
+use crate::shape_frame::{FrameDrag, FrameShape, FrameState};
+use crate::shape_image::{ImageDrag, ImageShape, ImageState};
+use crate::shape_ink::{InkShape, InkState};
+use crate::shape_path::{PathShape, PathState};
+use crate::shape_rectangle::{RectangleShape, RectangleState};
+use crate::shape_svg::{SvgShape, SvgState};
+
+#[derive(Clone, Debug)]
+pub enum Shape {
+    Rectangle(RectangleState),
+    Path(PathState),
+    Ink(InkState),
+    Image(ImageState),
+    Svg(SvgState),
+    Frame(FrameState),
+}
+
+impl From<RectangleState> for Shape {
+    fn from(state: RectangleState) -> Self {
+        Shape::Rectangle(state)
+    }
+}
+
+impl From<PathState> for Shape {
+    fn from(state: PathState) -> Self {
+        Shape::Path(state)
+    }
+}
+
+impl From<InkState> for Shape {
+    fn from(state: InkState) -> Self {
+        Shape::Ink(state)
+    }
+}
+
+impl From<ImageState> for Shape {
+    fn from(state: ImageState) -> Self {
+        Shape::Image(state)
+    }
+}
+
+impl From<SvgState> for Shape {
+    fn from(state: SvgState) -> Self {
+        Shape::Svg(state)
+    }
+}
+
+impl From<FrameState> for Shape {
+    fn from(state: FrameState) -> Self {
+        Shape::Frame(state)
+    }
+}
+
+impl Shape {
+    /// The axis aligned bounding box in world coordinates.
+    pub fn bounds(&self) -> Rectangle {
+        match self {
+            Shape::Rectangle(state) => state.rect,
+            Shape::Path(state) => state.bounds(),
+            Shape::Ink(state) => state.bounds(),
+            Shape::Image(state) => state.bounds(),
+            Shape::Svg(state) => state.bounds(),
+            Shape::Frame(state) => state.bounds(),
+        }
+    }
+
+    /// The outline of the shape as a path in world coordinates.
+    pub fn to_path(&self) -> VectorPath {
+        match self {
+            Shape::Rectangle(state) => VectorPath::polygon(
+                &state
+                    .local_to_world()
+                    .apply_rectangle(&Rectangle::with_size(state.rect.size())),
+            ),
+            Shape::Path(state) => state.path.clone(),
+            Shape::Ink(state) => state.to_path(),
+            Shape::Image(state) => VectorPath::polygon(&state.placement.corners()),
+            Shape::Svg(state) => VectorPath::polygon(&state.placement.corners()),
+            Shape::Frame(state) => VectorPath::polygon(&state.corners()),
+        }
+    }
+
+    /// Moves the shape by the given world distance.
+    pub fn translate(&mut self, delta: Vector) {
+        match self {
+            Shape::Rectangle(state) => state.rect = state.rect + delta,
+            Shape::Path(state) => {
+                state.path = state.path.transformed(&Affine2::translate(delta));
+            }
+            Shape::Ink(state) => {
+                for point in &mut state.points {
+                    point.position = point.position + delta;
+                }
+            }
+            Shape::Image(state) => state.placement.translate(delta),
+            Shape::Svg(state) => state.placement.translate(delta),
+            Shape::Frame(state) => state.rect = state.rect + delta,
+        }
+    }
+
+    /// Returns true if the point is on the shape or at most `tolerance` away from it.
+    pub fn hit(&self, point: Point, tolerance: f32) -> bool {
+        match self {
+            Shape::Rectangle(state) => state.hit(point, tolerance),
+            Shape::Path(state) => state.hit(point, tolerance),
+            Shape::Ink(state) => state.hit(point, tolerance),
+            Shape::Image(state) => state.hit(point, tolerance),
+            Shape::Svg(state) => state.hit(point, tolerance),
+            Shape::Frame(state) => state.hit(point, tolerance),
+        }
+    }
+
+    /// Returns true if the given tool can grab a handle of the (selected) shape at the point.
+    /// Handles may lie outside of the shape, like the rotate handle of an image.
+    pub fn hit_handle(&self, point: Point, tolerance: f32, scale: f32, tool: Tool) -> bool {
+        match self {
+            Shape::Rectangle(_) | Shape::Ink(_) => false,
+            Shape::Path(state) => tool == Tool::Node && state.hit_node(point, tolerance),
+            Shape::Image(state) => state.handle_at(point, tolerance, scale, tool).is_some(),
+            Shape::Svg(state) => {
+                tool == Tool::Select && state.placement.handle_at(point, tolerance, scale).is_some()
+            }
+            Shape::Frame(state) => {
+                tool == Tool::Select && state.hit_handle(point, tolerance, scale)
+            }
+        }
+    }
+
+    /// Returns true while the shape (or a part of it) is dragged.
+    pub fn is_dragging(&self) -> bool {
+        match self {
+            Shape::Rectangle(state) => state.drag_start.is_some(),
+            Shape::Path(state) => state.is_dragging(),
+            Shape::Ink(state) => state.drag_start.is_some(),
+            Shape::Image(state) => state.drag.is_some(),
+            Shape::Svg(state) => state.drag.is_some(),
+            Shape::Frame(state) => state.drag.is_some(),
+        }
+    }
+
+    /// The mouse cursor over the shape, or while it is dragged. Selected shapes also show the
+    /// cursors of their handles. Returns `None` if the tool does nothing with the shape at the
+    /// point.
+    pub fn mouse_interaction(
+        &self,
+        point: Point,
+        tolerance: f32,
+        scale: f32,
+        tool: Tool,
+        selected: bool,
+    ) -> Option<mouse::Interaction> {
+        // the frame resize handle sits in the lower right corner
+        let frame_resize =
+            |state: &FrameState| resize_interaction(state.corners()[2] - state.rect.center());
+
+        // while dragging
+        match self {
+            Shape::Rectangle(state) if state.drag_start.is_some() => {
+                return Some(mouse::Interaction::Move);
+            }
+            Shape::Ink(state) if state.drag_start.is_some() => {
+                return Some(mouse::Interaction::Move);
+            }
+            Shape::Path(state) if state.dragged_handle.is_some() => {
+                return Some(mouse::Interaction::Grabbing);
+            }
+            Shape::Path(state) if state.drag_start.is_some() => {
+                return Some(mouse::Interaction::Move);
+            }
+            Shape::Image(state) => match state.drag {
+                Some(ImageDrag::Placement(drag)) => {
+                    return Some(state.placement.drag_interaction(drag));
+                }
+                Some(ImageDrag::Crop(corner)) => {
+                    return Some(
+                        state
+                            .placement
+                            .handle_interaction(PlacementHandle::Corner(corner)),
+                    );
+                }
+                None => {}
+            },
+            Shape::Svg(state) if state.drag.is_some() => {
+                return state
+                    .drag
+                    .map(|drag| state.placement.drag_interaction(drag));
+            }
+            Shape::Frame(state) => match state.drag {
+                Some(FrameDrag::Move(_)) => return Some(mouse::Interaction::Move),
+                Some(FrameDrag::Resize) => return Some(frame_resize(state)),
+                None => {}
+            },
+            _ => {}
+        }
+
+        // over a handle of a selected shape
+        if selected {
+            let handle = match self {
+                Shape::Rectangle(_) | Shape::Ink(_) => None,
+                Shape::Path(state) if tool == Tool::Node => {
+                    if state.path.handle_at(point, tolerance).is_some() {
+                        Some(mouse::Interaction::Pointer)
+                    } else {
+                        // a click on a segment inserts a node
+                        state
+                            .path
+                            .segment_at(point, tolerance)
+                            .map(|_| mouse::Interaction::Crosshair)
+                    }
+                }
+                Shape::Path(_) => None,
+                Shape::Image(state) => state
+                    .handle_at(point, tolerance, scale, tool)
+                    .map(|handle| state.placement.handle_interaction(handle)),
+                Shape::Svg(state) if tool == Tool::Select => state
+                    .placement
+                    .handle_at(point, tolerance, scale)
+                    .map(|handle| state.placement.handle_interaction(handle)),
+                Shape::Svg(_) => None,
+                Shape::Frame(state) => (tool == Tool::Select
+                    && state.hit_handle(point, tolerance, scale))
+                .then(|| frame_resize(state)),
+            };
+            if handle.is_some() {
+                return handle;
+            }
+        }
+
+        // over the shape
+        if !self.hit(point, tolerance) {
+            return None;
+        }
+        match self {
+            // rectangles and strokes are moved with every tool
+            Shape::Rectangle(_) | Shape::Ink(_) => Some(mouse::Interaction::Move),
+            // a double click on the name renames the frame
+            Shape::Frame(state) if tool == Tool::Select && state.hit_title(point, tolerance) => {
+                Some(mouse::Interaction::Text)
+            }
+            Shape::Path(_) | Shape::Image(_) | Shape::Svg(_) | Shape::Frame(_) => {
+                (tool == Tool::Select).then_some(mouse::Interaction::Move)
+            }
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        event: Event,
+        cursor_position: Point,
+        tolerance: f32,
+        scale: f32,
+        tool: Tool,
+    ) -> bool {
+        match self {
+            Shape::Rectangle(state) => {
+                RectangleShape.update(state, event, cursor_position, tolerance)
+            }
+            Shape::Path(state) => PathShape.update(state, event, cursor_position, tolerance, tool),
+            Shape::Ink(state) => InkShape.update(state, event, cursor_position, tolerance),
+            Shape::Image(state) => {
+                ImageShape.update(state, event, cursor_position, tolerance, scale, tool)
+            }
+            Shape::Svg(state) => {
+                SvgShape.update(state, event, cursor_position, tolerance, scale, tool)
+            }
+            // frames are only moved and resized with the select tool
+            Shape::Frame(state) => {
+                tool == Tool::Select
+                    && FrameShape.update(state, event, cursor_position, tolerance, scale)
+            }
+        }
+    }
+
+    pub fn draw(
+        &self,
+        frame: &mut Frame,
+        theme: &Theme,
+        cursor: mouse::Cursor,
+        scale: f32,
+        selected: bool,
+    ) {
+        match self {
+            Shape::Rectangle(state) => RectangleShape.draw(state, frame, theme, cursor, selected),
+            Shape::Path(state) => PathShape.draw(state, frame, theme, selected),
+            Shape::Ink(state) => InkShape.draw(state, frame, theme, selected),
+            Shape::Image(state) => ImageShape.draw(state, frame, scale, selected),
+            Shape::Svg(state) => SvgShape.draw(state, frame, scale, selected),
+            Shape::Frame(state) => FrameShape.draw(state, frame, scale, selected),
+        }
+    }
+
+    pub fn draw_handles(&self, frame: &mut Frame, scale: f32, tool: Tool) {
+        match self {
+            Shape::Rectangle(state) => RectangleShape.draw_handles(state, frame, scale),
+            Shape::Path(state) => PathShape.draw_handles(state, frame, scale, tool),
+            // the outline drawn by `draw` marks a selected stroke
+            Shape::Ink(_) => {}
+            Shape::Image(state) => ImageShape.draw_handles(state, frame, scale, tool),
+            Shape::Svg(state) => SvgShape.draw_handles(state, frame, scale, tool),
+            Shape::Frame(state) => {
+                if tool == Tool::Select {
+                    FrameShape.draw_handles(state, frame, scale);
+                }
+            }
+        }
+    }
+}
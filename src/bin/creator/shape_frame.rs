@@ -0,0 +1,176 @@
+//
+// This file defines a frame: a named rectangular region of the board, used to present or
+// export a part of the board.
+//
+// Concept:
+//  - A frame is drawn as a thin outline with its name above the upper left corner. It has no
+//    fill, so it doesn't matter whether the contents were added before or after the frame.
+//  - Only the outline and the name can be grabbed, clicks inside go to the contents.
+//  - Dragging a frame moves the shapes inside of it as well (see
+//    CreatorApp::move_frame_contents). The handle at the lower right corner resizes the frame
+//    without touching the contents.
+//  - The name is drawn in world units, so it scales with the zoom like the contents.
+//
+
+use iced::{
+    Color, Pixels, Point, Rectangle, Size, alignment, mouse,
+    widget::canvas::{Event, Frame, Path, Stroke, Text},
+};
+use iced_hello_world::hit_test::distance_to_polyline;
+
+use crate::placement::{HANDLE_SIZE, draw_corner_handles};
+
+/// Font size of the name in world units
+const TITLE_SIZE: f32 = 16.0;
+/// Distance between the name and the outline in world units
+const TITLE_GAP: f32 = 4.0;
+/// Smallest width or height of a frame in world units
+const MIN_SIZE: f32 = 20.0;
+const OUTLINE_COLOR: Color = Color::from_rgb(0.45, 0.45, 0.5);
+
+/// What is dragged with the mouse.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameDrag {
+    /// Last cursor position
+    Move(Point),
+    /// The lower right corner
+    Resize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FrameShape;
+
+#[derive(Clone, Debug)]
+pub struct FrameState {
+    pub rect: Rectangle,
+    pub name: String,
+    pub drag: Option<FrameDrag>,
+}
+
+impl FrameState {
+    pub fn new(name: impl Into<String>, rect: Rectangle) -> Self {
+        Self {
+            rect,
+            name: name.into(),
+            drag: None,
+        }
+    }
+
+    /// The area of the name above the frame (estimated, the text isn't measured).
+    fn title_rect(&self) -> Rectangle {
+        let width = (self.name.chars().count() as f32 * TITLE_SIZE * 0.6).min(self.rect.width);
+        Rectangle::new(
+            Point::new(self.rect.x, self.rect.y - TITLE_GAP - TITLE_SIZE * 1.3),
+            Size::new(width, TITLE_SIZE * 1.3),
+        )
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.rect.union(&self.title_rect())
+    }
+
+    pub fn corners(&self) -> [Point; 4] {
+        let r = self.rect;
+        [
+            Point::new(r.x, r.y),
+            Point::new(r.x + r.width, r.y),
+            Point::new(r.x + r.width, r.y + r.height),
+            Point::new(r.x, r.y + r.height),
+        ]
+    }
+
+    /// Returns true if the point is on the outline or the name.
+    pub fn hit(&self, point: Point, tolerance: f32) -> bool {
+        let corners = self.corners();
+        let outline = [corners[0], corners[1], corners[2], corners[3], corners[0]];
+        distance_to_polyline(point, &outline) <= tolerance || self.hit_title(point, tolerance)
+    }
+
+    /// Returns true if the point is on the name above the frame.
+    pub fn hit_title(&self, point: Point, tolerance: f32) -> bool {
+        self.title_rect().expand(tolerance).contains(point)
+    }
+
+    /// Returns true if the point is on the resize handle in the lower right corner.
+    pub fn hit_handle(&self, point: Point, tolerance: f32, scale: f32) -> bool {
+        let reach = tolerance + HANDLE_SIZE / scale / 2.0;
+        point.distance(self.corners()[2]) <= reach
+    }
+
+    /// Returns true while the frame (and its contents) is moved.
+    pub fn is_moving(&self) -> bool {
+        matches!(self.drag, Some(FrameDrag::Move(_)))
+    }
+}
+
+impl FrameShape {
+    pub fn update(
+        &self,
+        state: &mut FrameState,
+        event: Event,
+        cursor_position: Point,
+        tolerance: f32,
+        scale: f32,
+    ) -> bool {
+        let Event::Mouse(mouse_event) = event else {
+            return false;
+        };
+        match mouse_event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                state.drag = if state.hit_handle(cursor_position, tolerance, scale) {
+                    Some(FrameDrag::Resize)
+                } else if state.hit(cursor_position, tolerance) {
+                    Some(FrameDrag::Move(cursor_position))
+                } else {
+                    None
+                };
+                state.drag.is_some()
+            }
+            mouse::Event::CursorMoved { .. } => {
+                match state.drag {
+                    Some(FrameDrag::Move(last)) => {
+                        state.rect = state.rect + (cursor_position - last);
+                        state.drag = Some(FrameDrag::Move(cursor_position));
+                    }
+                    Some(FrameDrag::Resize) => {
+                        state.rect.width = (cursor_position.x - state.rect.x).max(MIN_SIZE);
+                        state.rect.height = (cursor_position.y - state.rect.y).max(MIN_SIZE);
+                    }
+                    None => return false,
+                }
+                true
+            }
+            mouse::Event::ButtonReleased(mouse::Button::Left) if state.drag.is_some() => {
+                state.drag = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn draw(&self, state: &FrameState, frame: &mut Frame, scale: f32, selected: bool) {
+        let color = if selected {
+            Color::from_rgb(1.0, 0.6, 0.0)
+        } else {
+            OUTLINE_COLOR
+        };
+        frame.stroke(
+            &Path::rectangle(state.rect.position(), state.rect.size()),
+            Stroke::default()
+                .with_color(color)
+                .with_width(if selected { 2.0 } else { 1.0 } / scale),
+        );
+        frame.fill_text(Text {
+            content: state.name.clone(),
+            position: Point::new(state.rect.x, state.rect.y - TITLE_GAP),
+            color,
+            size: Pixels(TITLE_SIZE),
+            vertical_alignment: alignment::Vertical::Bottom,
+            ..Text::default()
+        });
+    }
+
+    pub fn draw_handles(&self, state: &FrameState, frame: &mut Frame, scale: f32) {
+        draw_corner_handles(frame, &[state.corners()[2]], scale, Color::WHITE);
+    }
+}
@@ -0,0 +1,375 @@
+//
+// This file defines a raster image (PNG or JPEG) placed on the board.
+//
+// Concept:
+//  - The decoded image is shared between all copies of the state (Arc), the canvas clones the
+//    shapes on every event.
+//  - `crop` is the visible part of the image in pixels. It is shown in the placement rectangle,
+//    stretched to fit.
+//  - Select tool: drag the body to move, the corner handles to resize (keeping the aspect ratio)
+//    and the handle above the image to rotate.
+//  - Node tool: the corner handles change the crop, the visible part doesn't move on the board.
+//    While the crop is changed the whole image is shown, the cut off parts shaded.
+//  - For display the cropped image is kept in several sizes, each half of the previous one.
+//    At low zoom a smaller version is drawn, which looks better and saves GPU memory.
+//
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use iced::{
+    Color, Point, Radians, Rectangle, Size, Vector, mouse,
+    widget::canvas::{self, Event, Fill, Frame, Image, Stroke, fill},
+    widget::image::Handle,
+};
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+
+use crate::message::Tool;
+use crate::placement::{Placement, PlacementDrag, PlacementHandle, draw_corner_handles};
+
+/// The smallest downscaled version is at most this many pixels wide and high.
+const SMALLEST_LEVEL: u32 = 64;
+/// Shade over the cut off parts while the crop is changed
+const CROPPED_SHADE: Color = Color::from_rgba(0.0, 0.0, 0.0, 0.6);
+
+/// Where the image data comes from, decides how it is saved.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageSource {
+    /// The data is written into the board file
+    Embedded,
+    /// The board file refers to the image file
+    File(PathBuf),
+}
+
+/// The decoded image, shared by all shapes showing it.
+pub struct ImageData {
+    /// The file content (PNG or JPEG), written unchanged when saving
+    pub bytes: Vec<u8>,
+    pub source: ImageSource,
+    pub pixels: RgbaImage,
+    /// The whole image, shown while cropping
+    full: Handle,
+}
+
+impl std::fmt::Debug for ImageData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImageData")
+            .field("bytes", &self.bytes.len())
+            .field("source", &self.source)
+            .field("size", &self.pixels.dimensions())
+            .finish()
+    }
+}
+
+impl ImageData {
+    pub fn decode(bytes: Vec<u8>, source: ImageSource) -> io::Result<Self> {
+        let pixels = image::load_from_memory(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            .to_rgba8();
+        let full = Handle::from_rgba(pixels.width(), pixels.height(), pixels.as_raw().clone());
+        Ok(Self {
+            bytes,
+            source,
+            pixels,
+            full,
+        })
+    }
+
+    /// Reads an image file. With `embed` the data is saved in the board file, otherwise the
+    /// board file refers to `path`.
+    pub fn open(path: &Path, embed: bool) -> io::Result<Self> {
+        let source = if embed {
+            ImageSource::Embedded
+        } else {
+            ImageSource::File(path.to_path_buf())
+        };
+        Self::decode(std::fs::read(path)?, source)
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.pixels.width() as f32, self.pixels.height() as f32)
+    }
+
+    /// The MIME type of the file content, for data URLs.
+    pub fn mime_type(&self) -> &'static str {
+        match image::guess_format(&self.bytes) {
+            Ok(image::ImageFormat::Jpeg) => "image/jpeg",
+            _ => "image/png",
+        }
+    }
+}
+
+/// What is dragged with the mouse.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageDrag {
+    /// Select tool: move, resize or rotate
+    Placement(PlacementDrag),
+    /// Node tool: move a corner of the crop
+    Crop(usize),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImageShape;
+
+#[derive(Clone, Debug)]
+pub struct ImageState {
+    pub data: Arc<ImageData>,
+    pub placement: Placement,
+    /// The visible part of the image in pixels
+    pub crop: Rectangle,
+    /// The cropped image, each level half the size of the previous one
+    levels: Arc<Vec<Handle>>,
+    pub drag: Option<ImageDrag>,
+}
+
+impl ImageState {
+    /// Shows the whole image in the given rectangle.
+    pub fn new(data: Arc<ImageData>, rect: Rectangle) -> Self {
+        let crop = Rectangle::with_size(data.size());
+        Self::with_crop(data, Placement::new(rect), crop)
+    }
+
+    pub fn with_crop(data: Arc<ImageData>, placement: Placement, crop: Rectangle) -> Self {
+        let levels = Arc::new(build_levels(&data.pixels, crop));
+        Self {
+            data,
+            placement,
+            crop,
+            levels,
+            drag: None,
+        }
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.placement.bounds()
+    }
+
+    pub fn hit(&self, point: Point, tolerance: f32) -> bool {
+        self.placement.hit(point, tolerance)
+    }
+
+    /// Returns the handle the given tool can grab at the point.
+    pub fn handle_at(
+        &self,
+        point: Point,
+        tolerance: f32,
+        scale: f32,
+        tool: Tool,
+    ) -> Option<PlacementHandle> {
+        match tool {
+            Tool::Select => self.placement.handle_at(point, tolerance, scale),
+            // only the corners can be grabbed to crop
+            Tool::Node => self
+                .placement
+                .handle_at(point, tolerance, scale)
+                .filter(|handle| *handle != PlacementHandle::Rotate),
+            _ => None,
+        }
+    }
+
+    /// Pixels of the image per world unit.
+    fn pixels_per_unit(&self) -> Vector {
+        Vector::new(
+            self.crop.width / self.placement.rect.width.max(f32::EPSILON),
+            self.crop.height / self.placement.rect.height.max(f32::EPSILON),
+        )
+    }
+
+    /// The rectangle (local coordinates of the placement) covered by the whole image.
+    fn full_image_rect(&self) -> Rectangle {
+        let k = self.pixels_per_unit();
+        let size = self.data.size();
+        let rect = self.placement.rect;
+        Rectangle::new(
+            Point::new(rect.x - self.crop.x / k.x, rect.y - self.crop.y / k.y),
+            Size::new(size.width / k.x, size.height / k.y),
+        )
+    }
+
+    /// Moves a corner of the crop to the cursor. The other corners stay in place.
+    fn crop_to(&mut self, corner: usize, cursor: Point) {
+        let k = self.pixels_per_unit();
+        let local = self.placement.world_to_local().apply(cursor);
+        let rect = self.placement.rect;
+        let size = self.data.size();
+        // the cursor position in pixels of the image
+        let x = (self.crop.x + (local.x - rect.x) * k.x)
+            .round()
+            .clamp(0.0, size.width);
+        let y = (self.crop.y + (local.y - rect.y) * k.y)
+            .round()
+            .clamp(0.0, size.height);
+
+        let (mut left, mut top) = (self.crop.x, self.crop.y);
+        let (mut right, mut bottom) = (
+            self.crop.x + self.crop.width,
+            self.crop.y + self.crop.height,
+        );
+        match corner {
+            0 => (left, top) = (x.min(right - 1.0), y.min(bottom - 1.0)),
+            1 => (right, top) = (x.max(left + 1.0), y.min(bottom - 1.0)),
+            2 => (right, bottom) = (x.max(left + 1.0), y.max(top + 1.0)),
+            _ => (left, bottom) = (x.min(right - 1.0), y.max(top + 1.0)),
+        }
+        let crop = Rectangle::new(Point::new(left, top), Size::new(right - left, bottom - top));
+
+        let local_rect = Rectangle::new(
+            Point::new(
+                rect.x + (crop.x - self.crop.x) / k.x,
+                rect.y + (crop.y - self.crop.y) / k.y,
+            ),
+            Size::new(crop.width / k.x, crop.height / k.y),
+        );
+        self.placement.set_local_rect(local_rect);
+        self.crop = crop;
+    }
+
+    /// Picks the level for the current zoom: the smallest one that still has at least one
+    /// pixel per screen pixel.
+    fn level(&self, scale: f32) -> &Handle {
+        let screen_width = self.placement.rect.width * scale;
+        let ratio = self.crop.width / screen_width.max(1.0);
+        let index = ratio.log2().floor().max(0.0) as usize;
+        &self.levels[index.min(self.levels.len() - 1)]
+    }
+}
+
+/// Crops the image and halves it until it fits into `SMALLEST_LEVEL`.
+fn build_levels(pixels: &RgbaImage, crop: Rectangle) -> Vec<Handle> {
+    let mut image = imageops::crop_imm(
+        pixels,
+        crop.x as u32,
+        crop.y as u32,
+        (crop.width as u32).max(1),
+        (crop.height as u32).max(1),
+    )
+    .to_image();
+    let mut levels = Vec::new();
+    loop {
+        let (width, height) = image.dimensions();
+        levels.push(Handle::from_rgba(width, height, image.as_raw().clone()));
+        if width.max(height) <= SMALLEST_LEVEL {
+            break;
+        }
+        image = imageops::resize(
+            &image,
+            (width / 2).max(1),
+            (height / 2).max(1),
+            FilterType::Triangle,
+        );
+    }
+    levels
+}
+
+impl ImageShape {
+    pub fn update(
+        &self,
+        state: &mut ImageState,
+        event: Event,
+        cursor_position: Point,
+        tolerance: f32,
+        scale: f32,
+        tool: Tool,
+    ) -> bool {
+        let Event::Mouse(mouse_event) = event else {
+            return false;
+        };
+        match mouse_event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                state.drag = match tool {
+                    Tool::Select => state
+                        .placement
+                        .start_drag(cursor_position, tolerance, scale)
+                        .map(ImageDrag::Placement),
+                    Tool::Node => match state.handle_at(cursor_position, tolerance, scale, tool) {
+                        Some(PlacementHandle::Corner(corner)) => Some(ImageDrag::Crop(corner)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                state.drag.is_some()
+            }
+            mouse::Event::CursorMoved { .. } => {
+                match &mut state.drag {
+                    Some(ImageDrag::Placement(drag)) => {
+                        state.placement.drag(drag, cursor_position, true, scale);
+                    }
+                    Some(ImageDrag::Crop(corner)) => {
+                        let corner = *corner;
+                        state.crop_to(corner, cursor_position);
+                    }
+                    None => return false,
+                }
+                true
+            }
+            mouse::Event::ButtonReleased(mouse::Button::Left) if state.drag.is_some() => {
+                if matches!(state.drag, Some(ImageDrag::Crop(_))) {
+                    state.levels = Arc::new(build_levels(&state.data.pixels, state.crop));
+                }
+                state.drag = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn draw(&self, state: &ImageState, frame: &mut Frame, scale: f32, selected: bool) {
+        let placement = state.placement;
+        let center = placement.center();
+        let cropping = matches!(state.drag, Some(ImageDrag::Crop(_)));
+
+        frame.with_save(|frame| {
+            frame.translate(Vector::new(center.x, center.y));
+            frame.rotate(Radians(placement.rotation_degrees.to_radians()));
+            let to_frame = |rect: Rectangle| rect - Vector::new(center.x, center.y);
+            if cropping {
+                // the levels are rebuilt when the button is released, until then the whole
+                // image is shown and the cut off parts are shaded
+                let full = to_frame(state.full_image_rect());
+                frame.draw_image(full, Image::new(state.data.full.clone()));
+                let outside = canvas::Path::new(|builder| {
+                    builder.rectangle(full.position(), full.size());
+                    let crop = to_frame(placement.rect);
+                    builder.rectangle(crop.position(), crop.size());
+                });
+                frame.fill(
+                    &outside,
+                    Fill {
+                        style: CROPPED_SHADE.into(),
+                        rule: fill::Rule::EvenOdd,
+                    },
+                );
+            } else {
+                frame.draw_image(
+                    to_frame(placement.rect),
+                    Image::new(state.level(scale).clone()),
+                );
+            }
+        });
+
+        if selected || cropping {
+            frame.stroke(
+                &placement.outline(),
+                Stroke::default()
+                    .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+                    .with_width(2.0 / scale),
+            );
+        }
+    }
+
+    pub fn draw_handles(&self, state: &ImageState, frame: &mut Frame, scale: f32, tool: Tool) {
+        match tool {
+            Tool::Select => state.placement.draw_frame(frame, scale),
+            // crop handles are filled, to tell them from the resize handles
+            Tool::Node => draw_corner_handles(
+                frame,
+                &state.placement.corners(),
+                scale,
+                Color::from_rgb(1.0, 0.6, 0.0),
+            ),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//
+// This file defines a freehand ink stroke for the creator.
+//
+// Concept:
+//  - The pen tool records the cursor samples while the left button is held (see CanvasProgram).
+//    When the button is released the samples become an InkState: the widths are computed from
+//    the drawing speed and the points are simplified, so only a few points are stored.
+//  - The stored points are smoothed every time the stroke is drawn.
+//  - With the select tool the stroke can be dragged around like the other shapes.
+//
+
+use std::time::Instant;
+
+use iced_hello_world::hit_test::distance_to_segment;
+use iced_hello_world::ink::{self, InkPoint, InkSample, PenStyle};
+use iced_hello_world::vector_path::VectorPath;
+
+use iced::{
+    Color, Point, Rectangle, Size, Theme,
+    widget::canvas::{Event, Frame, Path, Stroke, stroke},
+};
+
+/// Maximum distance (in world units) between the recorded and the simplified stroke.
+const SIMPLIFY_TOLERANCE: f32 = 0.75;
+/// Number of Chaikin iterations when drawing.
+const SMOOTHING_ITERATIONS: usize = 2;
+
+#[derive(Clone, Debug, Default)]
+pub struct InkShape;
+
+#[derive(Clone, Debug)]
+pub struct InkState {
+    pub points: Vec<InkPoint>,
+    /// Last cursor position while the stroke is dragged
+    pub drag_start: Option<Point>,
+}
+
+impl InkState {
+    pub fn new(points: Vec<InkPoint>) -> Self {
+        Self {
+            points,
+            drag_start: None,
+        }
+    }
+
+    /// Creates a stroke from recorded samples (in world coordinates). `scale` is the zoom factor
+    /// while drawing: the pen has the same width on screen at every zoom level.
+    pub fn from_samples(samples: &[InkSample], scale: f32) -> Self {
+        let style = PenStyle {
+            max_width: PenStyle::default().max_width / scale,
+            min_width: PenStyle::default().min_width / scale,
+            fast_speed: PenStyle::default().fast_speed / scale,
+            ..PenStyle::default()
+        };
+        let points = ink::widths_from_speed(samples, &style);
+        Self::new(ink::simplify(&points, SIMPLIFY_TOLERANCE / scale))
+    }
+
+    /// The points of the stroke as it is drawn.
+    pub fn smoothed(&self) -> Vec<InkPoint> {
+        ink::smooth(&self.points, SMOOTHING_ITERATIONS)
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        let max_width = self.points.iter().map(|p| p.width).fold(0.0, f32::max);
+        self.points
+            .iter()
+            .map(|p| Rectangle::new(p.position, Size::ZERO))
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_default()
+            .expand(max_width / 2.0)
+    }
+
+    pub fn hit(&self, point: Point, tolerance: f32) -> bool {
+        if let [single] = self.points.as_slice() {
+            return single.position.distance(point) <= single.width / 2.0 + tolerance;
+        }
+        self.points.windows(2).any(|pair| {
+            let width = pair[0].width.max(pair[1].width);
+            distance_to_segment(point, pair[0].position, pair[1].position)
+                <= width / 2.0 + tolerance
+        })
+    }
+
+    /// The outline of the stroke as a closed path.
+    pub fn to_path(&self) -> VectorPath {
+        VectorPath::polygon(&ink::outline(&self.smoothed()))
+    }
+}
+
+impl InkShape {
+    pub fn update(
+        &self,
+        state: &mut InkState,
+        event: Event,
+        cursor_position: Point,
+        tolerance: f32,
+    ) -> bool {
+        let Event::Mouse(mouse_event) = event else {
+            return false;
+        };
+        match mouse_event {
+            iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left)
+                if state.hit(cursor_position, tolerance) =>
+            {
+                state.drag_start = Some(cursor_position);
+                true
+            }
+            iced::mouse::Event::CursorMoved { .. } if state.drag_start.is_some() => {
+                let delta = cursor_position - state.drag_start.unwrap_or(cursor_position);
+                for point in &mut state.points {
+                    point.position = point.position + delta;
+                }
+                state.drag_start = Some(cursor_position);
+                true
+            }
+            iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left)
+                if state.drag_start.is_some() =>
+            {
+                state.drag_start = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Draws the stroke in the text color of the theme.
+    pub fn draw(&self, state: &InkState, frame: &mut Frame, theme: &Theme, selected: bool) {
+        let points = state.smoothed();
+        draw_stroke(frame, &points, theme.palette().text);
+
+        // Outline the selected stroke with a thin line along its center
+        if selected {
+            let path = Path::new(|builder| {
+                for (i, point) in points.iter().enumerate() {
+                    if i == 0 {
+                        builder.move_to(point.position);
+                    } else {
+                        builder.line_to(point.position);
+                    }
+                }
+            });
+            frame.stroke(
+                &path,
+                Stroke::default()
+                    .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+                    .with_width(1.0),
+            );
+        }
+    }
+}
+
+/// Draws a stroke of variable width as one round capped line per segment.
+pub fn draw_stroke(frame: &mut Frame, points: &[InkPoint], color: Color) {
+    if let [single] = points {
+        frame.fill(&Path::circle(single.position, single.width / 2.0), color);
+    }
+    for pair in points.windows(2) {
+        frame.stroke(
+            &Path::line(pair[0].position, pair[1].position),
+            Stroke::default()
+                .with_color(color)
+                .with_width((pair[0].width + pair[1].width) / 2.0)
+                .with_line_cap(stroke::LineCap::Round),
+        );
+    }
+}
+
+/// The samples of a stroke that is being drawn with the pen tool.
+#[derive(Clone, Debug)]
+pub struct InkRecorder {
+    started: Instant,
+    pub samples: Vec<InkSample>,
+}
+
+impl InkRecorder {
+    pub fn new(position: Point) -> Self {
+        Self {
+            started: Instant::now(),
+            samples: vec![InkSample {
+                position,
+                time: 0.0,
+            }],
+        }
+    }
+
+    pub fn record(&mut self, position: Point) {
+        self.samples.push(InkSample {
+            position,
+            time: self.started.elapsed().as_secs_f32(),
+        });
+    }
+
+    /// Draws the recorded samples with a constant width (the final widths are computed when
+    /// the stroke is finished).
+    pub fn draw(&self, frame: &mut Frame, scale: f32, theme: &Theme) {
+        let points: Vec<InkPoint> = self
+            .samples
+            .iter()
+            .map(|sample| InkPoint {
+                position: sample.position,
+                width: PenStyle::default().max_width / scale,
+            })
+            .collect();
+        draw_stroke(frame, &points, theme.palette().text);
+    }
+}
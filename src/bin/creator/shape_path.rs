@@ -0,0 +1,214 @@
+//
+// This file defines an editable vector path for the creator.
+//
+// Concept:
+//  - The path is stored in world coordinates (see iced_hello_world::vector_path).
+//  - With the select tool the whole path is dragged around.
+//  - With the node tool the anchors and control handles of the selected path are dragged.
+//    Clicking on a segment inserts a new node there. The last clicked node is the selected node,
+//    the node commands of the toolbar (delete, smooth/corner) work on it.
+//
+
+use iced_hello_world::affine::Affine2;
+use iced_hello_world::hit_test::FillRule;
+use iced_hello_world::vector_path::{PathHandle, VectorPath};
+
+use iced::{
+    Color, Point, Rectangle, Size, Theme, Vector, mouse,
+    widget::canvas::{Event, Frame, Path, Stroke},
+};
+
+use crate::message::Tool;
+
+/// Size of the node handles in pixels
+const HANDLE_SIZE: f32 = 8.0;
+/// Width of the outline in world units
+pub const STROKE_WIDTH: f32 = 2.0;
+
+#[derive(Clone, Debug, Default)]
+pub struct PathShape;
+
+#[derive(Clone, Debug)]
+pub struct PathState {
+    pub path: VectorPath,
+    /// Last cursor position while the whole path is dragged
+    pub drag_start: Option<Point>,
+    /// The handle that is dragged with the node tool
+    pub dragged_handle: Option<PathHandle>,
+    /// The node the node commands work on
+    pub selected_node: Option<usize>,
+}
+
+impl PathState {
+    pub fn new(path: VectorPath) -> Self {
+        Self {
+            path,
+            drag_start: None,
+            dragged_handle: None,
+            selected_node: None,
+        }
+    }
+
+    /// A closed wave like shape around the given center, used for new paths.
+    pub fn sample(center: Point) -> Self {
+        let path = VectorPath::from_svg_data(
+            "M -60 20 C -60 -40 -20 -40 0 -10 C 20 20 60 20 60 -20 L 60 40 L -60 40 Z",
+        )
+        .unwrap_or_default()
+        .transformed(&Affine2::translate(Vector::new(center.x, center.y)));
+        Self::new(path)
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.path.bounds()
+    }
+
+    /// Returns true if the point is on the outline (or inside a closed path).
+    pub fn hit(&self, point: Point, tolerance: f32) -> bool {
+        (self.path.is_closed() && self.path.contains(point, FillRule::EvenOdd, tolerance))
+            || self.path.distance_to(point, tolerance) <= STROKE_WIDTH / 2.0 + tolerance
+    }
+
+    /// Returns true if the point is on a handle or a segment, i.e. the node tool can do
+    /// something there.
+    pub fn hit_node(&self, point: Point, tolerance: f32) -> bool {
+        self.path.handle_at(point, tolerance).is_some()
+            || self.path.segment_at(point, tolerance).is_some()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag_start.is_some() || self.dragged_handle.is_some()
+    }
+}
+
+impl PathShape {
+    pub fn update(
+        &self,
+        state: &mut PathState,
+        event: Event,
+        cursor_position: Point,
+        tolerance: f32,
+        tool: Tool,
+    ) -> bool {
+        let Event::Mouse(mouse_event) = event else {
+            return false;
+        };
+        match (mouse_event, tool) {
+            (mouse::Event::ButtonPressed(mouse::Button::Left), Tool::Node) => {
+                if let Some(handle) = state.path.handle_at(cursor_position, tolerance) {
+                    state.dragged_handle = Some(handle);
+                    if let PathHandle::Anchor(index) = handle {
+                        state.selected_node = Some(index);
+                    }
+                    return true;
+                }
+                if let Some((segment, t)) = state.path.segment_at(cursor_position, tolerance) {
+                    state.selected_node = state.path.insert_node(segment, t);
+                    state.dragged_handle = state.selected_node.map(PathHandle::Anchor);
+                    return true;
+                }
+                if state.hit(cursor_position, tolerance) {
+                    state.selected_node = None;
+                    return true;
+                }
+            }
+            (mouse::Event::ButtonPressed(mouse::Button::Left), Tool::Select)
+                if state.hit(cursor_position, tolerance) =>
+            {
+                state.drag_start = Some(cursor_position);
+                return true;
+            }
+            (mouse::Event::CursorMoved { .. }, _) => {
+                if let Some(handle) = state.dragged_handle {
+                    state.path.move_handle(handle, cursor_position);
+                    return true;
+                }
+                if let Some(start) = state.drag_start {
+                    let delta = cursor_position - start;
+                    state.path = state.path.transformed(&Affine2::translate(delta));
+                    state.drag_start = Some(cursor_position);
+                    return true;
+                }
+            }
+            (mouse::Event::ButtonReleased(mouse::Button::Left), _) if state.is_dragging() => {
+                state.drag_start = None;
+                state.dragged_handle = None;
+                return true;
+            }
+            _ => {}
+        }
+
+        // No events were handled.
+        false
+    }
+
+    /// Draws the path filled with the primary color of the theme and outlined in its text color.
+    pub fn draw(&self, state: &PathState, frame: &mut Frame, theme: &Theme, selected: bool) {
+        let palette = theme.palette();
+        let path = state.path.to_canvas_path();
+        if state.path.is_closed() {
+            frame.fill(&path, palette.primary);
+        }
+        frame.stroke(
+            &path,
+            Stroke::default()
+                .with_color(palette.text)
+                .with_width(STROKE_WIDTH),
+        );
+
+        // Outline the selected path
+        if selected {
+            frame.stroke(
+                &path,
+                Stroke::default()
+                    .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+                    .with_width(3.0),
+            );
+        }
+    }
+
+    /// Draws the handles of the path. With the node tool these are the anchors and control
+    /// points, otherwise the corners of the bounding box. The handles have the same size on
+    /// screen at every zoom level.
+    pub fn draw_handles(&self, state: &PathState, frame: &mut Frame, scale: f32, tool: Tool) {
+        let size = HANDLE_SIZE / scale;
+        let outline = Stroke::default()
+            .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+            .with_width(1.0 / scale);
+
+        if tool == Tool::Select {
+            let bounds = state.bounds();
+            for corner in [
+                Point::new(bounds.x, bounds.y),
+                Point::new(bounds.x + bounds.width, bounds.y),
+                Point::new(bounds.x + bounds.width, bounds.y + bounds.height),
+                Point::new(bounds.x, bounds.y + bounds.height),
+            ] {
+                let top_left = Point::new(corner.x - size / 2.0, corner.y - size / 2.0);
+                frame.fill_rectangle(top_left, Size::new(size, size), Color::WHITE);
+                frame.stroke(&Path::rectangle(top_left, Size::new(size, size)), outline);
+            }
+            return;
+        }
+
+        // control handles: a line from the anchor to a small circle
+        for (_, control, anchor) in state.path.controls() {
+            frame.stroke(&Path::line(anchor, control), outline);
+            let circle = Path::circle(control, size / 2.5);
+            frame.fill(&circle, Color::WHITE);
+            frame.stroke(&circle, outline);
+        }
+
+        // anchors: squares, the selected one filled
+        for (index, anchor) in state.path.anchors() {
+            let top_left = Point::new(anchor.x - size / 2.0, anchor.y - size / 2.0);
+            let fill = if state.selected_node == Some(index) {
+                Color::from_rgb(1.0, 0.6, 0.0)
+            } else {
+                Color::WHITE
+            };
+            frame.fill_rectangle(top_left, Size::new(size, size), fill);
+            frame.stroke(&Path::rectangle(top_left, Size::new(size, size)), outline);
+        }
+    }
+}
@@ -9,11 +9,17 @@
 //  - the rectangle is defined by its top-left corner position, width, height, and fill color.
 //
 
+use iced_hello_world::affine::Affine2;
+use iced_hello_world::hit_test::hit_rotated_rectangle;
+
 use iced::{
-    Color, Point, Rectangle, Size, Theme, mouse,
-    widget::canvas::{Event, Frame},
+    Color, Point, Rectangle, Size, Theme, Vector, mouse,
+    widget::canvas::{Event, Frame, Stroke},
 };
 
+/// Size of the resize handles in pixels
+const HANDLE_SIZE: f32 = 8.0;
+
 #[derive(Clone, Debug, Default)]
 pub struct RectangleShape;
 
@@ -53,6 +59,17 @@ impl RectangleState {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Transform from the local coordinates of the rectangle (origin in the upper left corner) to
+    /// world coordinates.
+    pub fn local_to_world(&self) -> Affine2 {
+        Affine2::translate(Vector::new(self.rect.x, self.rect.y))
+    }
+
+    /// Returns true if the point is inside the rectangle or at most `tolerance` away from it.
+    pub fn hit(&self, point: Point, tolerance: f32) -> bool {
+        hit_rotated_rectangle(point, self.rect.center(), self.rect.size(), 0.0, tolerance)
+    }
     #[allow(dead_code)]
     pub fn with_dimensions(
         position: Point,
@@ -74,39 +91,43 @@ impl RectangleState {
 }
 
 impl RectangleShape {
-    pub fn update(&self, state: &mut RectangleState, event: Event, cursor_position: Point) -> bool {
+    pub fn update(
+        &self,
+        state: &mut RectangleState,
+        event: Event,
+        cursor_position: Point,
+        tolerance: f32,
+    ) -> bool {
         // we might add more patterns in the future so kill clippy
         #[allow(clippy::single_match)]
         match event {
             Event::Mouse(mouse_event) => {
                 match mouse_event {
-                    mouse::Event::ButtonPressed(mouse::Button::Left) => {
-                        // Check if the cursor is inside the rectangle
-                        if state.rect.contains(cursor_position) {
-                            println!("Rectangle clicked at {:?}", cursor_position);
-                            state.drag_start = Some(cursor_position);
-                            state.drag_offset = Some(Point {
-                                x: cursor_position.x - state.rect.x,
-                                y: cursor_position.y - state.rect.y,
-                            });
-                            return true;
-                        }
+                    // Check if the cursor is inside the rectangle
+                    mouse::Event::ButtonPressed(mouse::Button::Left)
+                        if state.hit(cursor_position, tolerance) =>
+                    {
+                        state.drag_start = Some(cursor_position);
+                        // the grabbed point in local coordinates stays under the cursor
+                        state.drag_offset = state
+                            .local_to_world()
+                            .invert()
+                            .map(|world_to_local| world_to_local.apply(cursor_position));
+                        return true;
                     }
-                    mouse::Event::ButtonReleased(mouse::Button::Left) => {
-                        // Handle button release if needed
-                        if state.drag_start.is_some() {
-                            println!("Rectangle released at {:?}", cursor_position);
-                            state.rect.x = cursor_position.x - state.drag_offset.unwrap().x;
-                            state.rect.y = cursor_position.y - state.drag_offset.unwrap().y;
-                            state.drag_start = None;
-                            state.drag_offset = None;
-                            return true;
-                        }
+                    // Handle button release if needed
+                    mouse::Event::ButtonReleased(mouse::Button::Left)
+                        if state.drag_start.is_some() =>
+                    {
+                        state.rect.x = cursor_position.x - state.drag_offset.unwrap().x;
+                        state.rect.y = cursor_position.y - state.drag_offset.unwrap().y;
+                        state.drag_start = None;
+                        state.drag_offset = None;
+                        return true;
                     }
                     mouse::Event::CursorMoved { .. } => {
                         // Handle cursor movement for dragging
                         if let Some(_start) = state.drag_start {
-                            println!("Rectangle dragged to {:?}", cursor_position);
                             state.rect.x = cursor_position.x - state.drag_offset.unwrap().x;
                             state.rect.y = cursor_position.y - state.drag_offset.unwrap().y;
                             return true;
@@ -128,9 +149,10 @@ impl RectangleShape {
         state: &RectangleState,
         frame: &mut Frame,
         // _renderer: &Renderer,
-        _theme: &Theme,
+        theme: &Theme,
         // _bounds: Rectangle,
         _cursor: mouse::Cursor,
+        selected: bool,
     ) //-> Vec<Geometry>
     {
         // let mut frame = Frame::new(renderer, bounds.size());
@@ -140,8 +162,40 @@ impl RectangleShape {
             Point::new(state.rect.x, state.rect.y),
             Size::new(state.rect.width, state.rect.height),
         );
-        frame.fill(&rect, Color::from_rgb(0.0, 0.5, 0.5));
+        frame.fill(&rect, theme.palette().primary);
+
+        // Outline the selected rectangle
+        if selected {
+            frame.stroke(
+                &rect,
+                Stroke::default()
+                    .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+                    .with_width(3.0),
+            );
+        }
 
         // vec![frame.into_geometry()]
     }
+
+    /// Draws the resize handles at the corners of the rectangle. The handles have the same size
+    /// on screen at every zoom level.
+    pub fn draw_handles(&self, state: &RectangleState, frame: &mut Frame, scale: f32) {
+        let size = HANDLE_SIZE / scale;
+        let rect = state.rect;
+        for corner in [
+            Point::new(rect.x, rect.y),
+            Point::new(rect.x + rect.width, rect.y),
+            Point::new(rect.x + rect.width, rect.y + rect.height),
+            Point::new(rect.x, rect.y + rect.height),
+        ] {
+            let top_left = Point::new(corner.x - size / 2.0, corner.y - size / 2.0);
+            frame.fill_rectangle(top_left, Size::new(size, size), Color::WHITE);
+            frame.stroke(
+                &iced::widget::canvas::Path::rectangle(top_left, Size::new(size, size)),
+                Stroke::default()
+                    .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+                    .with_width(1.0 / scale),
+            );
+        }
+    }
 }
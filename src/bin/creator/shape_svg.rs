@@ -0,0 +1,224 @@
+//
+// This file defines an SVG asset (e.g. an icon) placed on the board.
+//
+// Concept:
+//  - The original SVG source is kept unchanged. It is saved in the board file and written back
+//    into the SVG export as it is, only wrapped in an element that places it.
+//  - The canvas rasterizes the SVG at the size it has on screen, so it stays crisp at every
+//    zoom level.
+//  - Like images the asset fills its placement rectangle and can be moved, resized (keeping the
+//    aspect ratio) and rotated with the select tool.
+//
+
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use iced::{
+    Color, Point, Radians, Rectangle, Size, Vector, mouse,
+    widget::canvas::{Event, Frame, Stroke},
+    widget::svg::Handle,
+};
+
+use crate::message::Tool;
+use crate::placement::{Placement, PlacementDrag};
+
+/// Size of SVGs without width, height and viewBox
+const DEFAULT_SIZE: Size = Size::new(100.0, 100.0);
+
+#[derive(Clone, Debug, Default)]
+pub struct SvgShape;
+
+#[derive(Clone, Debug)]
+pub struct SvgState {
+    /// The SVG document as it was loaded
+    pub source: Arc<str>,
+    handle: Handle,
+    pub placement: Placement,
+    pub drag: Option<PlacementDrag>,
+}
+
+impl SvgState {
+    pub fn new(source: impl Into<Arc<str>>, placement: Placement) -> Self {
+        let source = source.into();
+        let handle = Handle::from_memory(source.as_bytes().to_vec());
+        Self {
+            source,
+            handle,
+            placement,
+            drag: None,
+        }
+    }
+
+    /// Reads an SVG file. It gets its own size, with the upper left corner at the origin.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        if !source.contains("<svg") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no <svg> element found",
+            ));
+        }
+        let size = intrinsic_size(&source).unwrap_or(DEFAULT_SIZE);
+        Ok(Self::new(
+            source,
+            Placement::new(Rectangle::with_size(size)),
+        ))
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        self.placement.bounds()
+    }
+
+    pub fn hit(&self, point: Point, tolerance: f32) -> bool {
+        self.placement.hit(point, tolerance)
+    }
+
+    /// The source without the XML declaration and doctype, ready to be nested into another SVG
+    /// document.
+    pub fn root_element(&self) -> &str {
+        let start = self.source.find("<svg").unwrap_or(0);
+        self.source[start..].trim_end()
+    }
+
+    /// The size the SVG document declares, see `intrinsic_size`.
+    pub fn intrinsic_size(&self) -> Size {
+        intrinsic_size(&self.source).unwrap_or(DEFAULT_SIZE)
+    }
+}
+
+/// Returns the size declared by the root element: `width` and `height`, or the size of the
+/// `viewBox` if they are missing or relative (e.g. "100%").
+pub fn intrinsic_size(source: &str) -> Option<Size> {
+    let start = source.find("<svg")?;
+    let tag = &source[start..start + source[start..].find('>')?];
+
+    let length = |name: &str| {
+        let value = attribute(tag, name)?;
+        if value.trim_end().ends_with('%') {
+            return None;
+        }
+        leading_number(value)
+    };
+    let view_box = attribute(tag, "viewBox").and_then(|value| {
+        let numbers: Vec<f32> = value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| s.parse().ok())
+            .collect();
+        (numbers.len() == 4).then(|| Size::new(numbers[2], numbers[3]))
+    });
+
+    let size = match (length("width"), length("height"), view_box) {
+        (Some(width), Some(height), _) => Size::new(width, height),
+        // keep the aspect ratio of the viewBox if only one length is given
+        (Some(width), None, Some(view_box)) => {
+            Size::new(width, width * view_box.height / view_box.width)
+        }
+        (None, Some(height), Some(view_box)) => {
+            Size::new(height * view_box.width / view_box.height, height)
+        }
+        (_, _, Some(view_box)) => view_box,
+        _ => return None,
+    };
+    (size.width > 0.0 && size.height > 0.0 && size.width.is_finite() && size.height.is_finite())
+        .then_some(size)
+}
+
+/// The value of an attribute in a start tag, quoted with " or '.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(index) = rest.find(name) {
+        let before = rest[..index].chars().next_back();
+        let after = rest[index + name.len()..].trim_start();
+        rest = &rest[index + name.len()..];
+        // skip matches inside other names, e.g. "stroke-width" when looking for "width"
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(after) = after.strip_prefix('=') else {
+            continue;
+        };
+        let after = after.trim_start();
+        let quote = after.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            continue;
+        }
+        let value = &after[1..];
+        return value.find(quote).map(|end| &value[..end]);
+    }
+    None
+}
+
+/// Parses the number at the start of a length like "24px".
+fn leading_number(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+' || c == 'e'))
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+impl SvgShape {
+    pub fn update(
+        &self,
+        state: &mut SvgState,
+        event: Event,
+        cursor_position: Point,
+        tolerance: f32,
+        scale: f32,
+        tool: Tool,
+    ) -> bool {
+        let Event::Mouse(mouse_event) = event else {
+            return false;
+        };
+        match mouse_event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) if tool == Tool::Select => {
+                state.drag = state
+                    .placement
+                    .start_drag(cursor_position, tolerance, scale);
+                state.drag.is_some()
+            }
+            mouse::Event::CursorMoved { .. } => {
+                let Some(drag) = &mut state.drag else {
+                    return false;
+                };
+                state.placement.drag(drag, cursor_position, true, scale);
+                true
+            }
+            mouse::Event::ButtonReleased(mouse::Button::Left) if state.drag.is_some() => {
+                state.drag = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn draw(&self, state: &SvgState, frame: &mut Frame, scale: f32, selected: bool) {
+        let placement = state.placement;
+        let center = placement.center();
+        frame.with_save(|frame| {
+            frame.translate(Vector::new(center.x, center.y));
+            frame.rotate(Radians(placement.rotation_degrees.to_radians()));
+            frame.draw_svg(
+                placement.rect - Vector::new(center.x, center.y),
+                &state.handle,
+            );
+        });
+
+        if selected {
+            frame.stroke(
+                &placement.outline(),
+                Stroke::default()
+                    .with_color(Color::from_rgb(1.0, 0.6, 0.0))
+                    .with_width(2.0 / scale),
+            );
+        }
+    }
+
+    pub fn draw_handles(&self, state: &SvgState, frame: &mut Frame, scale: f32, tool: Tool) {
+        if tool == Tool::Select {
+            state.placement.draw_frame(frame, scale);
+        }
+    }
+}
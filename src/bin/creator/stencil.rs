@@ -0,0 +1,283 @@
+//
+// Stencils: reusable groups of shapes that are dragged from the stencil panel onto the board.
+//
+// Concept:
+//  - A stencil is a named group of shapes in local coordinates: the upper left corner of their
+//    bounding box is at the origin.
+//  - Stencils are grouped into libraries. Three libraries are built in (flowchart, architecture
+//    and parametric curves), more are loaded from the JSON files in the library directory.
+//  - "Save as component" turns the selected shapes into a stencil of the user library, which is
+//    written to `components.json` in the library directory.
+//  - The panel shows a small preview of every stencil, drawn by the shapes themselves.
+//
+
+use std::path::{Path, PathBuf};
+
+use iced::widget::canvas::{self, Geometry, Program};
+use iced::{Point, Rectangle, Renderer, Size, Theme, Vector, mouse};
+use iced_hello_world::curves::{Curve, CurveKind, curve_points};
+use iced_hello_world::recognize::Recognized;
+use iced_hello_world::vector_path::VectorPath;
+
+use crate::board_file;
+use crate::message::Message;
+use crate::shape::Shape;
+use crate::shape_path::PathState;
+use crate::shape_rectangle::RectangleState;
+
+/// Name of the file in the library directory that holds the user's components.
+pub const COMPONENTS_FILE: &str = "components.json";
+/// Free space in pixels around the stencil previews.
+const PREVIEW_MARGIN: f32 = 4.0;
+/// Half the size of the curve stencils.
+const CURVE_RADIUS: f32 = 60.0;
+/// Largest distance between the sampled curve stencils and the real curves.
+const CURVE_TOLERANCE: f32 = 0.25;
+
+#[derive(Clone, Debug)]
+pub struct Stencil {
+    pub name: String,
+    /// The shapes, the upper left corner of their bounding box is at the origin
+    pub shapes: Vec<Shape>,
+}
+
+impl Stencil {
+    /// Creates a stencil from shapes anywhere on the board.
+    pub fn new(name: impl Into<String>, shapes: Vec<Shape>) -> Self {
+        let mut shapes = shapes;
+        if let Some(bounds) = bounds(&shapes) {
+            for shape in &mut shapes {
+                shape.translate(Vector::new(-bounds.x, -bounds.y));
+            }
+        }
+        Self {
+            name: name.into(),
+            shapes,
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        bounds(&self.shapes).unwrap_or_default().size()
+    }
+
+    /// Returns copies of the shapes centered on the given world point.
+    pub fn placed_at(&self, center: Point) -> Vec<Shape> {
+        let size = self.size();
+        let offset = Vector::new(center.x - size.width / 2.0, center.y - size.height / 2.0);
+        self.shapes
+            .iter()
+            .map(|shape| {
+                let mut shape = shape.clone();
+                shape.translate(offset);
+                shape
+            })
+            .collect()
+    }
+}
+
+fn bounds(shapes: &[Shape]) -> Option<Rectangle> {
+    shapes.iter().map(Shape::bounds).reduce(|a, b| a.union(&b))
+}
+
+#[derive(Clone, Debug)]
+pub struct StencilLibrary {
+    pub name: String,
+    pub stencils: Vec<Stencil>,
+    /// The file the library was loaded from, None for the built-in libraries
+    pub file: Option<PathBuf>,
+}
+
+/// The built-in libraries followed by the libraries in the given directory (in file name
+/// order). Files that can't be read are skipped.
+pub fn libraries(directory: &Path) -> Vec<StencilLibrary> {
+    let mut libraries = vec![flowchart(), architecture(), curves()];
+    let mut files: Vec<PathBuf> = std::fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    files.sort();
+    for file in files {
+        match board_file::load_library(&file) {
+            Ok(library) => libraries.push(library),
+            Err(e) => eprintln!("Could not load stencils from {}: {}", file.display(), e),
+        }
+    }
+    libraries
+}
+
+fn path(name: &str, svg_data: &str) -> Stencil {
+    Stencil::new(name, vec![path_shape(svg_data)])
+}
+
+fn path_shape(svg_data: &str) -> Shape {
+    PathState::new(VectorPath::from_svg_data(svg_data).unwrap_or_default()).into()
+}
+
+/// Path data of a rectangle with rounded corners (quarter circles approximated by cubics).
+fn rounded_rectangle(width: f32, height: f32, radius: f32) -> String {
+    let k = radius * (1.0 - 0.552_284_8);
+    let (w, h, r) = (width, height, radius);
+    format!(
+        "M {r} 0 L {} 0 C {} 0 {w} {k} {w} {r} L {w} {} C {w} {} {} {h} {} {h} \
+         L {r} {h} C {k} {h} 0 {} 0 {} L 0 {r} C 0 {k} {k} 0 {r} 0 Z",
+        w - r,
+        w - k,
+        h - r,
+        h - k,
+        w - k,
+        w - r,
+        h - k,
+        h - r,
+    )
+}
+
+/// Process, decision, terminator, data, document and arrow.
+pub fn flowchart() -> StencilLibrary {
+    StencilLibrary {
+        name: "Flowchart".to_string(),
+        stencils: vec![
+            Stencil::new(
+                "Process",
+                vec![RectangleState::with_dimensions(Point::ORIGIN, 120.0, 60.0).into()],
+            ),
+            path("Decision", "M 60 0 L 120 40 L 60 80 L 0 40 Z"),
+            path("Terminator", &rounded_rectangle(120.0, 60.0, 30.0)),
+            path("Data", "M 20 0 L 120 0 L 100 60 L 0 60 Z"),
+            path("Document", "M 0 0 L 120 0 L 120 60 C 80 40 40 80 0 60 Z"),
+            Stencil::new(
+                "Arrow",
+                vec![
+                    PathState::new(
+                        Recognized::Arrow {
+                            start: Point::ORIGIN,
+                            end: Point::new(100.0, 0.0),
+                        }
+                        .to_path(),
+                    )
+                    .into(),
+                ],
+            ),
+        ],
+        file: None,
+    }
+}
+
+/// Service, database, queue and user.
+pub fn architecture() -> StencilLibrary {
+    StencilLibrary {
+        name: "Architecture".to_string(),
+        stencils: vec![
+            path("Service", &rounded_rectangle(120.0, 70.0, 10.0)),
+            Stencil::new(
+                "Database",
+                vec![
+                    // body and the front edge of the top
+                    path_shape(
+                        "M 0 12 C 0 5.4 22.4 0 50 0 C 77.6 0 100 5.4 100 12 L 100 68 \
+                         C 100 74.6 77.6 80 50 80 C 22.4 80 0 74.6 0 68 Z",
+                    ),
+                    path_shape("M 0 12 C 0 18.6 22.4 24 50 24 C 77.6 24 100 18.6 100 12"),
+                ],
+            ),
+            Stencil::new(
+                "Queue",
+                vec![
+                    RectangleState::with_dimensions(Point::ORIGIN, 120.0, 50.0).into(),
+                    path_shape("M 75 0 L 75 50 M 90 0 L 90 50 M 105 0 L 105 50"),
+                ],
+            ),
+            Stencil::new(
+                "User",
+                vec![
+                    PathState::new(
+                        Recognized::Ellipse {
+                            center: Point::new(30.0, 18.0),
+                            radii: Vector::new(18.0, 18.0),
+                            rotation_degrees: 0.0,
+                        }
+                        .to_path(),
+                    )
+                    .into(),
+                    path_shape("M 0 80 C 0 50 10 40 30 40 C 50 40 60 50 60 80 Z"),
+                ],
+            ),
+        ],
+        file: None,
+    }
+}
+
+/// A closed path sampled from the curve.
+fn curve(name: &str, curve: Curve) -> Stencil {
+    let points = curve_points(&curve, Point::ORIGIN, CURVE_RADIUS, CURVE_TOLERANCE);
+    Stencil::new(
+        name,
+        vec![PathState::new(VectorPath::polygon(&points)).into()],
+    )
+}
+
+/// Squircle, astroid, spirographs, Lissajous figure and roses.
+pub fn curves() -> StencilLibrary {
+    StencilLibrary {
+        name: "Curves".to_string(),
+        stencils: vec![
+            curve("Squircle", CurveKind::Superellipse.default_curve()),
+            curve(
+                "Astroid",
+                Curve::Superellipse {
+                    exponent: 2.0 / 3.0,
+                    aspect: 1.0,
+                },
+            ),
+            curve("Spirograph", CurveKind::Hypotrochoid.default_curve()),
+            curve("Epitrochoid", CurveKind::Epitrochoid.default_curve()),
+            curve("Lissajous", CurveKind::Lissajous.default_curve()),
+            curve("Rose", CurveKind::Rose.default_curve()),
+            curve(
+                "Rose 7/3",
+                Curve::Rose {
+                    petals: 7,
+                    divisor: 3,
+                },
+            ),
+        ],
+        file: None,
+    }
+}
+
+/// Draws a stencil scaled to fit into the canvas, for the stencil panel.
+pub struct StencilPreview<'a> {
+    pub stencil: &'a Stencil,
+}
+
+impl Program<Message> for StencilPreview<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let size = self.stencil.size();
+        let scale = ((bounds.width - 2.0 * PREVIEW_MARGIN) / size.width.max(1.0))
+            .min((bounds.height - 2.0 * PREVIEW_MARGIN) / size.height.max(1.0))
+            .min(1.0);
+        frame.translate(Vector::new(
+            (bounds.width - size.width * scale) / 2.0,
+            (bounds.height - size.height * scale) / 2.0,
+        ));
+        frame.scale(scale);
+        for shape in &self.stencil.shapes {
+            shape.draw(&mut frame, theme, mouse::Cursor::Unavailable, scale, false);
+        }
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,165 @@
+//
+// Export of the board as an SVG image.
+//
+// Concept:
+//  - World coordinates are written unchanged, the viewBox covers all shapes plus a margin.
+//  - The colors match the ones used on the canvas.
+//  - Images are always embedded as data URLs, so the SVG file works on its own. The crop is a
+//    nested <svg> whose viewBox is the visible part of the image.
+//  - SVG assets are written with their original source, nested into an <svg> that places them.
+//  - A single frame can be exported on its own: the viewBox is the frame and only the shapes
+//    touching it are written (the frame clips the rest).
+//
+
+use std::fmt::Write;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use iced::Rectangle;
+
+use crate::document::Document;
+use crate::shape::Shape;
+use crate::shape_path::STROKE_WIDTH;
+
+/// Free space in world units around the shapes.
+const MARGIN: f32 = 20.0;
+
+/// Returns the whole document as an SVG document.
+pub fn to_svg(document: &Document) -> String {
+    let bounds = document.bounds().unwrap_or_default().expand(MARGIN);
+    let mut svg = open_svg(bounds);
+    for (_, shape) in document.iter() {
+        write_shape(&mut svg, shape);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Returns the part of the document inside a frame as an SVG document, on a white background.
+/// The frames themselves are left out.
+pub fn frame_to_svg(document: &Document, rect: Rectangle) -> String {
+    let mut svg = open_svg(rect);
+    let _ = writeln!(
+        svg,
+        r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="white"/>"#,
+        rect.x, rect.y, rect.width, rect.height
+    );
+    for id in document.shapes_in(rect) {
+        match document.get(id) {
+            Some(Shape::Frame(_)) | None => {}
+            Some(shape) => write_shape(&mut svg, shape),
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The start tag of an SVG document showing the given world rectangle.
+fn open_svg(view_box: Rectangle) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        view_box.x, view_box.y, view_box.width, view_box.height, view_box.width, view_box.height
+    );
+    svg
+}
+
+fn write_shape(svg: &mut String, shape: &Shape) {
+    let _ = match shape {
+        Shape::Rectangle(state) => writeln!(
+            svg,
+            r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="rgb(0,128,128)"/>"#,
+            state.rect.x, state.rect.y, state.rect.width, state.rect.height
+        ),
+        Shape::Path(state) => writeln!(
+            svg,
+            r#"  <path d="{}" fill="{}" stroke="rgb(26,51,77)" stroke-width="{}"/>"#,
+            state.path.to_svg_data(),
+            if state.path.is_closed() {
+                "rgb(0,128,128)"
+            } else {
+                "none"
+            },
+            STROKE_WIDTH
+        ),
+        // variable width strokes are exported as their filled outline
+        Shape::Ink(state) => writeln!(
+            svg,
+            r#"  <path d="{}" fill="rgb(26,26,51)"/>"#,
+            state.to_path().to_svg_data()
+        ),
+        Shape::Image(state) => {
+            let rect = state.placement.rect;
+            let center = rect.center();
+            let size = state.data.size();
+            writeln!(
+                svg,
+                concat!(
+                    r#"  <g transform="rotate({} {} {})">"#,
+                    r#"<svg x="{}" y="{}" width="{}" height="{}" viewBox="{} {} {} {}" preserveAspectRatio="none">"#,
+                    r#"<image width="{}" height="{}" href="data:{};base64,{}"/></svg></g>"#
+                ),
+                state.placement.rotation_degrees,
+                center.x,
+                center.y,
+                rect.x,
+                rect.y,
+                rect.width,
+                rect.height,
+                state.crop.x,
+                state.crop.y,
+                state.crop.width,
+                state.crop.height,
+                size.width,
+                size.height,
+                state.data.mime_type(),
+                BASE64.encode(&state.data.bytes)
+            )
+        }
+        Shape::Svg(state) => {
+            let rect = state.placement.rect;
+            let center = rect.center();
+            let size = state.intrinsic_size();
+            writeln!(
+                svg,
+                concat!(
+                    r#"  <g transform="rotate({} {} {})">"#,
+                    r#"<svg x="{}" y="{}" width="{}" height="{}" viewBox="0 0 {} {}" preserveAspectRatio="none">"#,
+                    "\n{}\n  </svg></g>"
+                ),
+                state.placement.rotation_degrees,
+                center.x,
+                center.y,
+                rect.x,
+                rect.y,
+                rect.width,
+                rect.height,
+                size.width,
+                size.height,
+                state.root_element()
+            )
+        }
+        Shape::Frame(state) => writeln!(
+            svg,
+            concat!(
+                r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="rgb(115,115,128)"/>"#,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="16" fill="rgb(115,115,128)">{}</text>"#
+            ),
+            state.rect.x,
+            state.rect.y,
+            state.rect.width,
+            state.rect.height,
+            state.rect.x,
+            state.rect.y - 4.0,
+            escape(&state.name)
+        ),
+    };
+}
+
+/// Escapes the characters that have a meaning in XML text.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
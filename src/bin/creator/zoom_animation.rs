@@ -0,0 +1,64 @@
+//
+// Smooth transition between two views of the canvas.
+//
+// The animation interpolates the world point in the center of the viewport linearly and the
+// scale logarithmically, so zooming by a factor of 10 looks as fast at the start as at the end.
+//
+
+use std::time::{Duration, Instant};
+
+use iced::{Point, Size};
+
+use crate::program_canvas::PanZoomState;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ZoomAnimation {
+    from_center: Point,
+    from_scale: f32,
+    to_center: Point,
+    to_scale: f32,
+    start: Instant,
+    duration: Duration,
+}
+
+impl ZoomAnimation {
+    pub const DEFAULT_DURATION: Duration = Duration::from_millis(300);
+
+    pub fn new(from: &PanZoomState, to: &PanZoomState, viewport_size: Size) -> Self {
+        Self {
+            from_center: from.viewport.center(viewport_size),
+            from_scale: from.viewport.scale,
+            to_center: to.viewport.center(viewport_size),
+            to_scale: to.viewport.scale,
+            start: Instant::now(),
+            duration: Self::DEFAULT_DURATION,
+        }
+    }
+
+    /// Returns the view at the given time and whether the animation is finished.
+    pub fn state_at(&self, now: Instant, viewport_size: Size) -> (PanZoomState, bool) {
+        let elapsed = now.saturating_duration_since(self.start);
+        let t = (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0);
+        let t = ease_in_out(t);
+
+        let center = Point::new(
+            self.from_center.x + (self.to_center.x - self.from_center.x) * t,
+            self.from_center.y + (self.to_center.y - self.from_center.y) * t,
+        );
+        let scale = (self.from_scale.ln() + (self.to_scale.ln() - self.from_scale.ln()) * t).exp();
+
+        (
+            PanZoomState::centered_on(center, scale, viewport_size),
+            elapsed >= self.duration,
+        )
+    }
+}
+
+// Cubic ease-in-out for t in 0..=1
+fn ease_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
@@ -1,53 +1,447 @@
-use iced::Element;
+//
+// The polygon of main_svg drawn with a canvas.
+//
+// Concept:
+//  - Both renderings use the same point list from the library, built from one polygon spec
+//    (regular polygon, star or polygram, rounded corners) and rotated here instead of with a
+//    transform, so they differ only in the way they get to the screen.
+//  - "Compare with SVG" shows the SVG rendering next to the canvas.
+//  - While the polygon spins, every frame gets a new rotation. The readout shows the averaged
+//    time between two frames and the time spent building the canvas geometry (in `draw`) and the
+//    SVG document (in `update`, whenever it is rebuilt). The rasterization of the SVG by the
+//    renderer is only part of the frame time.
+//
+
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::time::Instant;
+
 use iced::mouse;
-use iced::widget::canvas;
-use iced::{Color, Rectangle, Renderer, Theme};
+use iced::widget::canvas::{self, Stroke};
+use iced::widget::{
+    center, column, container, horizontal_space, pick_list, row, slider, svg, text, toggler,
+};
+use iced::{
+    Alignment, Color, Element, Fill, Length, Point, Rectangle, Renderer, Subscription, Task, Theme,
+    window,
+};
+use iced_hello_world::polygon::{
+    PolygonKind, PolygonSpec, canvas_path, polygon_points, svg_path_data,
+};
+use iced_hello_world::{hsb_to_rgb, rotate_point, theme};
+
+/// Width and height of the drawing, the same as the view box of main_svg.
+const VIEW_SIZE: f32 = 300.0;
+/// Degrees per second while spinning: one turn in six seconds like the default timeline of
+/// main_svg.
+const SPIN_SPEED: f32 = 60.0;
+/// Weight of the newest measurement in the averaged times.
+const SMOOTHING: f32 = 0.1;
 
+/// Command line options:
+/// - `--palettes <FILE>`: JSON file with additional color palettes (default `palettes.json`)
+/// - `--theme <NAME>`: the theme at start, e.g. "Dark"
 pub fn main() -> iced::Result {
+    let mut palette_file = PathBuf::from(theme::DEFAULT_PALETTE_FILE);
+    let mut theme_name = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--palettes" => palette_file = args.next().map(PathBuf::from).unwrap_or(palette_file),
+            "--theme" => theme_name = args.next(),
+            _ => eprintln!("ignoring unknown argument {}", arg),
+        }
+    }
+    let (themes, theme) = theme::select_theme(&palette_file, theme_name.as_deref());
+
     iced::application("iced canvas polygon", State::update, State::view)
-        //.subscription(State::subscription)
-        .run()
+        .subscription(State::subscription)
+        .theme(State::theme)
+        .run_with(move || {
+            let mut state = State {
+                themes,
+                theme,
+                ..State::default()
+            };
+            state.rebuild_svg();
+            (state, Task::none())
+        })
 }
 
 #[derive(Debug)]
 struct State {
-    edges: u32,
+    /// The polygon without the rotation
+    spec: PolygonSpec,
+    hue: f32,
+    saturation: f32,
+    brightness: f32,
+    /// In degrees
+    rotation: f32,
+    spinning: bool,
+    /// Show the SVG rendering next to the canvas
+    compare: bool,
+    /// Time of the previous frame while spinning
+    last_frame: Option<Instant>,
+    /// Averaged time between two frames in milliseconds, 0 while not spinning
+    frame_time: f32,
+    /// Averaged time for building the canvas geometry in milliseconds, measured in `draw`
+    canvas_time: Cell<f32>,
+    /// The SVG rendering, rebuilt in `update` while the comparison is shown
+    svg: Option<svg::Handle>,
+    /// Averaged time for building the SVG document in milliseconds
+    svg_time: f32,
+    /// Light, dark and the palettes of the palette file
+    themes: Vec<Theme>,
+    theme: Theme,
 }
 
 impl Default for State {
     fn default() -> Self {
-        Self { edges: 5 }
+        Self {
+            spec: PolygonSpec {
+                center: Point::new(VIEW_SIZE / 2.0, VIEW_SIZE / 2.0),
+                radius: 100.0,
+                ..PolygonSpec::default()
+            },
+            hue: 0.0,
+            saturation: 100.0,
+            brightness: 100.0,
+            rotation: 0.0,
+            spinning: false,
+            compare: true,
+            last_frame: None,
+            frame_time: 0.0,
+            canvas_time: Cell::new(0.0),
+            svg: None,
+            svg_time: 0.0,
+            themes: vec![Theme::Light, Theme::Dark],
+            theme: Theme::Light,
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    KindChanged(PolygonKind),
     EdgesChanged(u32),
+    InnerRadius(f32),
+    StepChanged(u32),
+    CornerRadius(f32),
+    RotationOffset(f32),
+    Hue(f32),
+    Saturation(f32),
+    Brightness(f32),
+    Rotation(f32),
+    ToggleSpin,
+    ToggleCompare,
+    /// A frame was drawn while spinning.
+    Frame(Instant),
+    ThemeChanged(Theme),
 }
 
 impl State {
     fn update(&mut self, message: Message) {
         match message {
+            Message::KindChanged(kind) => {
+                self.spec.kind = kind;
+            }
             Message::EdgesChanged(edges) => {
-                self.edges = edges.max(3); // Minimum 3 edges for a polygon
+                self.spec.points = edges.max(3); // Minimum 3 edges for a polygon
+            }
+            Message::InnerRadius(ratio) => {
+                self.spec.inner_ratio = ratio;
+            }
+            Message::StepChanged(step) => {
+                self.spec.step = step;
+            }
+            Message::CornerRadius(radius) => {
+                self.spec.corner_radius = radius;
+            }
+            Message::RotationOffset(degrees) => {
+                self.spec.rotation_offset_degrees = degrees;
             }
+            Message::Hue(hue) => {
+                self.hue = hue;
+            }
+            Message::Saturation(saturation) => {
+                self.saturation = saturation;
+            }
+            Message::Brightness(brightness) => {
+                self.brightness = brightness;
+            }
+            Message::Rotation(rotation) => {
+                self.rotation = rotation;
+            }
+            Message::ToggleSpin => {
+                self.spinning = !self.spinning;
+                self.last_frame = None;
+                self.frame_time = 0.0;
+            }
+            Message::ToggleCompare => {
+                self.compare = !self.compare;
+            }
+            Message::ThemeChanged(theme) => {
+                self.theme = theme;
+            }
+            Message::Frame(now) => {
+                if let Some(last) = self.last_frame {
+                    let elapsed = now.duration_since(last).as_secs_f32();
+                    self.rotation = (self.rotation + SPIN_SPEED * elapsed) % 360.0;
+                    self.frame_time = average(self.frame_time, elapsed * 1000.0);
+                }
+                self.last_frame = Some(now);
+            }
+        }
+        if self.compare {
+            self.rebuild_svg();
         }
     }
 
+    /// Builds the SVG rendering of the current polygon and measures the time it takes.
+    fn rebuild_svg(&mut self) {
+        let start = Instant::now();
+        let handle = svg::Handle::from_memory(self.svg_document().into_bytes());
+        let milliseconds = start.elapsed().as_secs_f32() * 1000.0;
+        self.svg_time = average(self.svg_time, milliseconds);
+        self.svg = Some(handle);
+    }
+
+    fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+
+    /// The outline of the polygon, rotated.
+    fn points(&self) -> Vec<Vec<Point>> {
+        let center = self.spec.center;
+        polygon_points(&self.spec)
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .map(|point| rotate_point(point, &center, self.rotation))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Fill and stroke color, the stroke is darker.
+    fn colors(&self) -> (Color, Color) {
+        let (r, g, b) = hsb_to_rgb(self.hue, self.saturation, self.brightness);
+        let fill = Color::from_rgba(r, g, b, 0.8);
+        let (r, g, b) = hsb_to_rgb(self.hue, self.saturation, self.brightness * 0.7);
+        (fill, Color::from_rgb(r, g, b))
+    }
+
+    fn svg_document(&self) -> String {
+        let (fill, stroke) = self.colors();
+        let rgb = |color: Color| {
+            let [r, g, b, _] = color.into_rgba8();
+            format!("rgb({},{},{})", r, g, b)
+        };
+        format!(
+            r#"<svg viewBox="0 0 {size} {size}" xmlns="http://www.w3.org/2000/svg">
+                <path d="{}" fill="{}" fill-opacity="0.8" stroke="{}" stroke-width="2"/>
+            </svg>"#,
+            svg_path_data(&self.points()),
+            rgb(fill),
+            rgb(stroke),
+            size = VIEW_SIZE
+        )
+    }
+
     fn view<'a>(&'a self) -> Element<'a, Message> {
-        //canvas(Circle { radius: 50.0 }).into()
-        view(self)
+        let drawing = |title: &'static str, content: Element<'a, Message>| {
+            column![
+                text(title).size(15),
+                container(content).style(container::rounded_box)
+            ]
+            .spacing(5)
+            .align_x(Alignment::Center)
+        };
+
+        let mut drawings = row![drawing(
+            "Canvas",
+            canvas::Canvas::new(Polygon {
+                points: self.points(),
+                colors: self.colors(),
+                time: &self.canvas_time,
+            })
+            .width(Length::Fixed(VIEW_SIZE))
+            .height(Length::Fixed(VIEW_SIZE))
+            .into()
+        )]
+        .spacing(20);
+        if self.compare
+            && let Some(handle) = &self.svg
+        {
+            drawings = drawings.push(drawing(
+                "SVG",
+                svg(handle.clone())
+                    .width(Length::Fixed(VIEW_SIZE))
+                    .height(Length::Fixed(VIEW_SIZE))
+                    .into(),
+            ));
+        }
+
+        let frame_time = if self.spinning && self.frame_time > 0.0 {
+            format!(
+                "{:.1} ms ({:.0} fps)",
+                self.frame_time,
+                1000.0 / self.frame_time
+            )
+        } else {
+            "-".to_string()
+        };
+        let mut readout = format!(
+            "Frame time: {}   Canvas geometry: {:.3} ms",
+            frame_time,
+            self.canvas_time.get()
+        );
+        if self.compare {
+            readout.push_str(&format!("   SVG document: {:.3} ms", self.svg_time));
+        }
+
+        let controls = column![
+            self.polygon_controls(),
+            row![
+                text(format!("Rotation: {:.0}°", self.rotation))
+                    .size(15)
+                    .width(Fill),
+                text(format!(
+                    "Rotation offset: {:.0}°",
+                    self.spec.rotation_offset_degrees
+                ))
+                .size(15)
+                .width(Fill),
+            ]
+            .spacing(5),
+            row![
+                slider(0.0..=360.0, self.rotation, Message::Rotation),
+                slider(
+                    0.0..=360.0,
+                    self.spec.rotation_offset_degrees,
+                    Message::RotationOffset
+                ),
+            ]
+            .spacing(5),
+            row![
+                text(format!("Hue: {:.1}", self.hue)).size(15).width(Fill),
+                text(format!("Saturation: {:.1}", self.saturation))
+                    .size(15)
+                    .width(Fill),
+                text(format!("Brightness: {:.1}", self.brightness))
+                    .size(15)
+                    .width(Fill),
+            ]
+            .spacing(5),
+            row![
+                slider(0.0..=360.0, self.hue, Message::Hue),
+                slider(0.0..=100.0, self.saturation, Message::Saturation),
+                slider(0.0..=100.0, self.brightness, Message::Brightness),
+            ]
+            .spacing(5),
+            row![
+                toggler(self.spinning)
+                    .label("Spin")
+                    .on_toggle(|_| Message::ToggleSpin)
+                    .width(Length::Shrink),
+                toggler(self.compare)
+                    .label("Compare with SVG")
+                    .on_toggle(|_| Message::ToggleCompare)
+                    .width(Length::Shrink),
+                pick_list(
+                    self.themes.as_slice(),
+                    Some(&self.theme),
+                    Message::ThemeChanged
+                ),
+                horizontal_space(),
+                text(readout).size(15),
+            ]
+            .spacing(20)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10);
+
+        center(
+            column![drawings, controls]
+                .spacing(20)
+                .align_x(Alignment::Center),
+        )
+        .padding(20)
+        .into()
+    }
+
+    /// Edges, the mode of the polygon and its parameters, and the corner radius, like in
+    /// main_svg.
+    fn polygon_controls(&self) -> Element<'_, Message> {
+        let spec = self.spec;
+        let mode_controls = match spec.kind {
+            PolygonKind::Regular => row![],
+            PolygonKind::Star => row![
+                text(format!("Inner radius: {:.0}%", spec.inner_ratio * 100.0))
+                    .size(15)
+                    .width(Fill),
+                slider(0.1..=0.95, spec.inner_ratio, Message::InnerRadius).step(0.01),
+            ],
+            PolygonKind::Polygram => row![
+                text(format!("{{{}/{}}}", spec.points, spec.step))
+                    .size(15)
+                    .width(Fill),
+                slider(
+                    1..=(spec.points / 2).max(1),
+                    spec.step.clamp(1, (spec.points / 2).max(1)),
+                    Message::StepChanged
+                ),
+            ],
+        };
+        column![
+            row![
+                text(format!("Edges: {}", spec.points)).size(15).width(Fill),
+                pick_list(PolygonKind::ALL, Some(spec.kind), Message::KindChanged),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            slider(3..=12, spec.points, Message::EdgesChanged),
+            mode_controls.spacing(5),
+            row![
+                text(format!("Corner radius: {:.1}", spec.corner_radius))
+                    .size(15)
+                    .width(Fill),
+                slider(0.0..=50.0, spec.corner_radius, Message::CornerRadius),
+            ]
+            .spacing(5),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        if self.spinning {
+            window::frames().map(Message::Frame)
+        } else {
+            Subscription::none()
+        }
     }
 }
 
-// First, we define the data we need for drawing
+fn average(average: f32, value: f32) -> f32 {
+    if average == 0.0 {
+        value
+    } else {
+        average + (value - average) * SMOOTHING
+    }
+}
+
+// The data we need for drawing
 #[derive(Debug)]
-struct Circle {
-    radius: f32,
+struct Polygon<'a> {
+    points: Vec<Vec<Point>>,
+    /// Fill and stroke
+    colors: (Color, Color),
+    /// Where the averaged drawing time goes
+    time: &'a Cell<f32>,
 }
 
-// Then, we implement the `Program` trait
-impl<Message> canvas::Program<Message> for Circle {
+impl<Message> canvas::Program<Message> for Polygon<'_> {
     // No internal state
     type State = ();
 
@@ -59,21 +453,17 @@ impl<Message> canvas::Program<Message> for Circle {
         bounds: Rectangle,
         _cursor: mouse::Cursor,
     ) -> Vec<canvas::Geometry> {
-        // We prepare a new `Frame`
+        let start = Instant::now();
         let mut frame = canvas::Frame::new(renderer, bounds.size());
 
-        // We create a `Path` representing a simple circle
-        let circle = canvas::Path::circle(frame.center(), self.radius);
+        let path = canvas_path(&self.points);
+        let (fill, stroke) = self.colors;
+        frame.fill(&path, fill);
+        frame.stroke(&path, Stroke::default().with_color(stroke).with_width(2.0));
 
-        // And fill it with some color
-        frame.fill(&circle, Color::BLACK);
-
-        // Then, we produce the geometry
-        vec![frame.into_geometry()]
+        let geometry = frame.into_geometry();
+        let milliseconds = start.elapsed().as_secs_f32() * 1000.0;
+        self.time.set(average(self.time.get(), milliseconds));
+        vec![geometry]
     }
 }
-
-// Finally, we simply use our `Circle` to create the `Canvas`!
-fn view<'a, Message: 'a>(_state: &'a State) -> Element<'a, Message> {
-    canvas(Circle { radius: 50.0 }).into()
-}
@@ -0,0 +1,232 @@
+//
+// Export of the polygon animation as an animated SVG or as a sequence of PNG frames.
+//
+// Concept:
+//  - The timeline is sampled `SAMPLES_PER_SECOND` times per second. The animated SVG holds the
+//    samples as SMIL `values`, which keeps the easing curves and lets the colors be mixed in
+//    HSB like in the window (SMIL would mix the RGB values).
+//  - Only animated properties get an animation element, everything else is written as a plain
+//    attribute.
+//  - The path data can only be interpolated while the number of points stays the same (edges,
+//    polygram rings, the adaptive samples of a curve), otherwise it switches from sample to
+//    sample.
+//  - PNG frames are rendered on the CPU with resvg, with a transparent background. Frames are
+//    numbered from 1 (`frame_0001.png`), the ready made pattern for video tools.
+//
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use resvg::{tiny_skia, usvg};
+
+use crate::polygon::{self, VIEW_SIZE};
+use crate::timeline::{Pose, Property, Timeline};
+
+/// Samples per second of the animated SVG.
+const SAMPLES_PER_SECOND: f32 = 30.0;
+/// Curves in the animated SVG are sampled to look smooth up to this size in pixels.
+const SVG_DETAIL_SIZE: f32 = 1024.0;
+
+/// A self-contained SVG document that plays the timeline.
+pub fn animated_svg(timeline: &Timeline) -> String {
+    let count = (timeline.duration * SAMPLES_PER_SECOND).ceil().max(1.0) as usize;
+    let poses: Vec<Pose> = (0..=count)
+        .map(|i| timeline.pose_at(timeline.duration * i as f32 / count as f32))
+        .collect();
+    let key_times = (0..=count)
+        .map(|i| format!("{:.4}", i as f32 / count as f32))
+        .collect::<Vec<_>>()
+        .join(";");
+    let timing = format!(
+        r#"dur="{}s" repeatCount="{}" fill="freeze" keyTimes="{}""#,
+        timeline.duration,
+        if timeline.looping { "indefinite" } else { "1" },
+        key_times
+    );
+    let animated = |properties: &[Property]| {
+        properties
+            .iter()
+            .any(|property| timeline.track(*property).is_some())
+    };
+    let values =
+        |value: &dyn Fn(&Pose) -> String| poses.iter().map(value).collect::<Vec<_>>().join(";");
+
+    let first = &poses[0];
+    let (fill, stroke) = polygon::colors(first);
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg viewBox="0 0 {size} {size}" xmlns="http://www.w3.org/2000/svg">"#,
+        size = VIEW_SIZE
+    );
+    let _ = writeln!(
+        svg,
+        r#"  <g transform="rotate({} {} {})">"#,
+        first.rotation, first.x, first.y
+    );
+    if animated(&[Property::Rotation, Property::X, Property::Y]) {
+        let _ = writeln!(
+            svg,
+            r#"    <animateTransform attributeName="transform" type="rotate" values="{}" {}/>"#,
+            values(&|pose| format!("{:.2} {:.2} {:.2}", pose.rotation, pose.x, pose.y)),
+            timing
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r#"    <path d="{}" fill="{}" fill-opacity="0.8" stroke="{}" stroke-width="2">"#,
+        polygon::path_data(first, SVG_DETAIL_SIZE),
+        fill,
+        stroke
+    );
+    if animated(&[
+        Property::Edges,
+        Property::Radius,
+        Property::InnerRadius,
+        Property::CornerRadius,
+        Property::X,
+        Property::Y,
+    ]) {
+        let point_counts = |pose: &Pose| {
+            polygon::points(pose, SVG_DETAIL_SIZE)
+                .iter()
+                .map(Vec::len)
+                .collect::<Vec<_>>()
+        };
+        let first_counts = point_counts(first);
+        let same_points = poses.iter().all(|pose| point_counts(pose) == first_counts);
+        let _ = writeln!(
+            svg,
+            r#"      <animate attributeName="d" calcMode="{}" values="{}" {}/>"#,
+            if same_points { "linear" } else { "discrete" },
+            values(&|pose| polygon::path_data(pose, SVG_DETAIL_SIZE)),
+            timing
+        );
+    }
+    if animated(&[Property::Hue, Property::Saturation, Property::Brightness]) {
+        let _ = writeln!(
+            svg,
+            r#"      <animate attributeName="fill" values="{}" {}/>"#,
+            values(&|pose| polygon::colors(pose).0),
+            timing
+        );
+        let _ = writeln!(
+            svg,
+            r#"      <animate attributeName="stroke" values="{}" {}/>"#,
+            values(&|pose| polygon::colors(pose).1),
+            timing
+        );
+    }
+    svg.push_str("    </path>\n  </g>\n</svg>\n");
+    svg
+}
+
+/// The directory for the PNG frames: the name of the timeline file with "_frames" appended.
+pub fn frames_directory(timeline_file: &Path) -> PathBuf {
+    let stem = timeline_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    timeline_file.with_file_name(format!("{}_frames", stem))
+}
+
+/// Renders the timeline as `size` x `size` pixel PNG files, `fps` per second. A looping
+/// timeline leaves out the last frame, it is the same as the first one. Returns the number of
+/// frames written.
+pub fn png_frames(timeline: &Timeline, directory: &Path, size: u32, fps: u32) -> io::Result<usize> {
+    if size == 0 || fps == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "frame size and frame rate must be above 0",
+        ));
+    }
+    std::fs::create_dir_all(directory)?;
+    let mut count = (timeline.duration * fps as f32).round().max(1.0) as usize;
+    if !timeline.looping {
+        count += 1;
+    }
+    let options = usvg::Options::default();
+    for index in 0..count {
+        let pose = timeline.pose_at(index as f32 / fps as f32);
+        let tree = usvg::Tree::from_str(&polygon::svg(&pose, size as f32), &options)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut pixmap = tiny_skia::Pixmap::new(size, size)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid frame size"))?;
+        let scale = size as f32 / tree.size().width();
+        resvg::render(
+            &tree,
+            tiny_skia::Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+        let png = pixmap
+            .encode_png()
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        std::fs::write(directory.join(format!("frame_{:04}.png", index + 1)), png)?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The names of the files in the directory, sorted.
+    fn file_names(directory: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(directory)
+            .expect("a directory")
+            .map(|entry| {
+                entry
+                    .expect("an entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn frames_are_numbered_from_one() {
+        let directory = std::env::temp_dir().join(format!("export_test_{}", std::process::id()));
+        let mut timeline = Timeline {
+            duration: 0.5,
+            ..Timeline::default()
+        };
+
+        // a looping timeline leaves out the frame at the end, it is the first one again
+        let looping = png_frames(&timeline, &directory.join("looping"), 16, 8);
+        timeline.looping = false;
+        let once = png_frames(&timeline, &directory.join("once"), 16, 8);
+        let looping_names = file_names(&directory.join("looping"));
+        let once_names = file_names(&directory.join("once"));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        assert_eq!(looping.expect("exported"), 4);
+        assert_eq!(
+            looping_names,
+            [
+                "frame_0001.png",
+                "frame_0002.png",
+                "frame_0003.png",
+                "frame_0004.png"
+            ]
+        );
+        assert_eq!(once.expect("exported"), 5);
+        assert_eq!(
+            once_names.last().map(String::as_str),
+            Some("frame_0005.png")
+        );
+    }
+
+    #[test]
+    fn empty_frames_are_an_error() {
+        let directory =
+            std::env::temp_dir().join(format!("export_error_test_{}", std::process::id()));
+        let timeline = Timeline::default();
+        assert!(png_frames(&timeline, &directory, 16, 0).is_err());
+        assert!(png_frames(&timeline, &directory, 0, 30).is_err());
+        assert!(!directory.exists());
+    }
+}
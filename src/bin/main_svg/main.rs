@@ -0,0 +1,784 @@
+use iced::widget::{
+    button, center, column, container, horizontal_space, pick_list, row, slider, svg, text, toggler,
+};
+use iced::{Alignment, Element, Fill, Length, Subscription, Task, Theme};
+use iced_hello_world::curves::{Curve, CurveKind};
+use iced_hello_world::polygon::PolygonKind;
+use iced_hello_world::theme;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+mod export;
+mod polygon;
+mod timeline;
+use timeline::{Easing, Keyframe, Playback, Pose, Property, Timeline};
+
+/// File for saving and loading the timeline if no `--timeline` is given.
+const DEFAULT_TIMELINE_FILE: &str = "polygon_timeline.json";
+/// Frame sizes (width and height in pixels) offered for the PNG export.
+const FRAME_SIZES: [u32; 5] = [64, 128, 256, 512, 1024];
+/// Frame rates offered for the PNG export.
+const FRAME_RATES: [u32; 4] = [12, 24, 30, 60];
+
+/// What is drawn: the polygon or one of the parametric curves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Generator {
+    Polygon,
+    Curve(CurveKind),
+}
+
+impl Generator {
+    const ALL: [Generator; 6] = [
+        Generator::Polygon,
+        Generator::Curve(CurveKind::Superellipse),
+        Generator::Curve(CurveKind::Hypotrochoid),
+        Generator::Curve(CurveKind::Epitrochoid),
+        Generator::Curve(CurveKind::Lissajous),
+        Generator::Curve(CurveKind::Rose),
+    ];
+}
+
+impl std::fmt::Display for Generator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Generator::Polygon => f.write_str("Polygon"),
+            Generator::Curve(kind) => kind.fmt(f),
+        }
+    }
+}
+
+/// Command line options:
+/// - `--timeline <PATH>`: the JSON file for saving and loading the keyframes
+/// - `--export-svg <PATH>`: write the timeline file as an animated SVG and exit
+/// - `--export-frames <DIR>`: write the timeline file as PNG frames and exit
+/// - `--size <PIXELS>`, `--fps <N>`: size and frame rate of the PNG frames (256 and 30)
+/// - `--palettes <FILE>`: JSON file with additional color palettes (default `palettes.json`)
+/// - `--theme <NAME>`: the theme at start, e.g. "Dark"
+pub fn main() -> iced::Result {
+    let mut file = PathBuf::from(DEFAULT_TIMELINE_FILE);
+    let mut export_svg = None;
+    let mut export_frames = None;
+    let mut size = 256;
+    let mut fps = 30;
+    let mut palette_file = PathBuf::from(theme::DEFAULT_PALETTE_FILE);
+    let mut theme_name = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--timeline" => file = args.next().map(PathBuf::from).unwrap_or(file),
+            "--export-svg" => export_svg = args.next().map(PathBuf::from),
+            "--export-frames" => export_frames = args.next().map(PathBuf::from),
+            "--size" => size = positive_argument(&arg, args.next()),
+            "--fps" => fps = positive_argument(&arg, args.next()),
+            "--palettes" => palette_file = args.next().map(PathBuf::from).unwrap_or(palette_file),
+            "--theme" => theme_name = args.next(),
+            _ => eprintln!("ignoring unknown argument {}", arg),
+        }
+    }
+
+    // headless export: no window is opened, only errors are reported
+    if export_svg.is_some() || export_frames.is_some() {
+        let timeline = if file.exists() {
+            match Timeline::load(&file) {
+                Ok(timeline) => timeline,
+                Err(e) => {
+                    eprintln!("Could not load {}: {}", file.display(), e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            Timeline::default()
+        };
+        let results = [
+            export_svg.map(|path| write_svg(&timeline, &path)),
+            export_frames.map(|directory| write_frames(&timeline, &directory, size, fps)),
+        ];
+        let mut failed = false;
+        for e in results.into_iter().flatten().filter_map(Result::err) {
+            eprintln!("{}", e);
+            failed = true;
+        }
+        if failed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let (themes, theme) = theme::select_theme(&palette_file, theme_name.as_deref());
+    iced::application("iced svg polygon", State::update, State::view)
+        .subscription(State::subscription)
+        .theme(State::theme)
+        .run_with(move || {
+            let state = State {
+                file,
+                themes,
+                theme,
+                ..State::default()
+            };
+            (state, Task::none())
+        })
+}
+
+/// Parses the value of a numeric option. Exits with an error if it is not a number above 0.
+fn positive_argument(option: &str, value: Option<String>) -> u32 {
+    match value.as_deref().map(str::parse) {
+        Some(Ok(number)) if number > 0 => number,
+        _ => {
+            eprintln!(
+                "{} needs a number above 0, got {}",
+                option,
+                value.as_deref().unwrap_or("nothing")
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    /// The values shown, set by the sliders and the timeline
+    pose: Pose,
+    timeline: Timeline,
+    playback: Playback,
+    /// Property and easing used by the keyframe buttons
+    property: Property,
+    easing: Easing,
+    /// The timeline file for Save and Load, the exports go next to it
+    file: PathBuf,
+    /// Width and height of exported PNG frames in pixels
+    frame_size: u32,
+    /// Frames per second of the PNG export
+    frame_rate: u32,
+    /// Light, dark and the palettes of the palette file
+    themes: Vec<Theme>,
+    theme: Theme,
+    /// Outcome of the last file operation, shown below the timeline
+    status: Option<Status>,
+}
+
+/// A message in the status line.
+#[derive(Debug, Clone)]
+enum Status {
+    Info(String),
+    Error(String),
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            pose: Pose::default(),
+            timeline: Timeline::default(),
+            playback: Playback::default(),
+            property: Property::Rotation,
+            easing: Easing::default(),
+            file: PathBuf::from(DEFAULT_TIMELINE_FILE),
+            frame_size: 256,
+            frame_rate: 30,
+            themes: vec![Theme::Light, Theme::Dark],
+            theme: Theme::Light,
+            status: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    GeneratorChanged(Generator),
+    /// New parameters of the curve.
+    CurveChanged(Curve),
+    KindChanged(PolygonKind),
+    EdgesChanged(u32),
+    InnerRadius(f32),
+    StepChanged(u32),
+    CornerRadius(f32),
+    RotationOffset(f32),
+    Hue(f32),
+    Saturation(f32),
+    Brightness(f32),
+    Radius(f32),
+    CenterX(f32),
+    CenterY(f32),
+    Tick(Instant),
+    PlayPause,
+    /// The user moved the play head.
+    Seek(f32),
+    ToggleLoop,
+    DurationChanged(f32),
+    SelectProperty(Property),
+    SelectEasing(Easing),
+    /// Store the current value of the selected property as a keyframe at the play head.
+    SetKeyframe,
+    /// Remove the keyframe of the selected property at the play head.
+    RemoveKeyframe,
+    /// Remove all keyframes of the selected property.
+    ClearTrack,
+    SaveTimeline,
+    LoadTimeline,
+    ExportSvg,
+    FrameSizeChanged(u32),
+    FrameRateChanged(u32),
+    ExportFrames,
+    ThemeChanged(Theme),
+}
+
+impl State {
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::PlayPause => {
+                if self.playback.playing {
+                    self.playback.pause();
+                } else {
+                    self.playback.play(&self.timeline);
+                }
+            }
+            Message::GeneratorChanged(generator) => {
+                self.pose.curve = match generator {
+                    Generator::Polygon => None,
+                    Generator::Curve(kind) => match self.pose.curve {
+                        Some(curve) if curve.kind() == kind => Some(curve),
+                        _ => Some(kind.default_curve()),
+                    },
+                };
+            }
+            Message::CurveChanged(curve) => {
+                self.pose.curve = Some(curve);
+            }
+            Message::KindChanged(kind) => {
+                self.pose.kind = kind;
+            }
+            Message::EdgesChanged(edges) => {
+                self.pose.edges = edges.max(3) as f32; // Minimum 3 edges for a polygon
+            }
+            Message::InnerRadius(ratio) => {
+                self.pose.inner_radius = ratio;
+            }
+            Message::StepChanged(step) => {
+                self.pose.step = step as f32;
+            }
+            Message::CornerRadius(radius) => {
+                self.pose.corner_radius = radius;
+            }
+            Message::RotationOffset(degrees) => {
+                self.pose.rotation_offset = degrees;
+            }
+            Message::Hue(hue) => {
+                self.pose.hue = hue;
+            }
+            Message::Saturation(saturation) => {
+                self.pose.saturation = saturation;
+            }
+            Message::Brightness(brightness) => {
+                self.pose.brightness = brightness;
+            }
+            Message::Radius(radius) => {
+                self.pose.radius = radius;
+            }
+            Message::CenterX(x) => {
+                self.pose.x = x;
+            }
+            Message::CenterY(y) => {
+                self.pose.y = y;
+            }
+            Message::Tick(now) => {
+                self.playback.tick(now, &self.timeline);
+                self.timeline.apply(self.playback.time, &mut self.pose);
+            }
+            Message::Seek(time) => {
+                self.playback.seek(time, &self.timeline);
+                self.timeline.apply(self.playback.time, &mut self.pose);
+            }
+            Message::ToggleLoop => {
+                self.timeline.looping = !self.timeline.looping;
+            }
+            Message::DurationChanged(duration) => {
+                self.timeline.duration = duration;
+                self.playback.seek(self.playback.time, &self.timeline);
+            }
+            Message::SelectProperty(property) => {
+                self.property = property;
+            }
+            Message::SelectEasing(easing) => {
+                self.easing = easing;
+            }
+            Message::SetKeyframe => {
+                let keyframe = Keyframe {
+                    time: self.playback.time,
+                    value: self.property.get(&self.pose),
+                    easing: self.easing,
+                };
+                self.timeline.set_keyframe(self.property, keyframe);
+            }
+            Message::RemoveKeyframe => {
+                self.timeline
+                    .remove_keyframe(self.property, self.playback.time);
+                self.timeline.apply(self.playback.time, &mut self.pose);
+            }
+            Message::ClearTrack => {
+                self.timeline.remove_track(self.property);
+            }
+            Message::SaveTimeline => {
+                self.timeline.base = self.pose;
+                match self.timeline.save(&self.file) {
+                    Ok(()) => self.show_info(format!("Saved {}", self.file.display())),
+                    Err(e) => {
+                        self.show_error(format!("Could not save {}: {}", self.file.display(), e))
+                    }
+                }
+            }
+            Message::LoadTimeline => match Timeline::load(&self.file) {
+                Ok(timeline) => {
+                    self.show_info(format!("Loaded {}", self.file.display()));
+                    self.timeline = timeline;
+                    self.playback.seek(0.0, &self.timeline);
+                    self.pose = self.timeline.pose_at(self.playback.time);
+                }
+                Err(e) => self.show_error(format!("Could not load {}: {}", self.file.display(), e)),
+            },
+            Message::ExportSvg => {
+                self.timeline.base = self.pose;
+                let result = write_svg(&self.timeline, &self.file.with_extension("svg"));
+                self.report(result);
+            }
+            Message::FrameSizeChanged(size) => {
+                self.frame_size = size;
+            }
+            Message::FrameRateChanged(fps) => {
+                self.frame_rate = fps;
+            }
+            Message::ThemeChanged(theme) => {
+                self.theme = theme;
+            }
+            Message::ExportFrames => {
+                self.timeline.base = self.pose;
+                let result = write_frames(
+                    &self.timeline,
+                    &export::frames_directory(&self.file),
+                    self.frame_size,
+                    self.frame_rate,
+                );
+                self.report(result);
+            }
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+
+    fn generate_polygon_svg(&self) -> String {
+        polygon::svg(&self.pose, polygon::VIEW_SIZE)
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        // Generate SVG data
+        let svg_data = self.generate_polygon_svg();
+
+        // Create handle from memory
+        let handle = svg::Handle::from_memory(svg_data.into_bytes());
+
+        let svg = container(
+            svg(handle)
+                .width(Length::Fixed(300.0))
+                .height(Length::Fixed(300.0)),
+        )
+        .style(container::rounded_box)
+        .center(Fill);
+
+        let generator = match self.pose.curve {
+            Some(curve) => Generator::Curve(curve.kind()),
+            None => Generator::Polygon,
+        };
+        let shape_controls = match self.pose.curve {
+            Some(curve) => curve_controls(curve),
+            None => self.polygon_controls(),
+        };
+
+        let edge_controls = container(
+            column![
+                row![
+                    text("Shape:").size(20),
+                    horizontal_space(),
+                    pick_list(Generator::ALL, Some(generator), Message::GeneratorChanged),
+                    pick_list(
+                        self.themes.as_slice(),
+                        Some(&self.theme),
+                        Message::ThemeChanged
+                    ),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+                shape_controls,
+                row![
+                    text(format!(
+                        "Rotation offset: {:.0}°",
+                        self.pose.rotation_offset
+                    ))
+                    .size(15)
+                    .width(Fill),
+                    slider(
+                        -180.0..=180.0,
+                        self.pose.rotation_offset,
+                        Message::RotationOffset
+                    ),
+                ]
+                .spacing(5),
+            ]
+            .spacing(10),
+        )
+        .center_x(Fill);
+
+        let hsb_controls = container(
+            column![
+                row![text("Color:").size(20),],
+                row![
+                    text(format!("Hue: {:.1}", self.pose.hue))
+                        .size(15)
+                        .width(Fill),
+                    text(format!("Saturation: {:.1}", self.pose.saturation))
+                        .size(15)
+                        .width(Fill),
+                    text(format!("Brightness: {:.1}", self.pose.brightness))
+                        .size(15)
+                        .width(Fill)
+                ]
+                .spacing(5),
+                row![
+                    slider(0.0..=360.0, self.pose.hue, Message::Hue),
+                    slider(0.0..=100.0, self.pose.saturation, Message::Saturation),
+                    slider(0.0..=100.0, self.pose.brightness, Message::Brightness)
+                ]
+                .spacing(5)
+            ]
+            .spacing(10),
+        );
+
+        let geometry_controls = container(
+            column![
+                row![
+                    text(format!("Radius: {:.1}", self.pose.radius))
+                        .size(15)
+                        .width(Fill),
+                    text(format!("X: {:.1}", self.pose.x)).size(15).width(Fill),
+                    text(format!("Y: {:.1}", self.pose.y)).size(15).width(Fill)
+                ]
+                .spacing(5),
+                row![
+                    slider(10.0..=150.0, self.pose.radius, Message::Radius),
+                    slider(0.0..=300.0, self.pose.x, Message::CenterX),
+                    slider(0.0..=300.0, self.pose.y, Message::CenterY)
+                ]
+                .spacing(5)
+            ]
+            .spacing(10),
+        );
+
+        let keyframes = self
+            .timeline
+            .track(self.property)
+            .map(|track| {
+                track
+                    .keyframes
+                    .iter()
+                    .map(|key| format!("{:.2} s: {:.1} ({})", key.time, key.value, key.easing))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_else(|| "not animated".to_string());
+        let timeline_controls = container(
+            column![
+                row![
+                    text("Timeline:").size(20),
+                    horizontal_space(),
+                    toggler(self.timeline.looping)
+                        .label("Loop")
+                        .on_toggle(|_| Message::ToggleLoop)
+                        .width(Length::Shrink),
+                    button(text(if !self.playback.playing {
+                        "Play"
+                    } else {
+                        "Pause"
+                    }))
+                    .on_press(Message::PlayPause),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+                row![
+                    text(format!("{:.2} s", self.playback.time))
+                        .size(15)
+                        .width(Length::Fixed(60.0)),
+                    slider(
+                        0.0..=self.timeline.duration,
+                        self.playback.time,
+                        Message::Seek
+                    )
+                    .step(0.01),
+                    text(format!("Length: {:.1} s", self.timeline.duration))
+                        .size(15)
+                        .width(Length::Fixed(100.0)),
+                    slider(1.0..=20.0, self.timeline.duration, Message::DurationChanged)
+                        .step(0.5)
+                        .width(Length::Fixed(120.0)),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+                row![
+                    pick_list(Property::ALL, Some(self.property), Message::SelectProperty),
+                    pick_list(Easing::ALL, Some(self.easing), Message::SelectEasing),
+                    button(text("Set key")).on_press(Message::SetKeyframe),
+                    button(text("Remove key")).on_press(Message::RemoveKeyframe),
+                    button(text("Clear track")).on_press(Message::ClearTrack),
+                    horizontal_space(),
+                    button(text("Save")).on_press(Message::SaveTimeline),
+                    button(text("Load")).on_press(Message::LoadTimeline),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+                text(format!("{}: {}", self.property, keyframes)).size(15),
+                row![
+                    button(text("Export SVG")).on_press(Message::ExportSvg),
+                    horizontal_space(),
+                    text("Size").size(15),
+                    pick_list(
+                        FRAME_SIZES,
+                        Some(self.frame_size),
+                        Message::FrameSizeChanged
+                    ),
+                    text("FPS").size(15),
+                    pick_list(
+                        FRAME_RATES,
+                        Some(self.frame_rate),
+                        Message::FrameRateChanged
+                    ),
+                    button(text("Export PNG frames")).on_press(Message::ExportFrames),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(10),
+        );
+
+        center(
+            column![
+                edge_controls,
+                svg,
+                hsb_controls,
+                geometry_controls,
+                timeline_controls
+            ]
+            .push_maybe(self.status.as_ref().map(|status| match status {
+                Status::Info(message) => text(message).size(15),
+                Status::Error(message) => text(message).size(15).style(text::danger),
+            }))
+            .spacing(20)
+            .height(Fill),
+        )
+        .padding(20)
+        .into()
+    }
+
+    /// Shows the outcome of a successful operation in the status line.
+    fn show_info(&mut self, message: String) {
+        self.status = Some(Status::Info(message));
+    }
+
+    /// Shows an error in the status line and writes it to stderr.
+    fn show_error(&mut self, message: String) {
+        eprintln!("{}", message);
+        self.status = Some(Status::Error(message));
+    }
+
+    /// Shows the message of a finished export in the status line.
+    fn report(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(message) => self.show_info(message),
+            Err(message) => self.show_error(message),
+        }
+    }
+
+    /// Edges, the mode of the polygon and its parameters, and the corner radius.
+    fn polygon_controls(&self) -> Element<'_, Message> {
+        let spec = self.pose.spec();
+        let mode_controls = match self.pose.kind {
+            PolygonKind::Regular => row![],
+            PolygonKind::Star => row![
+                text(format!(
+                    "Inner radius: {:.0}%",
+                    self.pose.inner_radius * 100.0
+                ))
+                .size(15)
+                .width(Fill),
+                slider(0.1..=0.95, self.pose.inner_radius, Message::InnerRadius).step(0.01),
+            ],
+            PolygonKind::Polygram => row![
+                text(format!("{{{}/{}}}", spec.points, spec.step))
+                    .size(15)
+                    .width(Fill),
+                slider(
+                    1..=(spec.points / 2).max(1),
+                    spec.step.min(spec.points / 2),
+                    Message::StepChanged
+                ),
+            ],
+        };
+        column![
+            row![
+                text("Number of edges:").size(15).width(Fill),
+                pick_list(PolygonKind::ALL, Some(self.pose.kind), Message::KindChanged),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            slider(3..=12, self.pose.edge_count(), Message::EdgesChanged),
+            mode_controls.spacing(5),
+            row![
+                text(format!("Corner radius: {:.1}", self.pose.corner_radius))
+                    .size(15)
+                    .width(Fill),
+                slider(0.0..=50.0, self.pose.corner_radius, Message::CornerRadius),
+            ]
+            .spacing(5),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        if self.playback.playing {
+            iced::time::every(Duration::from_millis(16)).map(Message::Tick)
+        } else {
+            Subscription::none()
+        }
+    }
+}
+
+/// The parameter sliders of the curve.
+fn curve_controls<'a>(curve: Curve) -> Element<'a, Message> {
+    let labeled = |label: String, slider: Element<'a, Message>| {
+        row![text(label).size(15).width(Fill), slider].spacing(5)
+    };
+    let controls = match curve {
+        Curve::Superellipse { exponent, aspect } => column![
+            labeled(
+                format!("Exponent: {:.2}", exponent),
+                slider(0.2..=10.0, exponent, move |exponent| {
+                    Message::CurveChanged(Curve::Superellipse { exponent, aspect })
+                })
+                .step(0.05)
+                .into()
+            ),
+            labeled(
+                format!("Aspect: {:.2}", aspect),
+                slider(0.1..=1.0, aspect, move |aspect| {
+                    Message::CurveChanged(Curve::Superellipse { exponent, aspect })
+                })
+                .step(0.01)
+                .into()
+            ),
+        ],
+        Curve::Hypotrochoid {
+            fixed,
+            rolling,
+            pen,
+        }
+        | Curve::Epitrochoid {
+            fixed,
+            rolling,
+            pen,
+        } => {
+            let inside = matches!(curve, Curve::Hypotrochoid { .. });
+            let make = move |fixed, rolling, pen| {
+                Message::CurveChanged(if inside {
+                    Curve::Hypotrochoid {
+                        fixed,
+                        rolling,
+                        pen,
+                    }
+                } else {
+                    Curve::Epitrochoid {
+                        fixed,
+                        rolling,
+                        pen,
+                    }
+                })
+            };
+            column![
+                labeled(
+                    format!("Ring: {}", fixed),
+                    slider(1..=24, fixed, move |fixed| make(fixed, rolling, pen)).into()
+                ),
+                labeled(
+                    format!("Wheel: {}", rolling),
+                    slider(1..=24, rolling, move |rolling| make(fixed, rolling, pen)).into()
+                ),
+                labeled(
+                    format!("Pen: {:.2}", pen),
+                    slider(0.0..=2.0, pen, move |pen| make(fixed, rolling, pen))
+                        .step(0.01)
+                        .into()
+                ),
+            ]
+        }
+        Curve::Lissajous {
+            a,
+            b,
+            phase_degrees,
+        } => {
+            let make = move |a, b, phase_degrees| {
+                Message::CurveChanged(Curve::Lissajous {
+                    a,
+                    b,
+                    phase_degrees,
+                })
+            };
+            column![
+                labeled(
+                    format!("Frequency x: {}", a),
+                    slider(1..=12, a, move |a| make(a, b, phase_degrees)).into()
+                ),
+                labeled(
+                    format!("Frequency y: {}", b),
+                    slider(1..=12, b, move |b| make(a, b, phase_degrees)).into()
+                ),
+                labeled(
+                    format!("Phase: {:.0}°", phase_degrees),
+                    slider(0.0..=180.0, phase_degrees, move |phase| make(a, b, phase)).into()
+                ),
+            ]
+        }
+        Curve::Rose { petals, divisor } => column![
+            labeled(
+                format!("Petals n: {}", petals),
+                slider(1..=12, petals, move |petals| {
+                    Message::CurveChanged(Curve::Rose { petals, divisor })
+                })
+                .into()
+            ),
+            labeled(
+                format!("Divisor d: {}", divisor),
+                slider(1..=12, divisor, move |divisor| {
+                    Message::CurveChanged(Curve::Rose { petals, divisor })
+                })
+                .into()
+            ),
+        ],
+    };
+    controls.spacing(10).into()
+}
+
+/// Writes the animated SVG. Returns the message for the status line.
+fn write_svg(timeline: &Timeline, path: &Path) -> Result<String, String> {
+    std::fs::write(path, export::animated_svg(timeline))
+        .map(|()| format!("Exported {}", path.display()))
+        .map_err(|e| format!("Could not export {}: {}", path.display(), e))
+}
+
+/// Writes the PNG frames. Returns the message for the status line.
+fn write_frames(
+    timeline: &Timeline,
+    directory: &Path,
+    size: u32,
+    fps: u32,
+) -> Result<String, String> {
+    export::png_frames(timeline, directory, size, fps)
+        .map(|count| format!("Exported {} frames to {}", count, directory.display()))
+        .map_err(|e| format!("Could not export to {}: {}", directory.display(), e))
+}
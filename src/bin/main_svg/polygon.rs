@@ -0,0 +1,84 @@
+//
+// SVG of the polygon for a pose.
+//
+// The window, the animated SVG export and the PNG frames all draw the polygon from here, so
+// they look the same. The points come from the library (polygons and parametric curves), like
+// the outline drawn on a canvas.
+//
+
+use iced::Point;
+use iced_hello_world::curves::curve_points;
+use iced_hello_world::polygon::{polygon_points, svg_path_data};
+use iced_hello_world::{hsb_to_rgb, rotate_point};
+
+use crate::timeline::Pose;
+
+/// Width and height of the view box.
+pub const VIEW_SIZE: f32 = 300.0;
+/// Largest distance in pixels between a sampled curve and the real curve.
+const SAMPLING_TOLERANCE: f32 = 0.25;
+
+/// The outline as closed rings of points, before the rotation. Curves are sampled finely enough
+/// to look smooth when the view box is shown `size` pixels wide.
+pub fn points(pose: &Pose, size: f32) -> Vec<Vec<Point>> {
+    let Some(curve) = &pose.curve else {
+        return polygon_points(&pose.spec());
+    };
+    let center = Point::new(pose.x, pose.y);
+    let tolerance = SAMPLING_TOLERANCE * VIEW_SIZE / size;
+    let ring = curve_points(curve, center, pose.radius, tolerance)
+        .iter()
+        .map(|point| rotate_point(point, &center, pose.rotation_offset))
+        .collect();
+    vec![ring]
+}
+
+/// The outline as SVG path data, before the rotation.
+pub fn path_data(pose: &Pose, size: f32) -> String {
+    svg_path_data(&points(pose, size))
+}
+
+/// Fill and stroke color as "rgb(r,g,b)". The fill is drawn with 80% opacity.
+pub fn colors(pose: &Pose) -> (String, String) {
+    let fill = hsb_to_rgb(pose.hue, pose.saturation, pose.brightness);
+    let stroke = hsb_to_rgb(
+        pose.hue,
+        pose.saturation,
+        pose.brightness * 0.7, // Darker stroke
+    );
+    (rgb(fill), rgb(stroke))
+}
+
+fn rgb((r, g, b): (f32, f32, f32)) -> String {
+    format!(
+        "rgb({},{},{})",
+        (r * 255.0) as u8,
+        (g * 255.0) as u8,
+        (b * 255.0) as u8
+    )
+}
+
+/// The complete SVG document: view box, path and rotation transform, to be shown `size` pixels
+/// wide.
+pub fn svg(pose: &Pose, size: f32) -> String {
+    let (fill_color, stroke_color) = colors(pose);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+            <svg viewBox="0 0 {size} {size}" xmlns="http://www.w3.org/2000/svg">
+                <g transform="rotate({} {} {})">
+                    <path d="{}"
+                          fill="{}"
+                          fill-opacity="0.8"
+                          stroke="{}"
+                          stroke-width="2"/>
+                </g>
+            </svg>"#,
+        pose.rotation,
+        pose.x,
+        pose.y,
+        path_data(pose, size),
+        fill_color,
+        stroke_color,
+        size = VIEW_SIZE
+    )
+}
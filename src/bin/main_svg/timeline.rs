@@ -0,0 +1,564 @@
+//
+// Keyframe timeline for the polygon.
+//
+// Concept:
+//  - A Pose holds every animatable value of the polygon. The sliders edit the pose directly.
+//  - A track animates one property of the pose. Its keyframes are sorted by time, between two
+//    keyframes the value is interpolated with the easing of the later keyframe. Before the first
+//    and after the last keyframe the value is held.
+//  - Properties without a track keep the value set with the sliders.
+//  - Playback advances by the real time between two ticks, so the speed doesn't depend on how
+//    often the ticks arrive.
+//  - The timeline (tracks, duration, looping) is saved as JSON, together with the pose of the
+//    sliders, so the animation can be exported without opening the window.
+//
+
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+
+use iced::Point;
+use iced_hello_world::curves::Curve;
+use iced_hello_world::polygon::{PolygonKind, PolygonSpec};
+use serde::{Deserialize, Serialize};
+
+/// Keyframes closer than this (in seconds) are treated as the same keyframe.
+const SAME_TIME: f32 = 0.01;
+
+/// Every animatable value of the polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pose {
+    /// Regular polygon, star or polygram, not animatable
+    pub kind: PolygonKind,
+    /// Number of edges (tips of a star, points of a polygram), rounded for drawing
+    pub edges: f32,
+    /// Star only: inner radius as a fraction of the radius
+    pub inner_radius: f32,
+    /// Polygram only: the k of {n/k}, rounded for drawing, not animatable
+    pub step: f32,
+    pub corner_radius: f32,
+    /// In degrees, turns the corners around the center before the rotation is applied
+    pub rotation_offset: f32,
+    /// Parametric curve drawn instead of the polygon, not animatable
+    pub curve: Option<Curve>,
+    pub hue: f32,
+    pub saturation: f32,
+    pub brightness: f32,
+    /// In degrees
+    pub rotation: f32,
+    pub radius: f32,
+    /// Center in the 300 x 300 view box
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Self {
+            kind: PolygonKind::Regular,
+            edges: 5.0,
+            inner_radius: 0.5,
+            step: 2.0,
+            corner_radius: 0.0,
+            rotation_offset: 0.0,
+            curve: None,
+            hue: 0.0,
+            saturation: 100.0,
+            brightness: 100.0,
+            rotation: 0.0,
+            radius: 100.0,
+            x: 150.0,
+            y: 150.0,
+        }
+    }
+}
+
+impl Pose {
+    /// The number of edges to draw, at least 3.
+    pub fn edge_count(&self) -> u32 {
+        self.edges.round().max(3.0) as u32
+    }
+
+    /// The geometry of the polygon, without the rotation.
+    pub fn spec(&self) -> PolygonSpec {
+        PolygonSpec {
+            kind: self.kind,
+            points: self.edge_count(),
+            center: Point::new(self.x, self.y),
+            radius: self.radius,
+            inner_ratio: self.inner_radius,
+            step: self.step.round().max(1.0) as u32,
+            corner_radius: self.corner_radius,
+            rotation_offset_degrees: self.rotation_offset,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Property {
+    Rotation,
+    Edges,
+    Hue,
+    Saturation,
+    Brightness,
+    Radius,
+    InnerRadius,
+    CornerRadius,
+    X,
+    Y,
+}
+
+impl Property {
+    pub const ALL: [Property; 10] = [
+        Property::Rotation,
+        Property::Edges,
+        Property::Hue,
+        Property::Saturation,
+        Property::Brightness,
+        Property::Radius,
+        Property::InnerRadius,
+        Property::CornerRadius,
+        Property::X,
+        Property::Y,
+    ];
+
+    pub fn get(&self, pose: &Pose) -> f32 {
+        match self {
+            Property::Rotation => pose.rotation,
+            Property::Edges => pose.edges,
+            Property::Hue => pose.hue,
+            Property::Saturation => pose.saturation,
+            Property::Brightness => pose.brightness,
+            Property::Radius => pose.radius,
+            Property::InnerRadius => pose.inner_radius,
+            Property::CornerRadius => pose.corner_radius,
+            Property::X => pose.x,
+            Property::Y => pose.y,
+        }
+    }
+
+    pub fn set(&self, pose: &mut Pose, value: f32) {
+        match self {
+            Property::Rotation => pose.rotation = value,
+            Property::Edges => pose.edges = value,
+            Property::Hue => pose.hue = value,
+            Property::Saturation => pose.saturation = value,
+            Property::Brightness => pose.brightness = value,
+            Property::Radius => pose.radius = value,
+            Property::InnerRadius => pose.inner_radius = value,
+            Property::CornerRadius => pose.corner_radius = value,
+            Property::X => pose.x = value,
+            Property::Y => pose.y = value,
+        }
+    }
+}
+
+impl std::fmt::Display for Property {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Property::Rotation => "Rotation",
+            Property::Edges => "Edges",
+            Property::Hue => "Hue",
+            Property::Saturation => "Saturation",
+            Property::Brightness => "Brightness",
+            Property::Radius => "Radius",
+            Property::InnerRadius => "Inner radius",
+            Property::CornerRadius => "Corner radius",
+            Property::X => "X",
+            Property::Y => "Y",
+        })
+    }
+}
+
+/// How the value moves from the previous keyframe to this one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Hold the previous value, jump at the keyframe
+    Step,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 5] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Step,
+    ];
+
+    /// Maps the linear progress `t` (0 to 1) to the eased progress.
+    pub fn apply(&self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Easing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Easing::Linear => "Linear",
+            Easing::EaseIn => "Ease in",
+            Easing::EaseOut => "Ease out",
+            Easing::EaseInOut => "Ease in/out",
+            Easing::Step => "Step",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// In seconds from the start of the timeline
+    pub time: f32,
+    pub value: f32,
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Track {
+    pub property: Property,
+    /// Sorted by time
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// The value at the given time, None if the track has no keyframes.
+    pub fn value_at(&self, time: f32) -> Option<f32> {
+        let first = self.keyframes.first()?;
+        if time <= first.time {
+            return Some(first.value);
+        }
+        let next = self.keyframes.iter().position(|key| key.time > time);
+        let Some(next) = next else {
+            return self.keyframes.last().map(|key| key.value);
+        };
+        let (from, to) = (self.keyframes[next - 1], self.keyframes[next]);
+        let t = (time - from.time) / (to.time - from.time).max(f32::EPSILON);
+        Some(from.value + (to.value - from.value) * to.easing.apply(t))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Timeline {
+    /// In seconds
+    pub duration: f32,
+    /// Start again at the end instead of stopping
+    pub looping: bool,
+    pub tracks: Vec<Track>,
+    /// The values of the properties without a track
+    #[serde(default)]
+    pub base: Pose,
+}
+
+impl Default for Timeline {
+    /// One full turn every six seconds.
+    fn default() -> Self {
+        Self {
+            duration: 6.0,
+            looping: true,
+            tracks: vec![Track {
+                property: Property::Rotation,
+                keyframes: vec![
+                    Keyframe {
+                        time: 0.0,
+                        value: 0.0,
+                        easing: Easing::Linear,
+                    },
+                    Keyframe {
+                        time: 6.0,
+                        value: 360.0,
+                        easing: Easing::Linear,
+                    },
+                ],
+            }],
+            base: Pose::default(),
+        }
+    }
+}
+
+impl Timeline {
+    pub fn track(&self, property: Property) -> Option<&Track> {
+        self.tracks.iter().find(|track| track.property == property)
+    }
+
+    /// The pose at the given time: the base pose with the animated properties applied.
+    pub fn pose_at(&self, time: f32) -> Pose {
+        let mut pose = self.base;
+        self.apply(time, &mut pose);
+        pose
+    }
+
+    /// Sets the animated properties of the pose to their values at the given time.
+    pub fn apply(&self, time: f32, pose: &mut Pose) {
+        for track in &self.tracks {
+            if let Some(value) = track.value_at(time) {
+                track.property.set(pose, value);
+            }
+        }
+    }
+
+    /// Adds a keyframe, or replaces the keyframe of the property at the same time.
+    pub fn set_keyframe(&mut self, property: Property, keyframe: Keyframe) {
+        let index = match self.tracks.iter().position(|t| t.property == property) {
+            Some(index) => index,
+            None => {
+                self.tracks.push(Track {
+                    property,
+                    keyframes: Vec::new(),
+                });
+                self.tracks.len() - 1
+            }
+        };
+        let keyframes = &mut self.tracks[index].keyframes;
+        keyframes.retain(|key| (key.time - keyframe.time).abs() >= SAME_TIME);
+        let position = keyframes.partition_point(|key| key.time < keyframe.time);
+        keyframes.insert(position, keyframe);
+    }
+
+    /// Removes the keyframe of the property at the given time. A track without keyframes is
+    /// removed as well.
+    pub fn remove_keyframe(&mut self, property: Property, time: f32) {
+        for track in &mut self.tracks {
+            if track.property == property {
+                track
+                    .keyframes
+                    .retain(|key| (key.time - time).abs() >= SAME_TIME);
+            }
+        }
+        self.tracks.retain(|track| !track.keyframes.is_empty());
+    }
+
+    pub fn remove_track(&mut self, property: Property) {
+        self.tracks.retain(|track| track.property != property);
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut timeline: Timeline = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        // keyframes edited by hand may be out of order
+        for track in &mut timeline.tracks {
+            track.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        }
+        timeline.duration = timeline.duration.max(SAME_TIME);
+        Ok(timeline)
+    }
+}
+
+/// The play head of the timeline.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Playback {
+    /// In seconds from the start of the timeline
+    pub time: f32,
+    pub playing: bool,
+    /// Time of the previous tick, None right after starting
+    last_tick: Option<Instant>,
+}
+
+impl Playback {
+    pub fn play(&mut self, timeline: &Timeline) {
+        // playing at the end of a timeline that doesn't loop starts from the beginning
+        if !timeline.looping && self.time >= timeline.duration {
+            self.time = 0.0;
+        }
+        self.playing = true;
+        self.last_tick = None;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Moves the play head by the time since the last tick.
+    pub fn tick(&mut self, now: Instant, timeline: &Timeline) {
+        if !self.playing {
+            return;
+        }
+        let elapsed = self
+            .last_tick
+            .map(|last| now.duration_since(last).as_secs_f32())
+            .unwrap_or_default();
+        self.last_tick = Some(now);
+        self.time += elapsed;
+        if self.time >= timeline.duration {
+            if timeline.looping {
+                self.time %= timeline.duration;
+            } else {
+                self.time = timeline.duration;
+                self.playing = false;
+            }
+        }
+    }
+
+    /// Jumps to the given time (scrubbing).
+    pub fn seek(&mut self, time: f32, timeline: &Timeline) {
+        self.time = time.clamp(0.0, timeline.duration);
+        self.last_tick = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced_hello_world::curves::CurveKind;
+    use std::time::Duration;
+
+    fn keyframe(time: f32, value: f32, easing: Easing) -> Keyframe {
+        Keyframe {
+            time,
+            value,
+            easing,
+        }
+    }
+
+    fn track(keyframes: Vec<Keyframe>) -> Track {
+        Track {
+            property: Property::Radius,
+            keyframes,
+        }
+    }
+
+    #[test]
+    fn values_are_held_before_the_first_and_after_the_last_keyframe() {
+        let track = track(vec![
+            keyframe(1.0, 10.0, Easing::Linear),
+            keyframe(3.0, 30.0, Easing::Linear),
+        ]);
+        assert_eq!(track.value_at(-5.0), Some(10.0));
+        assert_eq!(track.value_at(1.0), Some(10.0));
+        assert_eq!(track.value_at(2.0), Some(20.0));
+        assert_eq!(track.value_at(3.0), Some(30.0));
+        assert_eq!(track.value_at(100.0), Some(30.0));
+        assert_eq!(self::track(Vec::new()).value_at(1.0), None);
+
+        // properties without a track keep the base value
+        let mut timeline = Timeline {
+            tracks: vec![track],
+            ..Timeline::default()
+        };
+        timeline.base.hue = 42.0;
+        let pose = timeline.pose_at(0.0);
+        assert_eq!((pose.radius, pose.hue), (10.0, 42.0));
+    }
+
+    #[test]
+    fn easings_start_and_end_at_the_keyframes() {
+        let expected = [
+            (Easing::Linear, 0.5),
+            (Easing::EaseIn, 0.125),
+            (Easing::EaseOut, 0.875),
+            (Easing::EaseInOut, 0.5),
+            (Easing::Step, 0.0),
+        ];
+        for (easing, middle) in expected {
+            assert_eq!(easing.apply(0.0), 0.0, "{easing}");
+            assert_eq!(easing.apply(0.5), middle, "{easing}");
+            assert_eq!(easing.apply(1.0), 1.0, "{easing}");
+            // the easing of the later keyframe shapes the way to it
+            let track = track(vec![
+                keyframe(0.0, 100.0, Easing::Linear),
+                keyframe(2.0, 200.0, easing),
+            ]);
+            assert_eq!(
+                track.value_at(1.0),
+                Some(100.0 + 100.0 * middle),
+                "{easing}"
+            );
+        }
+    }
+
+    #[test]
+    fn keyframes_at_the_same_time_replace_each_other() {
+        let mut timeline = Timeline {
+            tracks: Vec::new(),
+            ..Timeline::default()
+        };
+        timeline.set_keyframe(Property::Hue, keyframe(2.0, 20.0, Easing::Linear));
+        timeline.set_keyframe(Property::Hue, keyframe(0.0, 0.0, Easing::Linear));
+        timeline.set_keyframe(
+            Property::Hue,
+            keyframe(2.0 + SAME_TIME / 2.0, 90.0, Easing::Step),
+        );
+        let track = timeline.track(Property::Hue).expect("a hue track");
+        assert_eq!(
+            track.keyframes,
+            vec![
+                keyframe(0.0, 0.0, Easing::Linear),
+                keyframe(2.0 + SAME_TIME / 2.0, 90.0, Easing::Step),
+            ]
+        );
+        // a keyframe a bit further away is a new one
+        timeline.set_keyframe(
+            Property::Hue,
+            keyframe(2.0 + SAME_TIME * 2.0, 10.0, Easing::Linear),
+        );
+        assert_eq!(
+            timeline
+                .track(Property::Hue)
+                .map(|track| track.keyframes.len()),
+            Some(3)
+        );
+
+        // removing the last keyframes removes the track
+        timeline.remove_keyframe(Property::Hue, 0.0);
+        timeline.remove_keyframe(Property::Hue, 2.0);
+        timeline.remove_keyframe(Property::Hue, 2.0 + SAME_TIME * 2.0);
+        assert_eq!(timeline.track(Property::Hue), None);
+    }
+
+    #[test]
+    fn looping_playback_starts_again_at_the_end() {
+        let mut timeline = Timeline::default();
+        let start = Instant::now();
+        let mut playback = Playback::default();
+        playback.play(&timeline);
+        playback.tick(start, &timeline);
+        playback.tick(start + Duration::from_secs_f32(7.5), &timeline);
+        assert!(playback.playing);
+        assert!((playback.time - 1.5).abs() < 1e-3, "{}", playback.time);
+
+        // without looping the play head stops at the end
+        timeline.looping = false;
+        playback.tick(start + Duration::from_secs(20), &timeline);
+        assert!(!playback.playing);
+        assert_eq!(playback.time, timeline.duration);
+    }
+
+    #[test]
+    fn saved_timelines_load_unchanged() {
+        let mut timeline = Timeline::default();
+        timeline.set_keyframe(Property::Hue, keyframe(1.25, 120.0, Easing::EaseInOut));
+        timeline.set_keyframe(Property::Hue, keyframe(4.0, 0.3, Easing::Step));
+        timeline.looping = false;
+        timeline.base.kind = PolygonKind::Star;
+        timeline.base.curve = Some(CurveKind::Rose.default_curve());
+        timeline.base.x = 12.345;
+
+        let file = std::env::temp_dir().join(format!("timeline_test_{}.json", std::process::id()));
+        timeline.save(&file).expect("saved");
+        let loaded = Timeline::load(&file);
+        let _ = std::fs::remove_file(&file);
+        assert_eq!(loaded.expect("loaded"), timeline);
+    }
+}
@@ -0,0 +1,593 @@
+/*!
+Boolean operations (union, intersection, difference, xor) on polygons and paths.
+
+Concept:
+- Both operands are sets of closed rings filled with a fill rule, so holes and multiple islands
+  are supported. Paths are flattened first, open subpaths count as closed.
+- All edges of both operands are split at every intersection (including overlapping, collinear
+  edges and vertices touching an edge), so the pieces only meet at their end points.
+- Each piece is classified by looking at both of its sides: a piece belongs to the outline of
+  the result if the result is filled on one side and empty on the other. Coincident edges of
+  both operands end up as one piece and are classified like any other piece, no special cases
+  needed.
+- The kept pieces are oriented so that the filled side is on the same side and then linked into
+  rings. The rings wind around the filled area exactly once, so the result looks the same with
+  both fill rules.
+- The computation runs in f64. Points closer than `SNAP` are treated as one point.
+*/
+
+use std::collections::{HashMap, HashSet};
+
+use iced::Point;
+
+use crate::hit_test::FillRule;
+use crate::vector_path::VectorPath;
+
+/// Points closer than this (in world units) are merged.
+const SNAP: f64 = 1e-6;
+/// Distance of the probe points from a piece when classifying it.
+const SIDE_OFFSET: f64 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// Area covered by any operand
+    Union,
+    /// Area covered by both operands
+    Intersection,
+    /// Area of the first operand not covered by the second one
+    Difference,
+    /// Area covered by exactly one operand
+    Xor,
+}
+
+impl BooleanOp {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b,
+            BooleanOp::Xor => a != b,
+        }
+    }
+}
+
+/// Combines two sets of closed rings. Returns the rings of the result (without repeating the
+/// first point at the end).
+pub fn boolean_polygons(
+    subject: &[Vec<Point>],
+    subject_rule: FillRule,
+    clip: &[Vec<Point>],
+    clip_rule: FillRule,
+    op: BooleanOp,
+) -> Vec<Vec<Point>> {
+    let subject = Operand::new(subject, subject_rule);
+    let clip = Operand::new(clip, clip_rule);
+
+    let pieces = split_edges(subject.edges().chain(clip.edges()).collect());
+
+    // keep the pieces with the result on exactly one side, filled side on the left
+    let mut boundary = Vec::new();
+    for (a, b) in pieces {
+        let direction = sub(b, a);
+        let length = direction.0.hypot(direction.1);
+        let normal = (-direction.1 / length, direction.0 / length);
+        let middle = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+        let offset = SIDE_OFFSET.min(length / 4.0);
+        let left = (middle.0 + normal.0 * offset, middle.1 + normal.1 * offset);
+        let right = (middle.0 - normal.0 * offset, middle.1 - normal.1 * offset);
+        let filled = |p: (f64, f64)| op.apply(subject.contains(p), clip.contains(p));
+        match (filled(left), filled(right)) {
+            (true, false) => boundary.push((a, b)),
+            (false, true) => boundary.push((b, a)),
+            _ => {}
+        }
+    }
+
+    link_rings(boundary)
+        .into_iter()
+        .map(simplify_ring)
+        .filter(|ring| ring.len() >= 3)
+        .map(|ring| {
+            ring.into_iter()
+                .map(|(x, y)| Point::new(x as f32, y as f32))
+                .collect()
+        })
+        .collect()
+}
+
+/// Combines two paths. Curves are flattened with the given tolerance. The result consists of
+/// closed polygons.
+pub fn boolean_paths(
+    subject: &VectorPath,
+    clip: &VectorPath,
+    op: BooleanOp,
+    tolerance: f32,
+) -> VectorPath {
+    let rings = boolean_polygons(
+        &path_rings(subject, tolerance),
+        FillRule::NonZero,
+        &path_rings(clip, tolerance),
+        FillRule::NonZero,
+        op,
+    );
+    let mut segments = Vec::new();
+    for ring in rings {
+        segments.extend(VectorPath::polygon(&ring).segments);
+    }
+    VectorPath::new(segments)
+}
+
+/// Flattens a path into closed rings (without repeating the first point at the end).
+pub fn path_rings(path: &VectorPath, tolerance: f32) -> Vec<Vec<Point>> {
+    path.flatten(tolerance)
+        .into_iter()
+        .map(|mut ring| {
+            if ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
+            }
+            ring
+        })
+        .filter(|ring| ring.len() >= 3)
+        .collect()
+}
+
+type P = (f64, f64);
+
+fn sub(a: P, b: P) -> P {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn cross(a: P, b: P) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: P, b: P) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn key(p: P) -> (i64, i64) {
+    ((p.0 / SNAP).round() as i64, (p.1 / SNAP).round() as i64)
+}
+
+/// One operand: its rings in f64 and the fill rule.
+struct Operand {
+    rings: Vec<Vec<P>>,
+    fill_rule: FillRule,
+}
+
+impl Operand {
+    fn new(rings: &[Vec<Point>], fill_rule: FillRule) -> Self {
+        let rings = rings
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .map(|p| (p.x as f64, p.y as f64))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|ring| ring.len() >= 3)
+            .collect();
+        Self { rings, fill_rule }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (P, P)> + '_ {
+        self.rings.iter().flat_map(|ring| {
+            ring.iter()
+                .enumerate()
+                .map(|(i, a)| (*a, ring[(i + 1) % ring.len()]))
+                .filter(|(a, b)| key(*a) != key(*b))
+        })
+    }
+
+    fn contains(&self, p: P) -> bool {
+        let mut winding = 0;
+        let mut crossings = 0;
+        for (a, b) in self.edges() {
+            if (a.1 > p.1) != (b.1 > p.1) {
+                let x = a.0 + (p.1 - a.1) / (b.1 - a.1) * (b.0 - a.0);
+                if p.0 < x {
+                    crossings += 1;
+                    winding += if b.1 > a.1 { 1 } else { -1 };
+                }
+            }
+        }
+        match self.fill_rule {
+            FillRule::EvenOdd => crossings % 2 == 1,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+}
+
+/// Splits the edges at all points where they touch or cross each other. Returns the pieces
+/// without duplicates (two coincident edges give one piece).
+fn split_edges(edges: Vec<(P, P)>) -> Vec<(P, P)> {
+    let mut splits: Vec<Vec<P>> = edges.iter().map(|(a, b)| vec![*a, *b]).collect();
+
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            for point in intersections(edges[i], edges[j]) {
+                splits[i].push(point);
+                splits[j].push(point);
+            }
+        }
+    }
+
+    // all points with the same key are replaced by the first one seen, so pieces connect
+    let mut points: HashMap<(i64, i64), P> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut pieces = Vec::new();
+    for ((a, b), mut split) in edges.into_iter().zip(splits) {
+        let direction = sub(b, a);
+        split.sort_by(|p, q| dot(sub(*p, a), direction).total_cmp(&dot(sub(*q, a), direction)));
+        let split: Vec<P> = split
+            .into_iter()
+            .map(|p| *points.entry(key(p)).or_insert(p))
+            .collect();
+        for pair in split.windows(2) {
+            let (ka, kb) = (key(pair[0]), key(pair[1]));
+            if ka != kb && seen.insert((ka.min(kb), ka.max(kb))) {
+                pieces.push((pair[0], pair[1]));
+            }
+        }
+    }
+    pieces
+}
+
+/// Points where two edges touch or cross. Collinear overlapping edges touch at the end points
+/// of the overlap.
+fn intersections((a0, a1): (P, P), (b0, b1): (P, P)) -> Vec<P> {
+    let r = sub(a1, a0);
+    let s = sub(b1, b0);
+    let (r_length, s_length) = (r.0.hypot(r.1), s.0.hypot(s.1));
+    let denominator = cross(r, s);
+    let between = |p: P, start: P, direction: P, length: f64| {
+        let t = dot(sub(p, start), direction) / (length * length);
+        let distance = cross(direction, sub(p, start)).abs() / length;
+        distance <= SNAP && t > -SNAP / length && t < 1.0 + SNAP / length
+    };
+
+    if denominator.abs() <= 1e-12 * r_length * s_length {
+        // parallel: only collinear edges can touch, at the end points of each other
+        let mut points = Vec::new();
+        for p in [b0, b1] {
+            if between(p, a0, r, r_length) {
+                points.push(p);
+            }
+        }
+        for p in [a0, a1] {
+            if between(p, b0, s, s_length) {
+                points.push(p);
+            }
+        }
+        return points;
+    }
+
+    let q = sub(b0, a0);
+    let t = cross(q, s) / denominator;
+    let u = cross(q, r) / denominator;
+    let (t_slack, u_slack) = (SNAP / r_length, SNAP / s_length);
+    if t < -t_slack || t > 1.0 + t_slack || u < -u_slack || u > 1.0 + u_slack {
+        return Vec::new();
+    }
+    let t = t.clamp(0.0, 1.0);
+    vec![(a0.0 + r.0 * t, a0.1 + r.1 * t)]
+}
+
+/// Links directed edges into closed rings.
+fn link_rings(edges: Vec<(P, P)>) -> Vec<Vec<P>> {
+    let mut outgoing: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, (a, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(*a)).or_default().push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let start = key(edges[first].0);
+        let mut ring = vec![edges[first].0];
+        let mut current = first;
+        loop {
+            let end = edges[current].1;
+            if key(end) == start {
+                rings.push(ring);
+                break;
+            }
+            ring.push(end);
+            let next = outgoing
+                .get(&key(end))
+                .and_then(|candidates| candidates.iter().copied().find(|i| !used[*i]));
+            match next {
+                Some(next) => {
+                    used[next] = true;
+                    current = next;
+                }
+                // can only happen for degenerated input, drop the open chain
+                None => break,
+            }
+        }
+    }
+    rings
+}
+
+/// Removes points that lie on a straight line between their neighbours.
+fn simplify_ring(ring: Vec<P>) -> Vec<P> {
+    let mut ring = ring;
+    let mut i = 0;
+    while ring.len() >= 3 && i < ring.len() {
+        let previous = ring[(i + ring.len() - 1) % ring.len()];
+        let next = ring[(i + 1) % ring.len()];
+        let (u, v) = (sub(ring[i], previous), sub(next, ring[i]));
+        let length = u.0.hypot(u.1) * v.0.hypot(v.1);
+        if cross(u, v).abs() <= 1e-9 * length && dot(u, v) > 0.0 {
+            ring.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+    ring
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit_test::{point_in_polygon, winding_number};
+    use crate::vector_path::Segment;
+
+    const ALL_OPS: [BooleanOp; 4] = [
+        BooleanOp::Union,
+        BooleanOp::Intersection,
+        BooleanOp::Difference,
+        BooleanOp::Xor,
+    ];
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Point> {
+        vec![
+            Point::new(x, y),
+            Point::new(x + size, y),
+            Point::new(x + size, y + size),
+            Point::new(x, y + size),
+        ]
+    }
+
+    /// A circle made of four cubic Bézier curves.
+    fn circle(center: Point, radius: f32) -> VectorPath {
+        let k = 0.552_284_8 * radius;
+        let at = |x: f32, y: f32| Point::new(center.x + x, center.y + y);
+        VectorPath::new(vec![
+            Segment::MoveTo(at(radius, 0.0)),
+            Segment::CubicTo(at(radius, k), at(k, radius), at(0.0, radius)),
+            Segment::CubicTo(at(-k, radius), at(-radius, k), at(-radius, 0.0)),
+            Segment::CubicTo(at(-radius, -k), at(-k, -radius), at(0.0, -radius)),
+            Segment::CubicTo(at(k, -radius), at(radius, -k), at(radius, 0.0)),
+            Segment::Close,
+        ])
+    }
+
+    /// Filled area of the result: holes wind the other way, so their area is subtracted.
+    fn area(rings: &[Vec<Point>]) -> f32 {
+        let signed: f32 = rings
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .enumerate()
+                    .map(|(i, a)| {
+                        let b = ring[(i + 1) % ring.len()];
+                        a.x * b.y - b.x * a.y
+                    })
+                    .sum::<f32>()
+                    / 2.0
+            })
+            .sum();
+        signed.abs()
+    }
+
+    fn filled(rings: &[Vec<Point>], p: Point) -> bool {
+        rings
+            .iter()
+            .map(|ring| winding_number(p, ring))
+            .sum::<i32>()
+            != 0
+    }
+
+    /// Compares the result with the operation applied to both operands at many points (none of
+    /// them on an edge) and checks that both fill rules see the same result.
+    fn assert_matches_operands(
+        a: &[Vec<Point>],
+        b: &[Vec<Point>],
+        op: BooleanOp,
+    ) -> Vec<Vec<Point>> {
+        let result = boolean_polygons(a, FillRule::NonZero, b, FillRule::NonZero, op);
+        for x in 0..60 {
+            for y in 0..60 {
+                let p = Point::new(x as f32 * 2.0 - 30.13, y as f32 * 2.0 - 30.29);
+                let inside = |rings: &[Vec<Point>]| filled(rings, p);
+                let expected = op.apply(inside(a), inside(b));
+                assert_eq!(filled(&result, p), expected, "{op:?} at {p:?}: {result:?}");
+                let even_odd = result
+                    .iter()
+                    .filter(|ring| point_in_polygon(p, ring, FillRule::EvenOdd))
+                    .count()
+                    % 2
+                    == 1;
+                assert_eq!(even_odd, expected, "{op:?} even-odd at {p:?}: {result:?}");
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn squares_sharing_an_edge() {
+        let (a, b) = (vec![square(0.0, 0.0, 10.0)], vec![square(10.0, 0.0, 10.0)]);
+        for op in ALL_OPS {
+            assert_matches_operands(&a, &b, op);
+        }
+        // the shared edge disappears, the union is one rectangle with four corners
+        let union = boolean_polygons(
+            &a,
+            FillRule::NonZero,
+            &b,
+            FillRule::NonZero,
+            BooleanOp::Union,
+        );
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].len(), 4);
+        assert_eq!(area(&union), 200.0);
+        let intersection = boolean_polygons(
+            &a,
+            FillRule::NonZero,
+            &b,
+            FillRule::NonZero,
+            BooleanOp::Intersection,
+        );
+        assert!(intersection.is_empty(), "{intersection:?}");
+        let difference = boolean_polygons(
+            &a,
+            FillRule::NonZero,
+            &b,
+            FillRule::NonZero,
+            BooleanOp::Difference,
+        );
+        assert_eq!(area(&difference), 100.0);
+    }
+
+    #[test]
+    fn squares_touching_at_a_vertex() {
+        let (a, b) = (vec![square(0.0, 0.0, 10.0)], vec![square(10.0, 10.0, 10.0)]);
+        for op in ALL_OPS {
+            assert_matches_operands(&a, &b, op);
+        }
+        let union = boolean_polygons(
+            &a,
+            FillRule::NonZero,
+            &b,
+            FillRule::NonZero,
+            BooleanOp::Union,
+        );
+        assert_eq!(area(&union), 200.0);
+        let intersection = boolean_polygons(
+            &a,
+            FillRule::NonZero,
+            &b,
+            FillRule::NonZero,
+            BooleanOp::Intersection,
+        );
+        assert!(intersection.is_empty(), "{intersection:?}");
+    }
+
+    #[test]
+    fn shape_fully_inside_another() {
+        let outer = vec![square(-20.0, -20.0, 40.0)];
+        let inner = vec![square(-10.0, -10.0, 20.0)];
+        for op in ALL_OPS {
+            assert_matches_operands(&outer, &inner, op);
+            assert_matches_operands(&inner, &outer, op);
+        }
+        let union = boolean_polygons(
+            &outer,
+            FillRule::NonZero,
+            &inner,
+            FillRule::NonZero,
+            BooleanOp::Union,
+        );
+        assert_eq!(union.len(), 1);
+        assert_eq!(area(&union), 1600.0);
+        let intersection = boolean_polygons(
+            &outer,
+            FillRule::NonZero,
+            &inner,
+            FillRule::NonZero,
+            BooleanOp::Intersection,
+        );
+        assert_eq!(area(&intersection), 400.0);
+        let nothing_left = boolean_polygons(
+            &inner,
+            FillRule::NonZero,
+            &outer,
+            FillRule::NonZero,
+            BooleanOp::Difference,
+        );
+        assert!(nothing_left.is_empty(), "{nothing_left:?}");
+    }
+
+    #[test]
+    fn difference_creates_a_hole() {
+        let outer = vec![square(-20.0, -20.0, 40.0)];
+        let inner = vec![square(-10.0, -10.0, 20.0)];
+        let result = assert_matches_operands(&outer, &inner, BooleanOp::Difference);
+        assert_eq!(result.len(), 2);
+        assert_eq!(area(&result), 1200.0);
+        assert!(!filled(&result, Point::ORIGIN));
+        // the hole winds the other way than the outline
+        let windings: Vec<i32> = result
+            .iter()
+            .map(|ring| winding_number(Point::ORIGIN, ring))
+            .collect();
+        assert_eq!(windings.iter().sum::<i32>(), 0, "{windings:?}");
+        assert!(windings.iter().all(|w| w.abs() == 1), "{windings:?}");
+    }
+
+    #[test]
+    fn xor_of_identical_shapes_is_empty() {
+        let shapes = [
+            vec![square(0.0, 0.0, 10.0)],
+            vec![vec![
+                Point::new(0.0, 0.0),
+                Point::new(30.0, 5.0),
+                Point::new(10.0, 25.0),
+            ]],
+        ];
+        for shape in shapes {
+            let xor = boolean_polygons(
+                &shape,
+                FillRule::NonZero,
+                &shape,
+                FillRule::NonZero,
+                BooleanOp::Xor,
+            );
+            assert!(xor.is_empty(), "{xor:?}");
+            let union = boolean_polygons(
+                &shape,
+                FillRule::NonZero,
+                &shape,
+                FillRule::NonZero,
+                BooleanOp::Union,
+            );
+            assert!((area(&union) - area(&shape)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn flattened_circles() {
+        let radius = 20.0;
+        let a = circle(Point::new(-10.0, 0.0), radius);
+        let b = circle(Point::new(10.0, 0.0), radius);
+        let rings = |path: &VectorPath| path_rings(path, 0.01);
+        for op in ALL_OPS {
+            assert_matches_operands(&rings(&a), &rings(&b), op);
+        }
+
+        // the circles overlap in a lens, the centers are one radius apart
+        let r2 = radius * radius;
+        let circle_area = std::f32::consts::PI * r2;
+        let lens = r2 * (2.0 * std::f32::consts::PI / 3.0 - 3.0_f32.sqrt() / 2.0);
+        let expected = [
+            (BooleanOp::Union, 2.0 * circle_area - lens),
+            (BooleanOp::Intersection, lens),
+            (BooleanOp::Difference, circle_area - lens),
+            (BooleanOp::Xor, 2.0 * (circle_area - lens)),
+        ];
+        for (op, expected) in expected {
+            let result = boolean_paths(&a, &b, op, 0.01);
+            let actual = area(&path_rings(&result, 0.01));
+            assert!(
+                (actual - expected).abs() < expected * 0.005,
+                "{op:?}: {actual} vs {expected}"
+            );
+            assert!(result.is_closed());
+        }
+    }
+}
@@ -0,0 +1,434 @@
+/*!
+Parametric curves: superellipses, spirographs (hypotrochoids and epitrochoids), Lissajous
+figures and roses.
+
+Concept:
+- Every curve is a function of an angle `t`, closed after `period()`. The integer parameters
+  (wheel sizes, frequencies, petal ratios) keep the curves closed.
+- The curve is scaled to fit into the square of `2 * radius` around the center, whatever its
+  parameters.
+- Sampling is adaptive: an interval is split as long as its middle point is farther than the
+  tolerance from the straight line between its ends. The tolerance is in output units (pixels),
+  so a curve sampled for a larger size gets more points and stays smooth.
+- Sharp features (the tips of a superellipse with a small exponent, the cusps of a spirograph)
+  get many points, long straight or gently curved stretches get few.
+- The result is a ring of points like `polygon::polygon_points`, so the same renderers draw it.
+*/
+
+use std::f32::consts::PI;
+
+use iced::{Point, Vector};
+use serde::{Deserialize, Serialize};
+
+use crate::hit_test::distance_to_segment;
+use crate::polygon::gcd;
+
+/// Intervals per full turn of `t` before the adaptive splitting. Enough to catch every petal and
+/// loop, which could otherwise fall between two samples.
+const INITIAL_SEGMENTS_PER_TURN: usize = 64;
+/// Maximum number of times an interval is halved.
+const MAX_DEPTH: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveKind {
+    Superellipse,
+    Hypotrochoid,
+    Epitrochoid,
+    Lissajous,
+    Rose,
+}
+
+impl CurveKind {
+    pub const ALL: [CurveKind; 5] = [
+        CurveKind::Superellipse,
+        CurveKind::Hypotrochoid,
+        CurveKind::Epitrochoid,
+        CurveKind::Lissajous,
+        CurveKind::Rose,
+    ];
+
+    /// A curve of this kind with good looking parameters.
+    pub fn default_curve(&self) -> Curve {
+        match self {
+            CurveKind::Superellipse => Curve::Superellipse {
+                exponent: 4.0,
+                aspect: 1.0,
+            },
+            CurveKind::Hypotrochoid => Curve::Hypotrochoid {
+                fixed: 5,
+                rolling: 3,
+                pen: 0.8,
+            },
+            CurveKind::Epitrochoid => Curve::Epitrochoid {
+                fixed: 3,
+                rolling: 1,
+                pen: 0.5,
+            },
+            CurveKind::Lissajous => Curve::Lissajous {
+                a: 3,
+                b: 2,
+                phase_degrees: 90.0,
+            },
+            CurveKind::Rose => Curve::Rose {
+                petals: 5,
+                divisor: 1,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for CurveKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CurveKind::Superellipse => "Superellipse",
+            CurveKind::Hypotrochoid => "Spirograph (inside)",
+            CurveKind::Epitrochoid => "Spirograph (outside)",
+            CurveKind::Lissajous => "Lissajous",
+            CurveKind::Rose => "Rose",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Curve {
+    /// `|x|^n + |y / aspect|^n = 1`, a squircle for n = 4
+    Superellipse { exponent: f32, aspect: f32 },
+    /// A wheel with `rolling` teeth rolling inside a ring with `fixed` teeth. The pen is at
+    /// `pen` times the wheel radius from the wheel center.
+    Hypotrochoid { fixed: u32, rolling: u32, pen: f32 },
+    /// Like the hypotrochoid, with the wheel rolling around the outside of the ring
+    Epitrochoid { fixed: u32, rolling: u32, pen: f32 },
+    /// `x = sin(a t + phase)`, `y = sin(b t)`
+    Lissajous { a: u32, b: u32, phase_degrees: f32 },
+    /// `r = cos(petals / divisor * t)`
+    Rose { petals: u32, divisor: u32 },
+}
+
+impl Curve {
+    pub fn kind(&self) -> CurveKind {
+        match self {
+            Curve::Superellipse { .. } => CurveKind::Superellipse,
+            Curve::Hypotrochoid { .. } => CurveKind::Hypotrochoid,
+            Curve::Epitrochoid { .. } => CurveKind::Epitrochoid,
+            Curve::Lissajous { .. } => CurveKind::Lissajous,
+            Curve::Rose { .. } => CurveKind::Rose,
+        }
+    }
+
+    /// The range of `t` after which the curve is closed.
+    pub fn period(&self) -> f32 {
+        match *self {
+            Curve::Superellipse { .. } | Curve::Lissajous { .. } => 2.0 * PI,
+            Curve::Hypotrochoid { fixed, rolling, .. }
+            | Curve::Epitrochoid { fixed, rolling, .. } => {
+                let (fixed, rolling) = (fixed.max(1), rolling.max(1));
+                2.0 * PI * (rolling / gcd(fixed, rolling)) as f32
+            }
+            Curve::Rose { petals, divisor } => {
+                let (petals, divisor) = reduced(petals.max(1), divisor.max(1));
+                if petals % 2 == 1 && divisor % 2 == 1 {
+                    PI * divisor as f32
+                } else {
+                    2.0 * PI * divisor as f32
+                }
+            }
+        }
+    }
+
+    /// The point at `t` in curve units, before the scaling to the radius.
+    pub fn unit_point(&self, t: f32) -> Point {
+        match *self {
+            Curve::Superellipse { exponent, aspect } => {
+                let power = 2.0 / exponent.max(0.05);
+                let (sin, cos) = t.sin_cos();
+                Point::new(
+                    cos.signum() * cos.abs().powf(power),
+                    aspect * sin.signum() * sin.abs().powf(power),
+                )
+            }
+            Curve::Hypotrochoid {
+                fixed,
+                rolling,
+                pen,
+            } => {
+                let (big, small) = (fixed.max(1) as f32, rolling.max(1) as f32);
+                let distance = pen * small;
+                let k = (big - small) / small;
+                Point::new(
+                    (big - small) * t.cos() + distance * (k * t).cos(),
+                    (big - small) * t.sin() - distance * (k * t).sin(),
+                )
+            }
+            Curve::Epitrochoid {
+                fixed,
+                rolling,
+                pen,
+            } => {
+                let (big, small) = (fixed.max(1) as f32, rolling.max(1) as f32);
+                let distance = pen * small;
+                let k = (big + small) / small;
+                Point::new(
+                    (big + small) * t.cos() - distance * (k * t).cos(),
+                    (big + small) * t.sin() - distance * (k * t).sin(),
+                )
+            }
+            Curve::Lissajous {
+                a,
+                b,
+                phase_degrees,
+            } => Point::new(
+                (a as f32 * t + phase_degrees.to_radians()).sin(),
+                (b as f32 * t).sin(),
+            ),
+            Curve::Rose { petals, divisor } => {
+                let r = (petals as f32 / divisor.max(1) as f32 * t).cos();
+                Point::new(r * t.cos(), r * t.sin())
+            }
+        }
+    }
+
+    /// Half the side of the square around the center the curve fits into, in curve units.
+    pub fn extent(&self) -> f32 {
+        match *self {
+            Curve::Superellipse { aspect, .. } => aspect.abs().max(1.0),
+            Curve::Hypotrochoid {
+                fixed,
+                rolling,
+                pen,
+            } => {
+                let (big, small) = (fixed.max(1) as f32, rolling.max(1) as f32);
+                (big - small).abs() + pen.abs() * small
+            }
+            Curve::Epitrochoid {
+                fixed,
+                rolling,
+                pen,
+            } => {
+                let (big, small) = (fixed.max(1) as f32, rolling.max(1) as f32);
+                big + small + pen.abs() * small
+            }
+            Curve::Lissajous { .. } | Curve::Rose { .. } => 1.0,
+        }
+    }
+}
+
+/// Samples the curve scaled to `radius` around `center`. No point is farther than `tolerance`
+/// from the curve between its neighbours. The last point is not repeated.
+pub fn curve_points(curve: &Curve, center: Point, radius: f32, tolerance: f32) -> Vec<Point> {
+    let scale = radius / curve.extent().max(f32::EPSILON);
+    let at = |t: f32| {
+        let point = curve.unit_point(t);
+        center + Vector::new(point.x * scale, point.y * scale)
+    };
+    let tolerance = tolerance.max(0.01);
+    let period = curve.period();
+    let turns = (period / (2.0 * PI)).ceil().max(1.0) as usize;
+    let segments = INITIAL_SEGMENTS_PER_TURN * turns;
+
+    let mut points = Vec::new();
+    for i in 0..segments {
+        let t0 = period * i as f32 / segments as f32;
+        let t1 = period * (i + 1) as f32 / segments as f32;
+        let start = at(t0);
+        points.push(start);
+        subdivide(
+            &at,
+            (t0, start),
+            (t1, at(t1)),
+            tolerance,
+            MAX_DEPTH,
+            &mut points,
+        );
+    }
+    points
+}
+
+/// Adds the points between `from` and `to` (both excluded) needed to stay within the tolerance.
+fn subdivide(
+    at: &impl Fn(f32) -> Point,
+    from: (f32, Point),
+    to: (f32, Point),
+    tolerance: f32,
+    depth: u32,
+    points: &mut Vec<Point>,
+) {
+    let t = (from.0 + to.0) / 2.0;
+    let middle = at(t);
+    if depth == 0 || distance_to_segment(middle, from.1, to.1) <= tolerance {
+        return;
+    }
+    subdivide(at, from, (t, middle), tolerance, depth - 1, points);
+    points.push(middle);
+    subdivide(at, (t, middle), to, tolerance, depth - 1, points);
+}
+
+fn reduced(numerator: u32, denominator: u32) -> (u32, u32) {
+    let divisor = gcd(numerator, denominator);
+    (numerator / divisor, denominator / divisor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Curves with sharp tips, cusps, loops and many petals.
+    fn test_curves() -> Vec<Curve> {
+        let mut curves: Vec<Curve> = CurveKind::ALL
+            .iter()
+            .map(CurveKind::default_curve)
+            .collect();
+        curves.extend([
+            Curve::Superellipse {
+                exponent: 0.5,
+                aspect: 0.5,
+            },
+            Curve::Hypotrochoid {
+                fixed: 7,
+                rolling: 4,
+                pen: 1.0,
+            },
+            Curve::Epitrochoid {
+                fixed: 6,
+                rolling: 4,
+                pen: 1.4,
+            },
+            Curve::Lissajous {
+                a: 9,
+                b: 8,
+                phase_degrees: 30.0,
+            },
+            Curve::Rose {
+                petals: 7,
+                divisor: 4,
+            },
+        ]);
+        curves
+    }
+
+    /// Checks that the curve between two sampled points stays within `tolerance` of the chord
+    /// between them, using a dense sampling of the curve as reference.
+    fn assert_chords_within(curve: &Curve, radius: f32, tolerance: f32) {
+        let points = curve_points(curve, Point::ORIGIN, radius, tolerance);
+        let scale = radius / curve.extent();
+        let period = curve.period();
+        let turns = (period / (2.0 * PI)).ceil() as usize;
+        // two reference points per smallest possible interval
+        let steps_per_interval = 2 << MAX_DEPTH;
+        let dense_count = INITIAL_SEGMENTS_PER_TURN * turns * steps_per_interval;
+        let mut dense: Vec<Point> = (0..dense_count)
+            .map(|i| {
+                let point = curve.unit_point(period * i as f32 / dense_count as f32);
+                Point::new(point.x * scale, point.y * scale)
+            })
+            .collect();
+        // the curve is closed, and `t = period` isn't exact in f32
+        dense.push(dense[0]);
+        // f32 can't place the points more exactly than this
+        let slack = radius * 1e-5;
+
+        // find every sampled point in the reference, going forward along the curve; the curve
+        // is closed, so the last chord ends at the first point
+        let mut start = 0;
+        for (k, point) in points.iter().chain([&points[0]]).enumerate().skip(1) {
+            let window = if k == points.len() {
+                dense.len() - 1..dense.len()
+            } else {
+                start + 1..(start + steps_per_interval + 1).min(dense.len())
+            };
+            let end = window
+                .min_by(|a, b| {
+                    dense[*a]
+                        .distance(*point)
+                        .total_cmp(&dense[*b].distance(*point))
+                })
+                .expect("a reference point");
+            assert!(
+                dense[end].distance(*point) <= slack,
+                "{curve:?}: {point:?} is off the curve"
+            );
+            for reference in &dense[start..=end] {
+                let distance = distance_to_segment(*reference, dense[start], dense[end]);
+                assert!(
+                    distance <= tolerance + slack,
+                    "{curve:?} at radius {radius}: {reference:?} is {distance} away from the chord"
+                );
+            }
+            start = end;
+        }
+    }
+
+    #[test]
+    fn chords_stay_within_the_tolerance() {
+        for curve in test_curves() {
+            assert_chords_within(&curve, 20.0, 0.25);
+            assert_chords_within(&curve, 1000.0, 0.5);
+        }
+    }
+
+    #[test]
+    fn larger_curves_get_more_points() {
+        for curve in test_curves() {
+            let small = curve_points(&curve, Point::ORIGIN, 20.0, 0.5).len();
+            let large = curve_points(&curve, Point::ORIGIN, 1000.0, 0.5).len();
+            assert!(large > small, "{curve:?}: {small} vs {large}");
+        }
+    }
+
+    #[test]
+    fn curves_are_closed_after_their_period() {
+        let curves = [
+            // gcd(6, 4) = 2: two turns like 3 and 2
+            Curve::Hypotrochoid {
+                fixed: 6,
+                rolling: 4,
+                pen: 0.7,
+            },
+            Curve::Epitrochoid {
+                fixed: 9,
+                rolling: 6,
+                pen: 0.5,
+            },
+            // 4/6 = 2/3: three turns
+            Curve::Rose {
+                petals: 4,
+                divisor: 6,
+            },
+            // 3/9 = 1/3, both odd: closed after half of the three turns
+            Curve::Rose {
+                petals: 3,
+                divisor: 9,
+            },
+        ];
+        for curve in curves.into_iter().chain(test_curves()) {
+            let period = curve.period();
+            let (start, end) = (curve.unit_point(0.0), curve.unit_point(period));
+            assert!(
+                start.distance(end) <= 1e-3 * curve.extent(),
+                "{curve:?}: {start:?} != {end:?} after {period}"
+            );
+        }
+        // a common factor doesn't add turns
+        let period = |fixed, rolling| {
+            Curve::Hypotrochoid {
+                fixed,
+                rolling,
+                pen: 0.5,
+            }
+            .period()
+        };
+        assert_eq!(period(6, 4), period(3, 2));
+        assert_eq!(period(6, 4), 4.0 * PI);
+        let rose = |petals, divisor| Curve::Rose { petals, divisor }.period();
+        assert_eq!(rose(4, 6), 6.0 * PI);
+        assert_eq!(rose(3, 9), 3.0 * PI);
+        // the curve isn't closed a turn earlier
+        let curve = Curve::Hypotrochoid {
+            fixed: 6,
+            rolling: 4,
+            pen: 0.7,
+        };
+        assert!(curve.unit_point(0.0).distance(curve.unit_point(2.0 * PI)) > 0.1);
+    }
+}
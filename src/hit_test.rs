@@ -0,0 +1,566 @@
+/*!
+Hit-testing for the shapes of the playground.
+
+All functions work in one coordinate system (usually world coordinates) and take a `tolerance`
+in the same units: a point hits a shape if it is at most `tolerance` away from it. Tolerances
+are usually given in screen pixels, use `tolerance_for_zoom` to convert them into world units so
+that grabbing a shape feels the same at every zoom level.
+
+Strokes are centered on the outline, so a stroke of width `w` extends `w / 2` to both sides.
+Rotations are given in degrees (like `rotate_point`) and are applied around the center of the
+shape.
+*/
+
+use iced::{Point, Size, Vector};
+
+use crate::bezier::{flatten_cubic, flatten_quadratic};
+use crate::rotate_point;
+
+/// How the inside of a self-intersecting polygon is determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// A point is inside if a ray from it crosses the outline an odd number of times.
+    #[default]
+    EvenOdd,
+    /// A point is inside if the outline winds around it at least once.
+    NonZero,
+}
+
+/// Converts a tolerance in screen pixels into world units for the given zoom factor.
+pub fn tolerance_for_zoom(pixels: f32, scale: f32) -> f32 {
+    pixels / scale.max(f32::EPSILON)
+}
+
+/// Distance of `p` to the line segment from `a` to `b`.
+pub fn distance_to_segment(p: Point, a: Point, b: Point) -> f32 {
+    let ab = b - a;
+    let ap = p - a;
+    let length_squared = ab.x * ab.x + ab.y * ab.y;
+    if length_squared <= f32::EPSILON {
+        return p.distance(a);
+    }
+    // project p onto the segment and clamp to its ends
+    let t = ((ap.x * ab.x + ap.y * ab.y) / length_squared).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+/// Returns true if `p` hits a line segment of the given stroke width (with round caps).
+pub fn hit_segment(p: Point, a: Point, b: Point, width: f32, tolerance: f32) -> bool {
+    distance_to_segment(p, a, b) <= width / 2.0 + tolerance
+}
+
+/// Distance of `p` to an open polyline. Returns infinity for an empty polyline.
+pub fn distance_to_polyline(p: Point, points: &[Point]) -> f32 {
+    match points {
+        [] => f32::INFINITY,
+        [single] => p.distance(*single),
+        _ => points
+            .windows(2)
+            .map(|w| distance_to_segment(p, w[0], w[1]))
+            .fold(f32::INFINITY, f32::min),
+    }
+}
+
+/// Returns true if `p` hits an open polyline of the given stroke width.
+pub fn hit_polyline(p: Point, points: &[Point], width: f32, tolerance: f32) -> bool {
+    let reach = width / 2.0 + tolerance;
+    if !bounding_box_reaches(p, points, reach) {
+        return false;
+    }
+    distance_to_polyline(p, points) <= reach
+}
+
+/// Returns true if `p` hits a quadratic Bézier curve of the given stroke width.
+pub fn hit_quadratic(p: Point, p0: Point, c: Point, p1: Point, width: f32, tolerance: f32) -> bool {
+    // the curve lies inside the convex hull of its control points
+    let reach = width / 2.0 + tolerance;
+    if !bounding_box_reaches(p, &[p0, c, p1], reach) {
+        return false;
+    }
+    let points = flatten_quadratic(p0, c, p1, flattening_tolerance(tolerance));
+    distance_to_polyline(p, &points) <= reach
+}
+
+/// Returns true if `p` hits a cubic Bézier curve of the given stroke width.
+pub fn hit_cubic(
+    p: Point,
+    p0: Point,
+    c0: Point,
+    c1: Point,
+    p1: Point,
+    width: f32,
+    tolerance: f32,
+) -> bool {
+    let reach = width / 2.0 + tolerance;
+    if !bounding_box_reaches(p, &[p0, c0, c1, p1], reach) {
+        return false;
+    }
+    let points = flatten_cubic(p0, c0, c1, p1, flattening_tolerance(tolerance));
+    distance_to_polyline(p, &points) <= reach
+}
+
+/// Returns true if `p` lies inside the (rotated) ellipse.
+pub fn point_in_ellipse(p: Point, center: Point, radii: Vector, rotation_degrees: f32) -> bool {
+    let local = rotate_point(&p, &center, -rotation_degrees) - center;
+    let (rx, ry) = (radii.x.abs(), radii.y.abs());
+    if rx <= f32::EPSILON || ry <= f32::EPSILON {
+        return false;
+    }
+    (local.x / rx).powi(2) + (local.y / ry).powi(2) <= 1.0
+}
+
+/// Distance of `p` to the outline of the (rotated) ellipse.
+pub fn distance_to_ellipse(p: Point, center: Point, radii: Vector, rotation_degrees: f32) -> f32 {
+    let local = rotate_point(&p, &center, -rotation_degrees) - center;
+    let closest = closest_point_on_ellipse(local, radii.x.abs(), radii.y.abs());
+    Point::new(local.x, local.y).distance(closest)
+}
+
+/// Returns true if `p` hits the (rotated) ellipse. If `filled` is false only the outline with
+/// the given stroke width counts.
+pub fn hit_ellipse(
+    p: Point,
+    center: Point,
+    radii: Vector,
+    rotation_degrees: f32,
+    stroke_width: f32,
+    filled: bool,
+    tolerance: f32,
+) -> bool {
+    if filled && point_in_ellipse(p, center, radii, rotation_degrees) {
+        return true;
+    }
+    distance_to_ellipse(p, center, radii, rotation_degrees) <= stroke_width / 2.0 + tolerance
+}
+
+/// Returns true if `p` lies inside the filled rectangle of the given size, centered at `center`
+/// and rotated around it.
+pub fn hit_rotated_rectangle(
+    p: Point,
+    center: Point,
+    size: Size,
+    rotation_degrees: f32,
+    tolerance: f32,
+) -> bool {
+    let local = rotate_point(&p, &center, -rotation_degrees) - center;
+    local.x.abs() <= size.width / 2.0 + tolerance && local.y.abs() <= size.height / 2.0 + tolerance
+}
+
+/// Returns the corner of the rotated rectangle hit by `p` (0=upper left, 1=upper right,
+/// 2=lower right, 3=lower left, before rotation) or None.
+pub fn hit_rotated_rectangle_corner(
+    p: Point,
+    center: Point,
+    size: Size,
+    rotation_degrees: f32,
+    tolerance: f32,
+) -> Option<usize> {
+    let corners = crate::rotate_rectangle_corners_at_center(
+        &center,
+        size.width,
+        size.height,
+        rotation_degrees,
+    );
+    hit_corner(p, &corners, tolerance)
+}
+
+/// Returns the index of the first point within `tolerance` of `p`.
+pub fn hit_corner(p: Point, corners: &[Point], tolerance: f32) -> Option<usize> {
+    corners
+        .iter()
+        .position(|corner| p.distance(*corner) <= tolerance)
+}
+
+/// Winding number of the closed polygon around `p`. Positive for counter clockwise
+/// (in y-up coordinates) windings.
+pub fn winding_number(p: Point, polygon: &[Point]) -> i32 {
+    let mut winding = 0;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        // which side of the edge a->b is p on
+        let side = (b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y);
+        if a.y <= p.y {
+            if b.y > p.y && side > 0.0 {
+                winding += 1;
+            }
+        } else if b.y <= p.y && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+/// Returns true if `p` lies inside the closed polygon according to the fill rule.
+pub fn point_in_polygon(p: Point, polygon: &[Point], fill_rule: FillRule) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+    match fill_rule {
+        FillRule::NonZero => winding_number(p, polygon) != 0,
+        FillRule::EvenOdd => {
+            let mut inside = false;
+            for (i, a) in polygon.iter().enumerate() {
+                let b = polygon[(i + 1) % polygon.len()];
+                if (a.y > p.y) != (b.y > p.y) {
+                    let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                    if p.x < x {
+                        inside = !inside;
+                    }
+                }
+            }
+            inside
+        }
+    }
+}
+
+/// Distance of `p` to the outline of a closed polygon.
+pub fn distance_to_polygon_outline(p: Point, polygon: &[Point]) -> f32 {
+    polygon
+        .iter()
+        .enumerate()
+        .map(|(i, a)| distance_to_segment(p, *a, polygon[(i + 1) % polygon.len()]))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Returns true if `p` hits the filled polygon (according to the fill rule) or its outline of the
+/// given stroke width.
+pub fn hit_polygon(
+    p: Point,
+    polygon: &[Point],
+    fill_rule: FillRule,
+    stroke_width: f32,
+    tolerance: f32,
+) -> bool {
+    let reach = stroke_width / 2.0 + tolerance;
+    if !bounding_box_reaches(p, polygon, reach) {
+        return false;
+    }
+    point_in_polygon(p, polygon, fill_rule) || distance_to_polygon_outline(p, polygon) <= reach
+}
+
+/// Quick rejection: returns false if `p` is further than `reach` outside the bounding box of
+/// the points.
+fn bounding_box_reaches(p: Point, points: &[Point], reach: f32) -> bool {
+    let Some(first) = points.first() else {
+        return false;
+    };
+    let (mut min, mut max) = (*first, *first);
+    for point in points {
+        min = Point::new(min.x.min(point.x), min.y.min(point.y));
+        max = Point::new(max.x.max(point.x), max.y.max(point.y));
+    }
+    p.x >= min.x - reach && p.x <= max.x + reach && p.y >= min.y - reach && p.y <= max.y + reach
+}
+
+// Curves are flattened a bit finer than the tolerance, so the flattening error doesn't eat it up
+fn flattening_tolerance(tolerance: f32) -> f32 {
+    (tolerance / 4.0).max(0.01)
+}
+
+/// Closest point on the outline of an axis aligned ellipse centered at the origin.
+///
+/// Uses a few iterations of the trig-free method that walks along the evolute of the
+/// ellipse. Three iterations are plenty for hit-testing.
+fn closest_point_on_ellipse(p: Vector, a: f32, b: f32) -> Point {
+    if a <= f32::EPSILON || b <= f32::EPSILON {
+        // degenerated to a line segment
+        let half = Vector::new(a, b);
+        let closest = Point::new(p.x.clamp(-half.x, half.x), p.y.clamp(-half.y, half.y));
+        return closest;
+    }
+
+    let (px, py) = (p.x.abs(), p.y.abs());
+    let (mut tx, mut ty) = (
+        std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+    );
+
+    for _ in 0..3 {
+        let x = a * tx;
+        let y = b * ty;
+
+        // center of curvature of the current point
+        let ex = (a * a - b * b) * tx.powi(3) / a;
+        let ey = (b * b - a * a) * ty.powi(3) / b;
+
+        let (rx, ry) = (x - ex, y - ey);
+        let (qx, qy) = (px - ex, py - ey);
+        let r = rx.hypot(ry);
+        let q = qx.hypot(qy).max(f32::EPSILON);
+
+        tx = ((qx * r / q + ex) / a).clamp(0.0, 1.0);
+        ty = ((qy * r / q + ey) / b).clamp(0.0, 1.0);
+        let t = tx.hypot(ty).max(f32::EPSILON);
+        tx /= t;
+        ty /= t;
+    }
+
+    Point::new((a * tx).copysign(p.x), (b * ty).copysign(p.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Five-pointed star drawn in one stroke. The pentagon in the middle is wound around twice.
+    fn pentagram() -> Vec<Point> {
+        (0..5)
+            .map(|i| {
+                let angle = (i as f32 * 144.0 - 90.0).to_radians();
+                Point::new(100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect()
+    }
+
+    /// Distance to a curve, measured on a densely sampled copy of it.
+    fn sampled_distance(p: Point, curve: impl Fn(f32) -> Point) -> f32 {
+        (0..=10_000)
+            .map(|i| p.distance(curve(i as f32 / 10_000.0)))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    #[test]
+    fn distance_to_segment_projects_and_clamps() {
+        let (a, b) = (Point::new(0.0, 0.0), Point::new(100.0, 0.0));
+        assert_eq!(distance_to_segment(Point::new(50.0, 5.0), a, b), 5.0);
+        assert_eq!(distance_to_segment(Point::new(50.0, -5.0), a, b), 5.0);
+        // beyond the ends the distance is measured to the end points
+        assert_eq!(distance_to_segment(Point::new(-3.0, 4.0), a, b), 5.0);
+        assert_eq!(distance_to_segment(Point::new(103.0, -4.0), a, b), 5.0);
+        // a segment without length is a point
+        assert_eq!(distance_to_segment(Point::new(3.0, 4.0), a, a), 5.0);
+    }
+
+    #[test]
+    fn segment_width_extends_to_both_sides() {
+        let (a, b) = (Point::new(10.0, 10.0), Point::new(90.0, 90.0));
+        // 5 units from the center line, the stroke reaches 4 units to each side
+        let offset = Vector::new(5.0, -5.0) * (1.0 / 2.0_f32.sqrt());
+        for p in [
+            Point::new(50.0, 50.0) + offset,
+            Point::new(50.0, 50.0) - offset,
+        ] {
+            assert!(!hit_segment(p, a, b, 8.0, 0.5));
+            assert!(hit_segment(p, a, b, 8.0, 1.0));
+            assert!(hit_segment(p, a, b, 10.0, 0.0));
+        }
+        // round caps
+        assert!(hit_segment(Point::new(7.0, 7.0), a, b, 10.0, 0.0));
+        assert!(!hit_segment(Point::new(5.0, 5.0), a, b, 10.0, 0.0));
+    }
+
+    #[test]
+    fn polyline_hits_every_segment_but_not_the_closing_one() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(100.0, 0.0),
+            Point::new(100.0, 100.0),
+        ];
+        assert!(hit_polyline(Point::new(50.0, 2.0), &points, 2.0, 1.0));
+        assert!(hit_polyline(Point::new(102.0, 50.0), &points, 2.0, 1.0));
+        assert!(!hit_polyline(Point::new(104.5, 50.0), &points, 2.0, 1.0));
+        // the polyline is open, its inside and the missing diagonal don't count
+        assert!(!hit_polyline(Point::new(50.0, 50.0), &points, 2.0, 1.0));
+        assert!(!hit_polyline(Point::new(50.0, 2.0), &[], 2.0, 1.0));
+        assert!(hit_polyline(Point::new(1.0, 1.0), &points[..1], 2.0, 1.0));
+    }
+
+    #[test]
+    fn quadratic_hits_the_curve_not_the_control_polygon() {
+        let (p0, c, p1) = (
+            Point::new(0.0, 0.0),
+            Point::new(50.0, 100.0),
+            Point::new(100.0, 0.0),
+        );
+        // the apex of the curve is at (50, 50)
+        assert!(hit_quadratic(Point::new(50.0, 50.0), p0, c, p1, 2.0, 0.0));
+        assert!(hit_quadratic(Point::new(50.0, 52.0), p0, c, p1, 2.0, 1.5));
+        assert!(!hit_quadratic(Point::new(50.0, 55.0), p0, c, p1, 2.0, 1.5));
+        assert!(!hit_quadratic(Point::new(50.0, 40.0), p0, c, p1, 2.0, 1.5));
+        assert!(!hit_quadratic(c, p0, c, p1, 2.0, 1.5));
+        assert!(hit_quadratic(Point::new(-1.0, 0.0), p0, c, p1, 2.0, 1.5));
+    }
+
+    #[test]
+    fn cubic_hits_match_a_densely_sampled_curve() {
+        let (p0, c0, c1, p1) = (
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 100.0),
+            Point::new(100.0, 100.0),
+            Point::new(100.0, -50.0),
+        );
+        let (width, tolerance) = (4.0, 2.0);
+        let reach = width / 2.0 + tolerance;
+        let mut hits = 0;
+        for x in (-10..=110).step_by(3) {
+            for y in (-60..=90).step_by(3) {
+                let p = Point::new(x as f32, y as f32);
+                let distance =
+                    sampled_distance(p, |t| crate::bezier::cubic_point(p0, c0, c1, p1, t));
+                // the flattened curve may be off by a fraction of the tolerance
+                if (distance - reach).abs() < tolerance / 4.0 {
+                    continue;
+                }
+                let expected = distance <= reach;
+                assert_eq!(
+                    hit_cubic(p, p0, c0, c1, p1, width, tolerance),
+                    expected,
+                    "{p:?} at distance {distance}"
+                );
+                hits += expected as usize;
+            }
+        }
+        assert!(hits > 20);
+    }
+
+    #[test]
+    fn ellipse_inside_outline_and_rotation() {
+        let center = Point::new(100.0, 50.0);
+        let radii = Vector::new(40.0, 20.0);
+        assert!(point_in_ellipse(
+            Point::new(135.0, 50.0),
+            center,
+            radii,
+            0.0
+        ));
+        assert!(!point_in_ellipse(
+            Point::new(100.0, 75.0),
+            center,
+            radii,
+            0.0
+        ));
+        // turned by 90 degrees the long axis is vertical
+        assert!(!point_in_ellipse(
+            Point::new(135.0, 50.0),
+            center,
+            radii,
+            90.0
+        ));
+        assert!(point_in_ellipse(
+            Point::new(100.0, 85.0),
+            center,
+            radii,
+            90.0
+        ));
+
+        assert!(
+            (distance_to_ellipse(Point::new(150.0, 50.0), center, radii, 0.0) - 10.0).abs() < 0.01
+        );
+        assert!(
+            (distance_to_ellipse(Point::new(100.0, 80.0), center, radii, 0.0) - 10.0).abs() < 0.01
+        );
+        assert!(
+            (distance_to_ellipse(Point::new(100.0, 100.0), center, radii, 90.0) - 10.0).abs()
+                < 0.01
+        );
+        for i in 0..36 {
+            let angle = (i as f32 * 10.0 + 3.0).to_radians();
+            for length in [5.0, 30.0, 60.0] {
+                let p = center + Vector::new(angle.cos(), angle.sin()) * length;
+                let sampled = sampled_distance(p, |t| {
+                    let angle = t * std::f32::consts::TAU;
+                    rotate_point(
+                        &(center + Vector::new(radii.x * angle.cos(), radii.y * angle.sin())),
+                        &center,
+                        30.0,
+                    )
+                });
+                let distance = distance_to_ellipse(p, center, radii, 30.0);
+                assert!(
+                    (distance - sampled).abs() < 0.1,
+                    "{p:?}: {distance} vs {sampled}"
+                );
+            }
+        }
+
+        // an unfilled ellipse is only hit on its outline
+        assert!(!hit_ellipse(center, center, radii, 0.0, 2.0, false, 1.0));
+        assert!(hit_ellipse(center, center, radii, 0.0, 2.0, true, 1.0));
+        assert!(hit_ellipse(
+            Point::new(141.5, 50.0),
+            center,
+            radii,
+            0.0,
+            2.0,
+            false,
+            1.0
+        ));
+        assert!(!hit_ellipse(
+            Point::new(142.5, 50.0),
+            center,
+            radii,
+            0.0,
+            2.0,
+            true,
+            1.0
+        ));
+    }
+
+    #[test]
+    fn rotated_rectangle_follows_the_rotation() {
+        let center = Point::new(20.0, -10.0);
+        let size = Size::new(100.0, 20.0);
+        for angle in [0.0, 30.0, 45.0, 90.0, 200.0, -60.0] {
+            let along = rotate_point(&(center + Vector::new(45.0, 0.0)), &center, angle);
+            let across = rotate_point(&(center + Vector::new(0.0, 15.0)), &center, angle);
+            assert!(
+                hit_rotated_rectangle(along, center, size, angle, 0.0),
+                "{angle}"
+            );
+            assert!(
+                !hit_rotated_rectangle(across, center, size, angle, 0.0),
+                "{angle}"
+            );
+            assert!(
+                hit_rotated_rectangle(across, center, size, angle, 5.0),
+                "{angle}"
+            );
+
+            let lower_right = rotate_point(&(center + Vector::new(49.0, 9.0)), &center, angle);
+            assert_eq!(
+                hit_rotated_rectangle_corner(lower_right, center, size, angle, 2.0),
+                Some(2),
+                "{angle}"
+            );
+            assert_eq!(
+                hit_rotated_rectangle_corner(center, center, size, angle, 2.0),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn fill_rules_differ_on_a_self_intersecting_polygon() {
+        let star = pentagram();
+        let center = Point::ORIGIN;
+        let tip = Point::new(0.0, -80.0);
+        assert_eq!(winding_number(center, &star).abs(), 2);
+        assert!(!point_in_polygon(center, &star, FillRule::EvenOdd));
+        assert!(point_in_polygon(center, &star, FillRule::NonZero));
+        for fill_rule in [FillRule::EvenOdd, FillRule::NonZero] {
+            assert!(point_in_polygon(tip, &star, fill_rule));
+            assert!(!point_in_polygon(Point::new(90.0, 90.0), &star, fill_rule));
+        }
+        // the outline still hits the hole of the even-odd star
+        assert!(!hit_polygon(center, &star, FillRule::EvenOdd, 2.0, 1.0));
+        assert!(hit_polygon(center, &star, FillRule::NonZero, 2.0, 1.0));
+        let on_outline = Point::new(0.0, star[2].y);
+        assert!(hit_polygon(on_outline, &star, FillRule::EvenOdd, 2.0, 1.0));
+    }
+
+    #[test]
+    fn screen_tolerance_is_the_same_at_every_zoom() {
+        let (a, b) = (Point::new(0.0, 0.0), Point::new(100.0, 0.0));
+        for scale in [0.1, 0.25, 1.0, 3.0, 10.0] {
+            let tolerance = tolerance_for_zoom(5.0, scale);
+            // a point 4 and 6 screen pixels away from the (hairline) segment
+            let near = Point::new(50.0, 4.0 / scale);
+            let far = Point::new(50.0, 6.0 / scale);
+            assert!(hit_segment(near, a, b, 0.0, tolerance), "{scale}");
+            assert!(!hit_segment(far, a, b, 0.0, tolerance), "{scale}");
+            assert!(hit_corner(near, &[a, Point::new(50.0, 0.0)], tolerance) == Some(1));
+        }
+        // a zoom of zero must not divide by zero
+        assert!(tolerance_for_zoom(5.0, 0.0).is_finite());
+    }
+}
@@ -0,0 +1,404 @@
+/*!
+Freehand ink: turning recorded mouse samples into smooth strokes of variable width.
+
+Concept:
+- While drawing, the cursor positions are recorded with their time (`InkSample`).
+- The width of the stroke follows the drawing speed like a real pen: slow strokes are thick,
+  fast strokes are thin. Mice have no pressure, so the speed is the only hint we have.
+- The recorded points are simplified with Ramer–Douglas–Peucker, only the simplified points
+  are stored. They are smoothed with Chaikin's corner cutting when the stroke is drawn.
+- The eraser cuts strokes where a circle passes over them.
+*/
+
+use iced::{Point, Vector};
+
+/// A recorded cursor position. `time` is in seconds since the stroke started.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InkSample {
+    pub position: Point,
+    pub time: f32,
+}
+
+/// A point of a stroke with the width of the stroke at that point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InkPoint {
+    pub position: Point,
+    pub width: f32,
+}
+
+/// How the width of a stroke follows the drawing speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PenStyle {
+    /// Width when drawing slowly
+    pub max_width: f32,
+    /// Width when drawing at `fast_speed` or faster
+    pub min_width: f32,
+    /// Speed (units per second) at which the stroke gets `min_width`
+    pub fast_speed: f32,
+    /// Weight of a new speed measurement in the moving average (0..=1). Smaller values give
+    /// smoother width changes.
+    pub smoothing: f32,
+}
+
+impl Default for PenStyle {
+    fn default() -> Self {
+        Self {
+            max_width: 6.0,
+            min_width: 1.5,
+            fast_speed: 1500.0,
+            smoothing: 0.3,
+        }
+    }
+}
+
+/// Computes the stroke width for every sample from the drawing speed.
+pub fn widths_from_speed(samples: &[InkSample], style: &PenStyle) -> Vec<InkPoint> {
+    let mut speed: Option<f32> = None;
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            if i > 0 {
+                let previous = samples[i - 1];
+                let dt = (sample.time - previous.time).max(1e-3);
+                let measured = sample.position.distance(previous.position) / dt;
+                speed = Some(match speed {
+                    Some(speed) => speed + (measured - speed) * style.smoothing,
+                    None => measured,
+                });
+            }
+            let t = (speed.unwrap_or(0.0) / style.fast_speed).clamp(0.0, 1.0);
+            InkPoint {
+                position: sample.position,
+                width: style.max_width + (style.min_width - style.max_width) * t,
+            }
+        })
+        .collect()
+}
+
+/// Ramer–Douglas–Peucker: returns the indices of the points to keep so that the polyline
+/// through them is at most `epsilon` away from the original one. The first and the last point
+/// are always kept.
+pub fn simplify_polyline(points: &[Point], epsilon: f32) -> Vec<usize> {
+    if points.len() < 3 {
+        return (0..points.len()).collect();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    // iterative to avoid deep recursion on long strokes
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let (index, distance) = (first + 1..last)
+            .map(|i| {
+                (
+                    i,
+                    crate::hit_test::distance_to_segment(points[i], points[first], points[last]),
+                )
+            })
+            .fold((first, 0.0), |best, current| {
+                if current.1 > best.1 { current } else { best }
+            });
+        if distance > epsilon {
+            keep[index] = true;
+            ranges.push((first, index));
+            ranges.push((index, last));
+        }
+    }
+    (0..points.len()).filter(|i| keep[*i]).collect()
+}
+
+/// Simplifies a stroke, keeping the widths of the remaining points.
+pub fn simplify(points: &[InkPoint], epsilon: f32) -> Vec<InkPoint> {
+    let positions: Vec<Point> = points.iter().map(|p| p.position).collect();
+    simplify_polyline(&positions, epsilon)
+        .into_iter()
+        .map(|i| points[i])
+        .collect()
+}
+
+/// Chaikin's corner cutting: every iteration replaces each segment by two points at 1/4 and 3/4
+/// of it. The end points stay in place. Widths are interpolated like the positions.
+pub fn smooth(points: &[InkPoint], iterations: usize) -> Vec<InkPoint> {
+    let mut points = points.to_vec();
+    for _ in 0..iterations {
+        if points.len() < 3 {
+            break;
+        }
+        let mut smoothed = Vec::with_capacity(points.len() * 2);
+        smoothed.push(points[0]);
+        for pair in points.windows(2) {
+            smoothed.push(lerp(pair[0], pair[1], 0.25));
+            smoothed.push(lerp(pair[0], pair[1], 0.75));
+        }
+        smoothed.push(points[points.len() - 1]);
+        points = smoothed;
+    }
+    points
+}
+
+/// Returns the outline of the stroke as a closed polygon: the left side from the start to the
+/// end, then the right side back. The ends are cut off square.
+pub fn outline(points: &[InkPoint]) -> Vec<Point> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+    let normals: Vec<Vector> = (0..points.len())
+        .map(|i| {
+            // direction averaged over the neighbouring segments
+            let before = points[i.saturating_sub(1)].position;
+            let after = points[(i + 1).min(points.len() - 1)].position;
+            let direction = after - before;
+            let length = direction.x.hypot(direction.y).max(f32::EPSILON);
+            Vector::new(-direction.y / length, direction.x / length)
+        })
+        .collect();
+    let left = points
+        .iter()
+        .zip(&normals)
+        .map(|(p, n)| p.position + *n * (p.width / 2.0));
+    let right = points
+        .iter()
+        .zip(&normals)
+        .rev()
+        .map(|(p, n)| p.position - *n * (p.width / 2.0));
+    left.chain(right).collect()
+}
+
+/// Removes the parts of the stroke inside the circle. Returns the remaining pieces, each with
+/// at least two points.
+pub fn erase(points: &[InkPoint], center: Point, radius: f32) -> Vec<Vec<InkPoint>> {
+    let mut pieces = Vec::new();
+    let mut current: Vec<InkPoint> = Vec::new();
+    let inside = |p: &InkPoint| p.position.distance(center) < radius;
+
+    if let Some(first) = points.first()
+        && !inside(first)
+    {
+        current.push(*first);
+    }
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        // parameters where the segment enters and leaves the circle
+        if let Some((t0, t1)) = circle_intersection(a.position, b.position, center, radius) {
+            if t0 > 0.0 && !current.is_empty() {
+                current.push(lerp(a, b, t0));
+            }
+            if current.len() >= 2 {
+                pieces.push(std::mem::take(&mut current));
+            }
+            current.clear();
+            if t1 < 1.0 {
+                current.push(lerp(a, b, t1));
+            }
+        }
+        if !inside(&b) && !current.is_empty() {
+            current.push(b);
+        }
+    }
+    if current.len() >= 2 {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Returns the parameters (clamped to 0..=1) where the segment from `a` to `b` is inside the
+/// circle, or None if it doesn't touch the circle.
+fn circle_intersection(a: Point, b: Point, center: Point, radius: f32) -> Option<(f32, f32)> {
+    let d = b - a;
+    let f = a - center;
+    let qa = d.x * d.x + d.y * d.y;
+    let qb = 2.0 * (f.x * d.x + f.y * d.y);
+    let qc = f.x * f.x + f.y * f.y - radius * radius;
+    if qa <= f32::EPSILON {
+        return (qc < 0.0).then_some((0.0, 1.0));
+    }
+    let discriminant = qb * qb - 4.0 * qa * qc;
+    if discriminant <= 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let t0 = (-qb - root) / (2.0 * qa);
+    let t1 = (-qb + root) / (2.0 * qa);
+    if t1 <= 0.0 || t0 >= 1.0 {
+        return None;
+    }
+    Some((t0.max(0.0), t1.min(1.0)))
+}
+
+fn lerp(a: InkPoint, b: InkPoint, t: f32) -> InkPoint {
+    InkPoint {
+        position: Point::new(
+            a.position.x + (b.position.x - a.position.x) * t,
+            a.position.y + (b.position.y - a.position.y) * t,
+        ),
+        width: a.width + (b.width - a.width) * t,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit_test::distance_to_segment;
+    use crate::random::Random;
+
+    fn ink(points: &[Point]) -> Vec<InkPoint> {
+        points
+            .iter()
+            .map(|p| InkPoint {
+                position: *p,
+                width: 2.0,
+            })
+            .collect()
+    }
+
+    /// A jittery stroke, like one drawn with a mouse.
+    fn scribble(random: &mut Random, count: usize) -> Vec<Point> {
+        let mut position = Point::ORIGIN;
+        (0..count)
+            .map(|_| {
+                position = position + random.vector(10.0);
+                position
+            })
+            .collect()
+    }
+
+    #[test]
+    fn simplification_stays_within_the_tolerance() {
+        let mut random = Random(21);
+        for epsilon in [0.5, 2.0, 10.0] {
+            let points = scribble(&mut random, 300);
+            let kept = simplify_polyline(&points, epsilon);
+            assert!(kept.len() < points.len(), "nothing removed at {epsilon}");
+            for pair in kept.windows(2) {
+                let (first, last) = (pair[0], pair[1]);
+                for point in &points[first..=last] {
+                    let distance = distance_to_segment(*point, points[first], points[last]);
+                    assert!(distance <= epsilon, "{distance} > {epsilon}");
+                }
+            }
+        }
+        // a corner sticking out further than the tolerance is kept, points on a line are not
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(5.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(15.0, 3.0),
+            Point::new(20.0, 0.0),
+        ];
+        assert_eq!(simplify_polyline(&points, 1.0), vec![0, 2, 3, 4]);
+        assert_eq!(simplify_polyline(&points, 5.0), vec![0, 4]);
+    }
+
+    #[test]
+    fn simplification_keeps_the_first_and_the_last_point() {
+        let mut random = Random(22);
+        for count in 0..20 {
+            let points = scribble(&mut random, count);
+            let kept = simplify_polyline(&points, 1000.0);
+            assert_eq!(kept.first(), (count > 0).then_some(&0));
+            assert_eq!(kept.last(), count.checked_sub(1).as_ref());
+        }
+        // a closed loop: first and last point are the same
+        let mut loop_points = scribble(&mut random, 50);
+        loop_points.push(loop_points[0]);
+        let kept = simplify(&ink(&loop_points), 3.0);
+        assert_eq!(kept.first().map(|p| p.position), Some(loop_points[0]));
+        assert_eq!(kept.last().map(|p| p.position), Some(loop_points[0]));
+        assert!(kept.len() > 2);
+    }
+
+    #[test]
+    fn smoothing_keeps_the_ends_and_cuts_the_corners() {
+        let points = ink(&[
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+        ]);
+        let smoothed = smooth(&points, 2);
+        assert_eq!(smoothed.first(), points.first());
+        assert_eq!(smoothed.last(), points.last());
+        assert!(!smoothed.contains(&points[1]), "the corner is cut");
+        assert!(
+            smoothed
+                .iter()
+                .all(|p| (0.0..=10.0).contains(&p.position.x)
+                    && (0.0..=10.0).contains(&p.position.y))
+        );
+    }
+
+    #[test]
+    fn width_shrinks_with_speed_within_the_limits() {
+        let style = PenStyle::default();
+        // the width after drawing a while at a constant speed
+        let width_at = |speed: f32| {
+            let samples: Vec<InkSample> = (0..30)
+                .map(|i| InkSample {
+                    position: Point::new(speed * i as f32 * 0.01, 0.0),
+                    time: i as f32 * 0.01,
+                })
+                .collect();
+            widths_from_speed(&samples, &style)
+                .last()
+                .map(|point| point.width)
+                .expect("a width")
+        };
+        let widths: Vec<f32> = (0..=40).map(|i| width_at(i as f32 * 100.0)).collect();
+        assert_eq!(widths[0], style.max_width);
+        for pair in widths.windows(2) {
+            assert!(pair[1] <= pair[0], "{widths:?}");
+        }
+        assert_eq!(width_at(style.fast_speed * 2.0), style.min_width);
+        assert_eq!(width_at(1.0e9), style.min_width);
+
+        // samples without time in between don't divide by zero
+        let same_time = [
+            InkSample {
+                position: Point::ORIGIN,
+                time: 0.0,
+            },
+            InkSample {
+                position: Point::new(100.0, 0.0),
+                time: 0.0,
+            },
+        ];
+        for point in widths_from_speed(&same_time, &style) {
+            assert!((style.min_width..=style.max_width).contains(&point.width));
+        }
+    }
+
+    #[test]
+    fn erasing_the_middle_splits_the_stroke() {
+        let points: Vec<InkPoint> = (0..=10)
+            .map(|i| InkPoint {
+                position: Point::new(i as f32 * 10.0, 0.0),
+                width: i as f32,
+            })
+            .collect();
+        let pieces = erase(&points, Point::new(50.0, 0.0), 15.0);
+        assert_eq!(pieces.len(), 2, "{pieces:?}");
+        let (first, second) = (&pieces[0], &pieces[1]);
+        assert_eq!(first[0], points[0]);
+        assert_eq!(second.last(), points.last());
+        // the pieces end on the circle, with interpolated widths
+        let cut = first.last().expect("a point");
+        assert!((cut.position.x - 35.0).abs() < 1e-3 && (cut.width - 3.5).abs() < 1e-3);
+        let cut = second[0];
+        assert!((cut.position.x - 65.0).abs() < 1e-3 && (cut.width - 6.5).abs() < 1e-3);
+
+        // erasing an end shortens the stroke, erasing everything removes it
+        let pieces = erase(&points, Point::new(100.0, 0.0), 15.0);
+        assert_eq!(pieces.len(), 1);
+        assert!((pieces[0].last().expect("a point").position.x - 85.0).abs() < 1e-3);
+        assert_eq!(
+            erase(&points, Point::new(50.0, 0.0), 100.0),
+            Vec::<Vec<InkPoint>>::new()
+        );
+        // a circle next to the stroke doesn't touch it
+        assert_eq!(
+            erase(&points, Point::new(50.0, 20.0), 15.0),
+            vec![points.clone()]
+        );
+    }
+}
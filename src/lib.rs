@@ -1,84 +1,133 @@
-use iced::{Point, Rectangle, widget::canvas::Frame};
+pub mod affine;
+pub mod bezier;
+pub mod boolean_ops;
+pub mod curves;
+pub mod hit_test;
+pub mod ink;
+pub mod polygon;
+pub mod random;
+pub mod recognize;
+pub mod spatial_index;
+pub mod theme;
+pub mod vector_path;
+pub mod viewport;
+
+use affine::Affine2;
+use iced::{Point, Rectangle, Vector, mouse, widget::canvas::Frame};
 
 // Rotate a point around a center by a given angle in degrees 0 - 359
 #[allow(dead_code)]
-pub fn rotate_point(point: &Point, center: &Point, angle_degrees: &f32) -> Point {
-    let angle_radians = angle_degrees.to_radians();
-    let sin_angle = angle_radians.sin();
-    let cos_angle = angle_radians.cos();
-
-    let translated_x = point.x - center.x;
-    let translated_y = point.y - center.y;
+pub fn rotate_point(point: &Point, center: &Point, angle_degrees: f32) -> Point {
+    Affine2::rotate_about(*center, angle_degrees).apply(*point)
+}
 
-    let rotated_x = translated_x * cos_angle - translated_y * sin_angle;
-    let rotated_y = translated_x * sin_angle + translated_y * cos_angle;
+/// Converts hue (0 - 360), saturation and brightness (0 - 100) to red, green and blue (0 - 1).
+pub fn hsb_to_rgb(h: f32, s: f32, b: f32) -> (f32, f32, f32) {
+    let h = h / 360.0;
+    let s = s / 100.0;
+    let b = b / 100.0;
+
+    let i = (h * 6.0).floor();
+    let f = h * 6.0 - i;
+    let p = b * (1.0 - s);
+    let q = b * (1.0 - f * s);
+    let t = b * (1.0 - (1.0 - f) * s);
+
+    match (i % 6.0) as i32 {
+        0 => (b, t, p),
+        1 => (q, b, p),
+        2 => (p, b, t),
+        3 => (p, q, b),
+        4 => (t, p, b),
+        _ => (b, p, q),
+    }
+}
 
-    Point::new(rotated_x + center.x, rotated_y + center.y)
+/// Returns the corners of a line of the given width from `line_start` to `line_end`, seen in the
+/// direction of the line: 0=upper left, 1=upper right, 2=lower right, 3=lower left. For a
+/// horizontal line from left to right these are the usual corners.
+pub fn line_corners(line_start: &Point, line_end: &Point, width: f32) -> [Point; 4] {
+    let direction = *line_end - *line_start;
+    let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+    // a line without length is treated as a horizontal one
+    let (dx, dy) = if length > f32::EPSILON {
+        (direction.x / length, direction.y / length)
+    } else {
+        (1.0, 0.0)
+    };
+    // points from the upper to the lower side
+    let down = Vector::new(-dy * width / 2.0, dx * width / 2.0);
+    [
+        *line_start - down,
+        *line_end - down,
+        *line_end + down,
+        *line_start + down,
+    ]
 }
 
-/**
- * This function checks if the given point is on the line defined by line_start and line_end within the given tolerance.
- * Note that this only works for horizontal lines.
- */
-pub fn is_point_on_horizontal_line(
+/// Checks if the given point lies on the line of the given width, at any angle. Points up to
+/// `tolerance` outside the line count as well.
+pub fn is_point_on_line(
     pt: &Point,
     line_start: &Point,
     line_end: &Point,
+    width: f32,
     tolerance: f32,
 ) -> bool {
-    if (line_start.y - line_end.y).abs() > 0.001 {
-        println!("is_point_on_line: only horizontal lines are supported currently.");
-    }
-
-    if pt.x < line_start.x.min(line_end.x) || pt.x > line_start.x.max(line_end.x) {
-        return false;
-    }
-    if pt.y < line_start.y.min(line_end.y) - tolerance
-        || pt.y > line_start.y.max(line_end.y) + tolerance
-    {
-        return false;
-    }
-    true
+    hit_test::hit_polygon(
+        *pt,
+        &line_corners(line_start, line_end, width),
+        hit_test::FillRule::NonZero,
+        0.0,
+        tolerance,
+    )
 }
 
 /**
- * This function finds out if the given point is on a corner of the line. It returns the number of the corner
- * (0=upper left, 1=upper right, 2=lower right, 3=lower left) or None if the point is not on a corner.
+ * This function finds out if the given point is on a corner of the line, at any angle. It returns the number of the
+ * corner (see `line_corners`) or None if the point is farther than `tolerance` from every corner.
  */
 pub fn is_point_on_line_corner(
     pt: &Point,
     line_start: &Point,
     line_end: &Point,
     width: f32,
+    tolerance: f32,
 ) -> Option<usize> {
-    let w_half = width / 2.0;
-    let corners = [
-        Point::new(line_start.x, line_start.y - w_half), // upper left
-        Point::new(line_end.x, line_end.y - w_half),     // upper right
-        Point::new(line_end.x, line_end.y + w_half),     // lower right
-        Point::new(line_start.x, line_start.y + w_half), // lower left
-    ];
-
-    for (i, corner) in corners.iter().enumerate() {
-        if (pt.x - corner.x).abs() <= 0.01 && (pt.y - corner.y).abs() <= 0.01 {
-            return Some(i);
-        }
+    hit_test::hit_corner(*pt, &line_corners(line_start, line_end, width), tolerance)
+}
+
+/// The resize cursor closest to the given direction (screen coordinates, y pointing down).
+/// Opposite directions give the same cursor.
+pub fn resize_interaction(direction: Vector) -> mouse::Interaction {
+    // angle in degrees, counter clockwise on the screen, folded into 0 - 180
+    let angle = (-direction.y)
+        .atan2(direction.x)
+        .to_degrees()
+        .rem_euclid(180.0);
+    if !(22.5..157.5).contains(&angle) {
+        mouse::Interaction::ResizingHorizontally
+    } else if angle < 67.5 {
+        mouse::Interaction::ResizingDiagonallyUp
+    } else if angle < 112.5 {
+        mouse::Interaction::ResizingVertically
+    } else {
+        mouse::Interaction::ResizingDiagonallyDown
     }
-    None
+}
+
+/// Transform from relative frame coordinates (0.0 - 1.0) to absolute frame coordinates.
+pub fn rel_to_abs(frame: &Frame) -> Affine2 {
+    Affine2::scale_xy(frame.width(), frame.height())
 }
 
 pub fn rel_to_abs_pt(frame: &Frame, rel_point: &Point) -> Point {
-    Point::new(rel_point.x * frame.width(), rel_point.y * frame.height())
+    rel_to_abs(frame).apply(*rel_point)
 }
 
 #[allow(dead_code)]
 pub fn rel_to_abs_rct(frame: &Frame, rel_rect: &Rectangle) -> Rectangle {
-    Rectangle {
-        x: rel_rect.x * frame.width(),
-        y: rel_rect.y * frame.height(),
-        width: rel_rect.width * frame.width(),
-        height: rel_rect.height * frame.height(),
-    }
+    rel_to_abs(frame).bounding_rectangle(rel_rect)
 }
 
 #[allow(dead_code)]
@@ -88,12 +137,12 @@ pub fn rotate_line(
     center: &Point,
     angle_degrees: f32,
 ) -> (Point, Point) {
-    (
-        rotate_point(start, center, &angle_degrees),
-        rotate_point(end, center, &angle_degrees),
-    )
+    let rotation = Affine2::rotate_about(*center, angle_degrees);
+    (rotation.apply(*start), rotation.apply(*end))
 }
 
+/// Returns the corners (upper left, upper right, lower right, lower left) of a rectangle of the
+/// given size centered at `center` and rotated around it.
 #[allow(dead_code)]
 pub fn rotate_rectangle_corners_at_center(
     center: &Point,
@@ -101,36 +150,67 @@ pub fn rotate_rectangle_corners_at_center(
     height: f32,
     angle_degrees: f32,
 ) -> [Point; 4] {
-    let half_width = width / 2.0;
-    let half_height = height / 2.0;
-
-    let top_left = Point::new(center.x - half_width, center.y - half_height);
-    let top_right = Point::new(center.x + half_width, center.y - half_height);
-    let bottom_right = Point::new(center.x + half_width, center.y + half_height);
-    let bottom_left = Point::new(center.x - half_width, center.y + half_height);
-
-    [
-        rotate_point(&top_left, center, &angle_degrees),
-        rotate_point(&top_right, center, &angle_degrees),
-        rotate_point(&bottom_right, center, &angle_degrees),
-        rotate_point(&bottom_left, center, &angle_degrees),
-    ]
+    let rectangle = Rectangle::new(
+        Point::new(center.x - width / 2.0, center.y - height / 2.0),
+        iced::Size::new(width, height),
+    );
+    rotate_rectangle(&rectangle, center, angle_degrees)
 }
 
+/// Returns the corners (upper left, upper right, lower right, lower left) of the rectangle
+/// rotated around `center`.
 #[allow(dead_code)]
 pub fn rotate_rectangle(rectangle: &Rectangle, center: &Point, angle_degrees: f32) -> [Point; 4] {
-    let top_left = Point::new(rectangle.x, rectangle.y);
-    let top_right = Point::new(rectangle.x + rectangle.width, rectangle.y);
-    let bottom_right = Point::new(rectangle.x, rectangle.y + rectangle.height);
-    let bottom_left = Point::new(
-        rectangle.x + rectangle.width,
-        rectangle.y + rectangle.height,
-    );
+    Affine2::rotate_about(*center, angle_degrees).apply_rectangle(rectangle)
+}
 
-    [
-        rotate_point(&top_left, center, &angle_degrees),
-        rotate_point(&top_right, center, &angle_degrees),
-        rotate_point(&bottom_right, center, &angle_degrees),
-        rotate_point(&bottom_left, center, &angle_degrees),
-    ]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_on_line_at_any_angle() {
+        let center = Point::new(50.0, 50.0);
+        for angle in [0.0, 15.0, 45.0, 90.0, 135.0, 180.0, 275.0] {
+            let (start, end) = rotate_line(
+                &Point::new(0.0, 50.0),
+                &Point::new(100.0, 50.0),
+                &center,
+                angle,
+            );
+            let inside = rotate_point(&Point::new(80.0, 54.0), &center, angle);
+            let near = rotate_point(&Point::new(80.0, 57.0), &center, angle);
+            let beyond_end = rotate_point(&Point::new(103.0, 50.0), &center, angle);
+            assert!(
+                is_point_on_line(&inside, &start, &end, 10.0, 0.0),
+                "{angle}"
+            );
+            assert!(!is_point_on_line(&near, &start, &end, 10.0, 0.5), "{angle}");
+            assert!(is_point_on_line(&near, &start, &end, 10.0, 2.5), "{angle}");
+            assert!(
+                !is_point_on_line(&beyond_end, &start, &end, 10.0, 2.5),
+                "{angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn point_on_line_corner_uses_the_given_tolerance() {
+        let (start, end) = (Point::new(0.0, 0.0), Point::new(100.0, 0.0));
+        let corners = line_corners(&start, &end, 10.0);
+        assert_eq!(corners[0], Point::new(0.0, -5.0));
+        assert_eq!(corners[2], Point::new(100.0, 5.0));
+        let near_lower_right = Point::new(103.0, 6.0);
+        assert_eq!(
+            is_point_on_line_corner(&near_lower_right, &start, &end, 10.0, 1.0),
+            None
+        );
+        assert_eq!(
+            is_point_on_line_corner(&near_lower_right, &start, &end, 10.0, 4.0),
+            Some(2)
+        );
+        // a vertical line pointing down has its upper left corner on the right
+        let corners = line_corners(&start, &Point::new(0.0, 100.0), 10.0);
+        assert_eq!(corners[0], Point::new(5.0, 0.0));
+    }
 }
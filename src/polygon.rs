@@ -0,0 +1,327 @@
+/*!
+Regular polygons, star polygons and polygrams as lists of points.
+
+Concept:
+- All kinds are generated from `n` points on a circle, starting at `rotation_offset_degrees`
+  (0 points to the right, angles grow clockwise in screen coordinates).
+- A star alternates between the outer radius and the inner radius, giving `2n` corners.
+- A polygram `{n/k}` connects every k-th point. If n and k have a common divisor d, this gives d
+  separate polygons (e.g. `{6/2}` is two triangles), so the result is a list of closed rings.
+- Rounded corners are replaced by circular arcs, flattened into points. The radius shrinks at
+  corners whose edges are too short for it.
+- Renderers (SVG path data, canvas paths) only connect the points, so they all show exactly the
+  same outline.
+*/
+
+use std::f32::consts::PI;
+
+use iced::widget::canvas::Path;
+use iced::{Point, Vector};
+use serde::{Deserialize, Serialize};
+
+/// Number of segments of a flattened corner arc. The same for every corner, so the number of
+/// points doesn't change while the shape is animated.
+const ARC_SEGMENTS: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolygonKind {
+    /// Convex regular polygon
+    #[default]
+    Regular,
+    /// Star polygon with `n` outer and `n` inner corners
+    Star,
+    /// Polygram `{n/k}`: every k-th point connected
+    Polygram,
+}
+
+impl PolygonKind {
+    pub const ALL: [PolygonKind; 3] = [
+        PolygonKind::Regular,
+        PolygonKind::Star,
+        PolygonKind::Polygram,
+    ];
+}
+
+impl std::fmt::Display for PolygonKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PolygonKind::Regular => "Regular polygon",
+            PolygonKind::Star => "Star",
+            PolygonKind::Polygram => "Polygram",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolygonSpec {
+    pub kind: PolygonKind,
+    /// Number of points (corners of a regular polygon, tips of a star)
+    pub points: u32,
+    pub center: Point,
+    pub radius: f32,
+    /// Star only: inner radius as a fraction of the radius
+    pub inner_ratio: f32,
+    /// Polygram only: the k of `{n/k}`
+    pub step: u32,
+    /// Radius of the rounded corners, 0 for sharp corners
+    pub corner_radius: f32,
+    pub rotation_offset_degrees: f32,
+}
+
+impl Default for PolygonSpec {
+    fn default() -> Self {
+        Self {
+            kind: PolygonKind::Regular,
+            points: 5,
+            center: Point::ORIGIN,
+            radius: 100.0,
+            inner_ratio: 0.5,
+            step: 2,
+            corner_radius: 0.0,
+            rotation_offset_degrees: 0.0,
+        }
+    }
+}
+
+/// Returns the closed rings of the shape. The last point of a ring is not repeated.
+pub fn polygon_points(spec: &PolygonSpec) -> Vec<Vec<Point>> {
+    let n = spec.points.max(3);
+    let start = spec.rotation_offset_degrees.to_radians();
+    let on_circle = |index: f32, count: f32, radius: f32| {
+        let angle = start + index * 2.0 * PI / count;
+        spec.center + Vector::new(radius * angle.cos(), radius * angle.sin())
+    };
+
+    let rings: Vec<Vec<Point>> = match spec.kind {
+        PolygonKind::Regular => {
+            vec![
+                (0..n)
+                    .map(|i| on_circle(i as f32, n as f32, spec.radius))
+                    .collect(),
+            ]
+        }
+        PolygonKind::Star => {
+            let inner = spec.radius * spec.inner_ratio.clamp(0.0, 1.0);
+            vec![
+                (0..2 * n)
+                    .map(|i| {
+                        let radius = if i % 2 == 0 { spec.radius } else { inner };
+                        on_circle(i as f32, 2.0 * n as f32, radius)
+                    })
+                    .collect(),
+            ]
+        }
+        PolygonKind::Polygram => {
+            // {n/k} and {n/(n-k)} are the same figure, k = 1 is the regular polygon
+            let step = spec.step.clamp(1, n / 2);
+            let divisor = gcd(n, step);
+            (0..divisor)
+                .map(|ring| {
+                    (0..n / divisor)
+                        .map(|i| on_circle((ring + i * step) as f32, n as f32, spec.radius))
+                        .collect()
+                })
+                .collect()
+        }
+    };
+
+    if spec.corner_radius > 0.0 {
+        rings
+            .iter()
+            .map(|ring| round_corners(ring, spec.corner_radius))
+            .collect()
+    } else {
+        rings
+    }
+}
+
+/// The rings as SVG path data, one closed subpath per ring.
+pub fn svg_path_data(rings: &[Vec<Point>]) -> String {
+    rings
+        .iter()
+        .map(|ring| {
+            let mut subpath = ring
+                .iter()
+                .enumerate()
+                .map(|(i, point)| {
+                    if i == 0 {
+                        format!("M {:.2} {:.2}", point.x, point.y)
+                    } else {
+                        format!("L {:.2} {:.2}", point.x, point.y)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            subpath.push_str(" Z");
+            subpath
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The rings as one canvas path of closed subpaths.
+pub fn canvas_path(rings: &[Vec<Point>]) -> Path {
+    Path::new(|builder| {
+        for ring in rings {
+            let Some((first, rest)) = ring.split_first() else {
+                continue;
+            };
+            builder.move_to(*first);
+            for point in rest {
+                builder.line_to(*point);
+            }
+            builder.close();
+        }
+    })
+}
+
+pub(crate) fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Replaces every corner of the closed ring by an arc of the given radius. A radius of 0 keeps
+/// the sharp corners.
+pub fn round_corners(ring: &[Point], radius: f32) -> Vec<Point> {
+    if radius <= 0.0 {
+        return ring.to_vec();
+    }
+    let count = ring.len();
+    let mut result = Vec::new();
+    for i in 0..count {
+        let corner = ring[i];
+        let previous = ring[(i + count - 1) % count];
+        let next = ring[(i + 1) % count];
+        let (to_previous, to_next) = (previous - corner, next - corner);
+        let (length_previous, length_next) = (length(to_previous), length(to_next));
+        if length_previous < f32::EPSILON || length_next < f32::EPSILON {
+            result.push(corner);
+            continue;
+        }
+        let u = to_previous * (1.0 / length_previous);
+        let v = to_next * (1.0 / length_next);
+        let half_angle = (u.x * v.x + u.y * v.y).clamp(-1.0, 1.0).acos() / 2.0;
+        if !(f32::EPSILON..=PI / 2.0 - f32::EPSILON).contains(&half_angle) {
+            // no corner (straight line) or a spike that can't be rounded
+            result.push(corner);
+            continue;
+        }
+        // distance from the corner to where the arc touches the edges, at most half an edge
+        let tangent = (radius / half_angle.tan()).min(length_previous.min(length_next) / 2.0);
+        let radius = tangent * half_angle.tan();
+        let bisector = u + v;
+        let center = corner + bisector * (radius / half_angle.sin() / length(bisector));
+        let from = corner + u * tangent;
+        let to = corner + v * tangent;
+
+        let start = (from.y - center.y).atan2(from.x - center.x);
+        let mut sweep = (to.y - center.y).atan2(to.x - center.x) - start;
+        // the arc between the tangent points is always the short way round
+        if sweep > PI {
+            sweep -= 2.0 * PI;
+        } else if sweep < -PI {
+            sweep += 2.0 * PI;
+        }
+        for step in 0..=ARC_SEGMENTS {
+            let angle = start + sweep * step as f32 / ARC_SEGMENTS as f32;
+            result.push(center + Vector::new(radius * angle.cos(), radius * angle.sin()));
+        }
+    }
+    result
+}
+
+fn length(v: Vector) -> f32 {
+    (v.x * v.x + v.y * v.y).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(kind: PolygonKind, points: u32, step: u32) -> PolygonSpec {
+        PolygonSpec {
+            kind,
+            points,
+            step,
+            center: Point::new(10.0, -20.0),
+            radius: 100.0,
+            inner_ratio: 0.4,
+            rotation_offset_degrees: 15.0,
+            ..PolygonSpec::default()
+        }
+    }
+
+    fn assert_radius(point: Point, center: Point, radius: f32) {
+        let distance = point.distance(center);
+        assert!(
+            (distance - radius).abs() < 1e-3,
+            "{point:?}: {distance} != {radius}"
+        );
+    }
+
+    #[test]
+    fn stars_alternate_between_outer_and_inner_radius() {
+        for n in 3..12 {
+            let spec = spec(PolygonKind::Star, n, 2);
+            let rings = polygon_points(&spec);
+            assert_eq!(rings.len(), 1);
+            assert_eq!(rings[0].len(), 2 * n as usize);
+            for (i, point) in rings[0].iter().enumerate() {
+                let radius = if i % 2 == 0 { 100.0 } else { 40.0 };
+                assert_radius(*point, spec.center, radius);
+            }
+        }
+    }
+
+    #[test]
+    fn polygrams_split_into_rings() {
+        let rings = polygon_points(&spec(PolygonKind::Polygram, 6, 2));
+        assert_eq!(rings.iter().map(Vec::len).collect::<Vec<_>>(), vec![3, 3]);
+        // two triangles that together use every point of the hexagon once
+        let hexagon = polygon_points(&spec(PolygonKind::Regular, 6, 1)).remove(0);
+        for point in &hexagon {
+            let uses = rings.iter().flatten().filter(|p| p.distance(*point) < 1e-3);
+            assert_eq!(uses.count(), 1, "{point:?}");
+        }
+
+        let rings = polygon_points(&spec(PolygonKind::Polygram, 5, 2));
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].len(), 5);
+        // the pentagram connects every second point of the pentagon
+        let pentagon = polygon_points(&spec(PolygonKind::Regular, 5, 1)).remove(0);
+        for (i, point) in rings[0].iter().enumerate() {
+            assert!(point.distance(pentagon[i * 2 % 5]) < 1e-3, "{i}");
+        }
+    }
+
+    #[test]
+    fn polygram_step_is_clamped_to_half_the_points() {
+        // {7/5} is drawn as {7/3}, {8/7} as the two squares of {8/4}
+        assert_eq!(
+            polygon_points(&spec(PolygonKind::Polygram, 7, 5)),
+            polygon_points(&spec(PolygonKind::Polygram, 7, 3))
+        );
+        assert_eq!(
+            polygon_points(&spec(PolygonKind::Polygram, 8, 7)),
+            polygon_points(&spec(PolygonKind::Polygram, 8, 4))
+        );
+        // a step of 0 is the regular polygon
+        assert_eq!(
+            polygon_points(&spec(PolygonKind::Polygram, 6, 0)),
+            polygon_points(&spec(PolygonKind::Regular, 6, 0))
+        );
+    }
+
+    #[test]
+    fn rounding_with_radius_zero_keeps_the_corners() {
+        let ring = polygon_points(&spec(PolygonKind::Star, 5, 2)).remove(0);
+        assert_eq!(round_corners(&ring, 0.0), ring);
+
+        // a real radius replaces each corner by an arc that starts away from the corner
+        let rounded = round_corners(&ring, 5.0);
+        assert_eq!(rounded.len(), ring.len() * (ARC_SEGMENTS + 1));
+        for point in rounded.iter().step_by(ARC_SEGMENTS + 1) {
+            assert!(!ring.contains(point), "{point:?}");
+        }
+    }
+}
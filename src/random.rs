@@ -0,0 +1,37 @@
+/*!
+Tiny deterministic random numbers (a linear congruential generator) for tests and benchmarks.
+
+The same seed always gives the same numbers, so that failing tests and measurements can be
+reproduced. Not suitable for anything else.
+*/
+
+use iced::{Point, Vector};
+
+#[derive(Debug, Clone)]
+pub struct Random(pub u64);
+
+impl Random {
+    /// Returns a number in 0.0..1.0
+    pub fn fraction(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a number in min..max
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + self.fraction() * (max - min)
+    }
+
+    /// Returns a point with both coordinates in -max..max
+    pub fn point(&mut self, max: f32) -> Point {
+        Point::new(self.range(-max, max), self.range(-max, max))
+    }
+
+    /// Returns a vector with both components in -max..max
+    pub fn vector(&mut self, max: f32) -> Vector {
+        Vector::new(self.range(-max, max), self.range(-max, max))
+    }
+}
@@ -0,0 +1,484 @@
+/*!
+Recognition of rough freehand sketches: lines, arrows, triangles, rectangles and ellipses.
+
+Concept:
+- The stroke is resampled at equal distances along its length first, so slow and fast parts of
+  the stroke count the same.
+- Open strokes can be lines or arrows. The stroke is simplified (Ramer–Douglas–Peucker) and the
+  corners are compared with the expected pattern: a line has no corners, an arrow is a long
+  shaft followed by short barbs near the tip.
+- Closed strokes (end close to the start) are fitted with a triangle, a (rotated) rectangle and
+  a (rotated) ellipse. The fit with the smallest mean distance to the stroke wins, if that
+  distance is small compared to the size of the shape.
+- Everything is deterministic: the same points always give the same result.
+*/
+
+use iced::{Point, Size, Vector};
+
+use crate::affine::Affine2;
+use crate::hit_test::{distance_to_ellipse, distance_to_polygon_outline, distance_to_segment};
+use crate::ink::simplify_polyline;
+use crate::vector_path::{Segment, VectorPath};
+
+/// Number of points after resampling.
+const RESAMPLE_COUNT: usize = 64;
+/// A stroke is closed if the gap between its ends is below this fraction of its length.
+const CLOSED_GAP: f32 = 0.2;
+/// Largest accepted mean distance between stroke and shape, as a fraction of the shape size.
+const MAX_FIT_ERROR: f32 = 0.06;
+/// Largest deviation from a straight line, as a fraction of the line length.
+const LINE_DEVIATION: f32 = 0.08;
+/// Length of the arrow head relative to the shaft when drawing recognized arrows.
+const ARROW_HEAD: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Recognized {
+    Line {
+        start: Point,
+        end: Point,
+    },
+    /// A line with a head at `end`
+    Arrow {
+        start: Point,
+        end: Point,
+    },
+    Triangle([Point; 3]),
+    /// Rectangle of the given size centered at `center` and rotated around it. The rotation is
+    /// in -45..=45 degrees.
+    Rectangle {
+        center: Point,
+        size: Size,
+        rotation_degrees: f32,
+    },
+    Ellipse {
+        center: Point,
+        radii: Vector,
+        rotation_degrees: f32,
+    },
+}
+
+impl Recognized {
+    /// The recognized shape as a path.
+    pub fn to_path(&self) -> VectorPath {
+        match *self {
+            Recognized::Line { start, end } => VectorPath::polyline(&[start, end]),
+            Recognized::Arrow { start, end } => {
+                let shaft = end - start;
+                let back = shaft * -ARROW_HEAD;
+                let barb = |degrees: f32| end + Affine2::rotate(degrees).apply_vector(back);
+                let mut path = VectorPath::polyline(&[start, end]);
+                path.segments.extend([
+                    Segment::MoveTo(barb(30.0)),
+                    Segment::LineTo(end),
+                    Segment::LineTo(barb(-30.0)),
+                ]);
+                path
+            }
+            Recognized::Triangle(corners) => VectorPath::polygon(&corners),
+            Recognized::Rectangle {
+                center,
+                size,
+                rotation_degrees,
+            } => {
+                let corners = Affine2::rotate_about(center, rotation_degrees).apply_rectangle(
+                    &iced::Rectangle::new(
+                        Point::new(center.x - size.width / 2.0, center.y - size.height / 2.0),
+                        size,
+                    ),
+                );
+                VectorPath::polygon(&corners)
+            }
+            Recognized::Ellipse {
+                center,
+                radii,
+                rotation_degrees,
+            } => {
+                // four cubic arcs approximate a circle within 0.03%
+                const KAPPA: f32 = 0.552_284_8;
+                let (rx, ry) = (radii.x, radii.y);
+                let k = Vector::new(rx * KAPPA, ry * KAPPA);
+                let unit = VectorPath::new(vec![
+                    Segment::MoveTo(Point::new(rx, 0.0)),
+                    Segment::CubicTo(
+                        Point::new(rx, k.y),
+                        Point::new(k.x, ry),
+                        Point::new(0.0, ry),
+                    ),
+                    Segment::CubicTo(
+                        Point::new(-k.x, ry),
+                        Point::new(-rx, k.y),
+                        Point::new(-rx, 0.0),
+                    ),
+                    Segment::CubicTo(
+                        Point::new(-rx, -k.y),
+                        Point::new(-k.x, -ry),
+                        Point::new(0.0, -ry),
+                    ),
+                    Segment::CubicTo(
+                        Point::new(k.x, -ry),
+                        Point::new(rx, -k.y),
+                        Point::new(rx, 0.0),
+                    ),
+                    Segment::Close,
+                ]);
+                unit.transformed(
+                    &Affine2::rotate(rotation_degrees)
+                        .then(Affine2::translate(Vector::new(center.x, center.y))),
+                )
+            }
+        }
+    }
+}
+
+/// Recognizes the shape drawn by the given stroke. Returns None if the stroke doesn't look like
+/// any of the known shapes.
+pub fn recognize(points: &[Point]) -> Option<Recognized> {
+    let length = polyline_length(points);
+    if points.len() < 2 || length <= f32::EPSILON {
+        return None;
+    }
+    let gap = points[0].distance(points[points.len() - 1]);
+    if gap < CLOSED_GAP * length {
+        recognize_closed(&resample(points, RESAMPLE_COUNT))
+    } else {
+        recognize_open(&resample(points, RESAMPLE_COUNT), length)
+    }
+}
+
+fn recognize_open(points: &[Point], length: f32) -> Option<Recognized> {
+    let start = points[0];
+    let end = points[points.len() - 1];
+    let chord = start.distance(end);
+
+    // a line: no point is far away from the line between the ends
+    let deviation = points
+        .iter()
+        .map(|p| distance_to_segment(*p, start, end))
+        .fold(0.0, f32::max);
+    if deviation <= LINE_DEVIATION * chord {
+        return Some(Recognized::Line { start, end });
+    }
+
+    // an arrow: a long shaft, then all other corners close to its tip, at least one of them a
+    // barb pointing back along the shaft
+    let corners: Vec<Point> = simplify_polyline(points, 0.05 * length)
+        .into_iter()
+        .map(|i| points[i])
+        .collect();
+    let (shaft_start, tip) = (corners[0], *corners.get(1)?);
+    let shaft = tip - shaft_start;
+    let shaft_length = shaft_start.distance(tip);
+    if corners.len() < 3 || shaft_length < 0.5 * length {
+        return None;
+    }
+    let head = &corners[2..];
+    let near_tip = head.iter().all(|p| p.distance(tip) <= 0.4 * shaft_length);
+    let has_barb = head.iter().any(|p| {
+        let barb = *p - tip;
+        let barb_length = barb.x.hypot(barb.y);
+        if barb_length < 0.05 * shaft_length {
+            return false;
+        }
+        // the barb points back within 60 degrees of the shaft, but not along it
+        let cos = -(barb.x * shaft.x + barb.y * shaft.y) / (barb_length * shaft_length);
+        cos > 0.5 && cos < 0.98
+    });
+    (near_tip && has_barb).then_some(Recognized::Arrow {
+        start: shaft_start,
+        end: tip,
+    })
+}
+
+fn recognize_closed(points: &[Point]) -> Option<Recognized> {
+    let mut candidates: Vec<(f32, Recognized)> = Vec::new();
+    candidates.extend(fit_triangle(points));
+    candidates.extend(fit_rectangle(points));
+    candidates.extend(fit_ellipse(points));
+    candidates
+        .into_iter()
+        .filter(|(error, _)| *error <= MAX_FIT_ERROR)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, shape)| shape)
+}
+
+/// Fits a triangle through the three strongest corners of the stroke.
+fn fit_triangle(points: &[Point]) -> Option<(f32, Recognized)> {
+    let perimeter = polyline_length(points);
+    let mut closed = points.to_vec();
+    closed.push(points[0]);
+    let corners: Vec<Point> = simplify_polyline(&closed, 0.05 * perimeter)
+        .into_iter()
+        .map(|i| closed[i])
+        .collect();
+    // the first and the last point are the same (the start of the stroke)
+    let mut corners = corners[..corners.len() - 1].to_vec();
+    remove_flat_corners(&mut corners);
+    let [a, b, c] = corners[..] else {
+        return None;
+    };
+    let size = polyline_length(&[a, b, c, a]) / 3.0;
+    let error = mean(
+        points
+            .iter()
+            .map(|p| distance_to_polygon_outline(*p, &corners)),
+    ) / size;
+    Some((error, Recognized::Triangle([a, b, c])))
+}
+
+/// Fits a rectangle whose edges follow the dominant direction of the stroke.
+fn fit_rectangle(points: &[Point]) -> Option<(f32, Recognized)> {
+    // dominant direction modulo 90 degrees: average of the segment angles times four
+    let (sin, cos) = points
+        .windows(2)
+        .map(|w| {
+            let d = w[1] - w[0];
+            let angle = d.y.atan2(d.x) * 4.0;
+            let weight = d.x.hypot(d.y);
+            (angle.sin() * weight, angle.cos() * weight)
+        })
+        .fold((0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+    let rotation_degrees = (sin.atan2(cos) / 4.0).to_degrees();
+
+    let (center, size) = extents(points, rotation_degrees);
+    if size.width <= f32::EPSILON || size.height <= f32::EPSILON {
+        return None;
+    }
+    let corners =
+        Affine2::rotate_about(center, rotation_degrees).apply_rectangle(&iced::Rectangle::new(
+            Point::new(center.x - size.width / 2.0, center.y - size.height / 2.0),
+            size,
+        ));
+    let scale = (size.width + size.height) / 2.0;
+    let error = mean(
+        points
+            .iter()
+            .map(|p| distance_to_polygon_outline(*p, &corners)),
+    ) / scale;
+    Some((
+        error,
+        Recognized::Rectangle {
+            center,
+            size,
+            rotation_degrees,
+        },
+    ))
+}
+
+/// Fits an ellipse along the principal axes of the stroke.
+fn fit_ellipse(points: &[Point]) -> Option<(f32, Recognized)> {
+    let n = points.len() as f32;
+    let mean_point = Point::new(
+        points.iter().map(|p| p.x).sum::<f32>() / n,
+        points.iter().map(|p| p.y).sum::<f32>() / n,
+    );
+    let (mut xx, mut xy, mut yy) = (0.0, 0.0, 0.0);
+    for p in points {
+        let d = *p - mean_point;
+        xx += d.x * d.x;
+        xy += d.x * d.y;
+        yy += d.y * d.y;
+    }
+    // angle of the major axis of the covariance matrix
+    let rotation_degrees = (0.5 * (2.0 * xy).atan2(xx - yy)).to_degrees();
+
+    let (center, size) = extents(points, rotation_degrees);
+    let radii = Vector::new(size.width / 2.0, size.height / 2.0);
+    if radii.x <= f32::EPSILON || radii.y <= f32::EPSILON {
+        return None;
+    }
+    let scale = radii.x + radii.y;
+    let error = mean(
+        points
+            .iter()
+            .map(|p| distance_to_ellipse(*p, center, radii, rotation_degrees)),
+    ) / scale;
+    Some((
+        error,
+        Recognized::Ellipse {
+            center,
+            radii,
+            rotation_degrees,
+        },
+    ))
+}
+
+/// Center and size of the bounding box of the points in a coordinate system rotated by the
+/// given angle. The center is returned in the original coordinates.
+fn extents(points: &[Point], rotation_degrees: f32) -> (Point, Size) {
+    let to_local = Affine2::rotate(-rotation_degrees);
+    let local: Vec<Point> = points.iter().map(|p| to_local.apply(*p)).collect();
+    let (mut min, mut max) = (local[0], local[0]);
+    for p in &local {
+        min = Point::new(min.x.min(p.x), min.y.min(p.y));
+        max = Point::new(max.x.max(p.x), max.y.max(p.y));
+    }
+    let local_center = Point::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+    (
+        Affine2::rotate(rotation_degrees).apply(local_center),
+        Size::new(max.x - min.x, max.y - min.y),
+    )
+}
+
+/// Removes corners whose edges continue in (almost) the same direction.
+fn remove_flat_corners(corners: &mut Vec<Point>) {
+    let mut i = 0;
+    while corners.len() > 3 && i < corners.len() {
+        let previous = corners[(i + corners.len() - 1) % corners.len()];
+        let next = corners[(i + 1) % corners.len()];
+        let (u, v) = (corners[i] - previous, next - corners[i]);
+        let cos = (u.x * v.x + u.y * v.y) / (u.x.hypot(u.y) * v.x.hypot(v.y)).max(f32::EPSILON);
+        if cos > 20f32.to_radians().cos() {
+            corners.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Returns `count` points at equal distances along the polyline (including both ends).
+fn resample(points: &[Point], count: usize) -> Vec<Point> {
+    let length = polyline_length(points);
+    let step = length / (count - 1) as f32;
+    let mut result = vec![points[0]];
+    let mut walked = 0.0;
+    let mut next = step;
+    for pair in points.windows(2) {
+        let segment = pair[0].distance(pair[1]);
+        while next <= walked + segment && result.len() < count - 1 {
+            let t = (next - walked) / segment;
+            result.push(pair[0] + (pair[1] - pair[0]) * t);
+            next += step;
+        }
+        walked += segment;
+    }
+    result.push(points[points.len() - 1]);
+    result
+}
+
+fn polyline_length(points: &[Point]) -> f32 {
+    points.windows(2).map(|w| w[0].distance(w[1])).sum()
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    sum / count.max(1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loads a stroke from `tests/fixtures/strokes`. The fixtures are point sequences as the pen
+    /// tool samples them: uneven spacing, a shaky hand, rounded corners and ends that overshoot
+    /// or don't quite meet.
+    fn stroke(json: &str) -> Vec<Point> {
+        let points: Vec<[f32; 2]> = serde_json::from_str(json).expect("valid stroke fixture");
+        points.iter().map(|[x, y]| Point::new(*x, *y)).collect()
+    }
+
+    /// Recognizes the stroke twice and checks that both runs agree.
+    fn recognize_twice(points: &[Point]) -> Option<Recognized> {
+        let first = recognize(points);
+        assert_eq!(recognize(points), first, "recognition is not deterministic");
+        first
+    }
+
+    fn assert_near(actual: Point, expected: Point, tolerance: f32) {
+        assert!(
+            actual.distance(expected) <= tolerance,
+            "{actual:?} is not within {tolerance} of {expected:?}"
+        );
+    }
+
+    fn assert_about(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    #[test]
+    fn rectangle() {
+        let points = stroke(include_str!("../tests/fixtures/strokes/rectangle.json"));
+        let Some(Recognized::Rectangle {
+            center,
+            size,
+            rotation_degrees,
+        }) = recognize_twice(&points)
+        else {
+            panic!("not a rectangle: {:?}", recognize(&points));
+        };
+        assert_near(center, Point::new(200.0, 160.0), 4.0);
+        assert_about(size.width, 200.0, 6.0);
+        assert_about(size.height, 120.0, 6.0);
+        assert_about(rotation_degrees, 10.0, 2.0);
+    }
+
+    #[test]
+    fn ellipse() {
+        let points = stroke(include_str!("../tests/fixtures/strokes/ellipse.json"));
+        let Some(Recognized::Ellipse {
+            center,
+            radii,
+            rotation_degrees,
+        }) = recognize_twice(&points)
+        else {
+            panic!("not an ellipse: {:?}", recognize(&points));
+        };
+        assert_near(center, Point::new(400.0, 300.0), 4.0);
+        assert_about(radii.x, 120.0, 4.0);
+        assert_about(radii.y, 70.0, 4.0);
+        assert_about(rotation_degrees, 25.0, 3.0);
+    }
+
+    #[test]
+    fn triangle() {
+        let points = stroke(include_str!("../tests/fixtures/strokes/triangle.json"));
+        let Some(Recognized::Triangle(corners)) = recognize_twice(&points) else {
+            panic!("not a triangle: {:?}", recognize(&points));
+        };
+        // the corners in drawing order, starting at the start of the stroke
+        let expected = [
+            Point::new(50.0, 300.0),
+            Point::new(200.0, 80.0),
+            Point::new(330.0, 310.0),
+        ];
+        for (corner, expected) in corners.into_iter().zip(expected) {
+            assert_near(corner, expected, 10.0);
+        }
+    }
+
+    #[test]
+    fn line() {
+        let points = stroke(include_str!("../tests/fixtures/strokes/line.json"));
+        let Some(Recognized::Line { start, end }) = recognize_twice(&points) else {
+            panic!("not a line: {:?}", recognize(&points));
+        };
+        assert_near(start, Point::new(20.0, 20.0), 3.0);
+        assert_near(end, Point::new(300.0, 140.0), 3.0);
+    }
+
+    #[test]
+    fn arrow() {
+        let points = stroke(include_str!("../tests/fixtures/strokes/arrow.json"));
+        let Some(Recognized::Arrow { start, end }) = recognize_twice(&points) else {
+            panic!("not an arrow: {:?}", recognize(&points));
+        };
+        // the head is at the tip of the shaft, not at the end of the stroke
+        assert_near(start, Point::new(50.0, 400.0), 3.0);
+        assert_near(end, Point::new(350.0, 300.0), 4.0);
+    }
+
+    #[test]
+    fn scribble_is_rejected() {
+        let points = stroke(include_str!("../tests/fixtures/strokes/scribble.json"));
+        assert_eq!(recognize_twice(&points), None);
+    }
+
+    #[test]
+    fn degenerate_strokes_are_rejected() {
+        assert_eq!(recognize(&[]), None);
+        assert_eq!(recognize(&[Point::new(1.0, 1.0)]), None);
+        assert_eq!(recognize(&[Point::new(1.0, 1.0); 5]), None);
+    }
+}
@@ -0,0 +1,426 @@
+/*!
+A quadtree over axis aligned bounding boxes, used to find shapes quickly on large boards.
+
+Concept:
+- Every item is identified by a key and has a bounding box in world coordinates.
+- An item is stored in the smallest node that contains its whole bounding box. Items that
+  straddle the border between two children stay in the parent node.
+- A node is split into four children once it holds more than `MAX_ITEMS` items.
+- The world is infinite, so the root grows (doubles its size) whenever an item is inserted outside
+  of it. Bounding boxes with non-finite values or coordinates beyond `MAX_COORDINATE` are
+  rejected, otherwise the root would have to grow forever.
+- The index remembers the bounding box of each key, so that items can be moved and removed by key
+  alone.
+*/
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use iced::{Point, Rectangle, Size};
+
+/// Number of items a node holds before it is split.
+const MAX_ITEMS: usize = 8;
+/// Nodes at this depth are never split.
+const MAX_DEPTH: usize = 16;
+/// Side length of the initial root node in world units.
+const INITIAL_SIZE: f32 = 1024.0;
+/// Largest absolute coordinate of an item. The root never grows beyond about twice this size.
+pub const MAX_COORDINATE: f32 = 1.0e12;
+
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<K> {
+    root: Node<K>,
+    bounds: HashMap<K, Rectangle>,
+}
+
+impl<K: Copy + Eq + Hash> Default for SpatialIndex<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Copy + Eq + Hash> SpatialIndex<K> {
+    pub fn new() -> Self {
+        let half = INITIAL_SIZE / 2.0;
+        Self {
+            root: Node::new(Rectangle::new(
+                Point::new(-half, -half),
+                Size::new(INITIAL_SIZE, INITIAL_SIZE),
+            )),
+            bounds: HashMap::new(),
+        }
+    }
+
+    /// Number of items in the index.
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// Returns the bounding box the item was inserted with.
+    pub fn get(&self, key: &K) -> Option<Rectangle> {
+        self.bounds.get(key).copied()
+    }
+
+    /// Inserts an item. An existing item with the same key is replaced. Returns false (and
+    /// removes the existing item) if the bounds are not finite or exceed `MAX_COORDINATE`.
+    pub fn insert(&mut self, key: K, bounds: Rectangle) -> bool {
+        self.remove(&key);
+        if !is_indexable(&bounds) {
+            return false;
+        }
+        while !contains_rect(&self.root.bounds, &bounds) {
+            self.grow_towards(&bounds);
+        }
+        self.root.insert(key, bounds, 0);
+        self.bounds.insert(key, bounds);
+        true
+    }
+
+    /// Moves an item to a new bounding box (same as inserting it again).
+    pub fn update(&mut self, key: K, bounds: Rectangle) -> bool {
+        self.insert(key, bounds)
+    }
+
+    /// Removes an item. Returns false if there was no item with the given key.
+    pub fn remove(&mut self, key: &K) -> bool {
+        match self.bounds.remove(key) {
+            Some(bounds) => self.root.remove(key, &bounds),
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns all items whose bounding box contains the point (in no particular order).
+    pub fn query_point(&self, point: Point) -> Vec<K> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|node_bounds| intersects_or_touches(node_bounds, &Rectangle::new(point, Size::ZERO)),
+            &|item_bounds| item_bounds.contains(point),
+            &mut result,
+        );
+        result
+    }
+
+    /// Returns all items whose bounding box intersects the rectangle (in no particular order).
+    pub fn query_rect(&self, rect: Rectangle) -> Vec<K> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|node_bounds| intersects_or_touches(node_bounds, &rect),
+            &|item_bounds| intersects_or_touches(item_bounds, &rect),
+            &mut result,
+        );
+        result
+    }
+
+    /// Returns all items whose bounding box lies completely inside the rectangle.
+    pub fn query_within(&self, rect: Rectangle) -> Vec<K> {
+        let mut result = Vec::new();
+        self.root.query(
+            &|node_bounds| intersects_or_touches(node_bounds, &rect),
+            &|item_bounds| contains_rect(&rect, item_bounds),
+            &mut result,
+        );
+        result
+    }
+
+    /// Doubles the root so that it extends towards the given rectangle. The old root becomes
+    /// one of the four children of the new root.
+    fn grow_towards(&mut self, target: &Rectangle) {
+        let old = &self.root.bounds;
+        let grow_left = target.x < old.x;
+        let grow_up = target.y < old.y;
+        let new_bounds = Rectangle::new(
+            Point::new(
+                if grow_left { old.x - old.width } else { old.x },
+                if grow_up { old.y - old.height } else { old.y },
+            ),
+            Size::new(old.width * 2.0, old.height * 2.0),
+        );
+
+        let old_root = std::mem::replace(&mut self.root, Node::new(new_bounds));
+        let mut children = self.root.split_bounds().map(Node::new);
+        let slot = match (grow_left, grow_up) {
+            (false, false) => 0, // old root is the upper left child
+            (true, false) => 1,  // upper right
+            (false, true) => 2,  // lower left
+            (true, true) => 3,   // lower right
+        };
+        // the depth of all existing nodes increases by one, MAX_DEPTH is only a soft limit
+        children[slot] = old_root;
+        self.root.children = Some(Box::new(children));
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node<K> {
+    bounds: Rectangle,
+    items: Vec<(K, Rectangle)>,
+    children: Option<Box<[Node<K>; 4]>>,
+}
+
+impl<K: Copy + Eq> Node<K> {
+    fn new(bounds: Rectangle) -> Self {
+        Self {
+            bounds,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+
+    /// Bounds of the four children: upper left, upper right, lower left, lower right.
+    fn split_bounds(&self) -> [Rectangle; 4] {
+        let size = Size::new(self.bounds.width / 2.0, self.bounds.height / 2.0);
+        let (x, y) = (self.bounds.x, self.bounds.y);
+        [
+            Rectangle::new(Point::new(x, y), size),
+            Rectangle::new(Point::new(x + size.width, y), size),
+            Rectangle::new(Point::new(x, y + size.height), size),
+            Rectangle::new(Point::new(x + size.width, y + size.height), size),
+        ]
+    }
+
+    /// Index of the child that contains the whole rectangle, if any.
+    fn child_for(&self, bounds: &Rectangle) -> Option<usize> {
+        let children = self.children.as_ref()?;
+        children
+            .iter()
+            .position(|child| contains_rect(&child.bounds, bounds))
+    }
+
+    fn insert(&mut self, key: K, bounds: Rectangle, depth: usize) {
+        if let Some(index) = self.child_for(&bounds) {
+            self.children.as_mut().unwrap()[index].insert(key, bounds, depth + 1);
+            return;
+        }
+
+        self.items.push((key, bounds));
+
+        // split once the node gets crowded and push down what fits into the children
+        if self.children.is_none() && self.items.len() > MAX_ITEMS && depth < MAX_DEPTH {
+            self.children = Some(Box::new(self.split_bounds().map(Node::new)));
+            let items = std::mem::take(&mut self.items);
+            for (key, bounds) in items {
+                match self.child_for(&bounds) {
+                    Some(index) => {
+                        self.children.as_mut().unwrap()[index].insert(key, bounds, depth + 1)
+                    }
+                    None => self.items.push((key, bounds)),
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &K, bounds: &Rectangle) -> bool {
+        if let Some(index) = self.child_for(bounds)
+            && self.children.as_mut().unwrap()[index].remove(key, bounds)
+        {
+            return true;
+        }
+        match self.items.iter().position(|(k, _)| k == key) {
+            Some(position) => {
+                self.items.swap_remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn query(
+        &self,
+        visit_node: &impl Fn(&Rectangle) -> bool,
+        matches: &impl Fn(&Rectangle) -> bool,
+        result: &mut Vec<K>,
+    ) {
+        result.extend(
+            self.items
+                .iter()
+                .filter(|(_, bounds)| matches(bounds))
+                .map(|(key, _)| *key),
+        );
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                if visit_node(&child.bounds) {
+                    child.query(visit_node, matches, result);
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if the rectangle can be stored: all values are finite and the rectangle lies
+/// within `MAX_COORDINATE` of the origin.
+fn is_indexable(bounds: &Rectangle) -> bool {
+    [bounds.x, bounds.y, bounds.width, bounds.height]
+        .iter()
+        .all(|value| value.is_finite())
+        && bounds.x.abs() <= MAX_COORDINATE
+        && bounds.y.abs() <= MAX_COORDINATE
+        && (bounds.x + bounds.width).abs() <= MAX_COORDINATE
+        && (bounds.y + bounds.height).abs() <= MAX_COORDINATE
+}
+
+/// Returns true if `outer` contains the whole `inner` rectangle (borders included).
+fn contains_rect(outer: &Rectangle, inner: &Rectangle) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.width <= outer.x + outer.width
+        && inner.y + inner.height <= outer.y + outer.height
+}
+
+/// Like `Rectangle::intersects`, but rectangles that only share a border count as well. This
+/// matters for zero sized bounding boxes (e.g. a horizontal line).
+fn intersects_or_touches(a: &Rectangle, b: &Rectangle) -> bool {
+    a.x <= b.x + b.width && b.x <= a.x + a.width && a.y <= b.y + b.height && b.y <= a.y + a.height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    /// Returns a rectangle somewhere on a large board, partly at negative coordinates.
+    fn random_rect(random: &mut Random) -> Rectangle {
+        Rectangle::new(
+            Point::new(
+                random.fraction() * 20_000.0 - 5_000.0,
+                random.fraction() * 20_000.0 - 5_000.0,
+            ),
+            Size::new(random.fraction() * 300.0, random.fraction() * 300.0),
+        )
+    }
+
+    /// Checks all queries of the index against a brute force search over `items`.
+    fn assert_in_sync(
+        index: &SpatialIndex<usize>,
+        items: &HashMap<usize, Rectangle>,
+        random: &mut Random,
+    ) {
+        assert_eq!(index.len(), items.len());
+        for (key, bounds) in items {
+            assert_eq!(index.get(key), Some(*bounds));
+        }
+        for _ in 0..50 {
+            let area = Rectangle::new(
+                Point::new(
+                    random.fraction() * 25_000.0 - 7_500.0,
+                    random.fraction() * 25_000.0 - 7_500.0,
+                ),
+                Size::new(random.fraction() * 3_000.0, random.fraction() * 3_000.0),
+            );
+            let point = area.center();
+
+            let mut expected: Vec<usize> = items
+                .iter()
+                .filter(|(_, bounds)| intersects_or_touches(bounds, &area))
+                .map(|(key, _)| *key)
+                .collect();
+            let mut found = index.query_rect(area);
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected, "query_rect({area:?})");
+
+            let mut expected: Vec<usize> = items
+                .iter()
+                .filter(|(_, bounds)| contains_rect(&area, bounds))
+                .map(|(key, _)| *key)
+                .collect();
+            let mut found = index.query_within(area);
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected, "query_within({area:?})");
+
+            let mut expected: Vec<usize> = items
+                .iter()
+                .filter(|(_, bounds)| bounds.contains(point))
+                .map(|(key, _)| *key)
+                .collect();
+            let mut found = index.query_point(point);
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected, "query_point({point:?})");
+        }
+    }
+
+    #[test]
+    fn stays_in_sync_on_insert_move_and_remove() {
+        let mut random = Random(7);
+        let mut index = SpatialIndex::new();
+        let mut items = HashMap::new();
+
+        for key in 0..2_000 {
+            let bounds = random_rect(&mut random);
+            assert!(index.insert(key, bounds));
+            items.insert(key, bounds);
+        }
+        assert_in_sync(&index, &items, &mut random);
+
+        for key in (0..2_000).step_by(3) {
+            let bounds = random_rect(&mut random);
+            assert!(index.update(key, bounds));
+            items.insert(key, bounds);
+        }
+        assert_in_sync(&index, &items, &mut random);
+
+        for key in (0..2_000).step_by(2) {
+            assert!(index.remove(&key));
+            items.remove(&key);
+        }
+        assert!(!index.remove(&0));
+        assert_in_sync(&index, &items, &mut random);
+
+        // moves after removals and a second insert of the same key
+        for key in (1..2_000).step_by(4) {
+            let bounds = random_rect(&mut random);
+            index.insert(key, bounds);
+            index.insert(key, bounds);
+            items.insert(key, bounds);
+        }
+        assert_in_sync(&index, &items, &mut random);
+    }
+
+    #[test]
+    fn grows_towards_far_away_items() {
+        let mut index = SpatialIndex::new();
+        let far = [
+            Rectangle::new(Point::new(1.0e6, -2.0e6), Size::new(10.0, 10.0)),
+            Rectangle::new(Point::new(-3.0e9, 5.0e8), Size::new(1.0e3, 1.0e3)),
+            Rectangle::new(Point::new(-5.0, -5.0), Size::new(10.0, 10.0)),
+        ];
+        for (key, bounds) in far.iter().enumerate() {
+            assert!(index.insert(key, *bounds));
+        }
+        for (key, bounds) in far.iter().enumerate() {
+            assert_eq!(index.query_point(bounds.center()), vec![key]);
+        }
+    }
+
+    #[test]
+    fn rejects_bounds_the_root_can_not_grow_to() {
+        let mut index = SpatialIndex::new();
+        index.insert(1, Rectangle::new(Point::ORIGIN, Size::new(10.0, 10.0)));
+        let invalid = [
+            Rectangle::new(Point::new(f32::NAN, 0.0), Size::new(10.0, 10.0)),
+            Rectangle::new(Point::new(0.0, 0.0), Size::new(f32::NAN, 10.0)),
+            Rectangle::new(Point::new(f32::INFINITY, 0.0), Size::new(10.0, 10.0)),
+            Rectangle::new(Point::new(0.0, f32::NEG_INFINITY), Size::new(10.0, 10.0)),
+            Rectangle::new(Point::new(0.0, 0.0), Size::new(f32::INFINITY, 10.0)),
+            Rectangle::new(Point::new(-f32::MAX, 0.0), Size::new(10.0, 10.0)),
+            Rectangle::new(Point::new(0.0, 0.0), Size::new(10.0, 2.0 * MAX_COORDINATE)),
+        ];
+        for bounds in invalid {
+            assert!(!index.insert(2, bounds), "{bounds:?}");
+            assert_eq!(index.get(&2), None);
+        }
+        // a rejected move removes the item instead of leaving it at the old position
+        assert!(!index.update(1, invalid[0]));
+        assert!(index.is_empty());
+        assert!(index.query_point(Point::new(5.0, 5.0)).is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+/*!
+Themes shared by all binaries: light, dark and palettes defined by the user.
+
+Concept:
+- Every binary has a theme picker with Light, Dark and the palettes of the palette file.
+- The palette file is JSON: a list of palettes, each with a name and five colors as `#rrggbb`,
+  for example
+  `[{"name": "Night", "background": "#1e1e2e", "text": "#cdd6f4", "primary": "#89b4fa",
+  "success": "#a6e3a1", "danger": "#f38ba8"}]`.
+- The binaries read `palettes.json` from the working directory, or the file given with
+  `--palettes <FILE>`. `--theme <NAME>` selects the theme at start.
+- Canvas programs take their colors from the extended palette of the theme they are drawn
+  with, so drawings follow the theme like the widgets do.
+*/
+
+use std::io;
+use std::path::Path;
+
+use iced::theme::Palette;
+use iced::{Color, Theme};
+use serde::{Deserialize, Serialize};
+
+/// The palette file read if no `--palettes` is given.
+pub const DEFAULT_PALETTE_FILE: &str = "palettes.json";
+
+/// A palette as stored in the palette file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaletteRecord {
+    pub name: String,
+    pub background: String,
+    pub text: String,
+    pub primary: String,
+    pub success: String,
+    pub danger: String,
+}
+
+impl PaletteRecord {
+    pub fn to_theme(&self) -> Result<Theme, String> {
+        let color = |value: &str| {
+            Color::parse(value).ok_or_else(|| format!("{}: invalid color {:?}", self.name, value))
+        };
+        Ok(Theme::custom(
+            self.name.clone(),
+            Palette {
+                background: color(&self.background)?,
+                text: color(&self.text)?,
+                primary: color(&self.primary)?,
+                success: color(&self.success)?,
+                danger: color(&self.danger)?,
+            },
+        ))
+    }
+}
+
+pub fn load_palettes(path: &Path) -> io::Result<Vec<Theme>> {
+    let records: Vec<PaletteRecord> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    records
+        .iter()
+        .map(|record| {
+            record
+                .to_theme()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
+/// Light, Dark and the palettes of the palette file. A missing palette file is fine, a file that
+/// can't be read is reported and skipped.
+pub fn available_themes(palette_file: &Path) -> Vec<Theme> {
+    let mut themes = vec![Theme::Light, Theme::Dark];
+    if palette_file.exists() {
+        match load_palettes(palette_file) {
+            Ok(palettes) => themes.extend(palettes),
+            Err(e) => eprintln!(
+                "Could not load palettes from {}: {}",
+                palette_file.display(),
+                e
+            ),
+        }
+    }
+    themes
+}
+
+/// The available themes and the one named `name` (ignoring case), Light if there is none.
+pub fn select_theme(palette_file: &Path, name: Option<&str>) -> (Vec<Theme>, Theme) {
+    let themes = available_themes(palette_file);
+    let theme = name
+        .and_then(|name| {
+            let theme = themes
+                .iter()
+                .find(|theme| theme.to_string().eq_ignore_ascii_case(name));
+            if theme.is_none() {
+                eprintln!("unknown theme {}", name);
+            }
+            theme
+        })
+        .cloned()
+        .unwrap_or(Theme::Light);
+    (themes, theme)
+}
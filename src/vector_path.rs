@@ -0,0 +1,1122 @@
+/*!
+Editable vector paths made of move/line/quadratic/cubic segments.
+
+Concept:
+- A path is a list of segments, like the `d` attribute of an SVG path. A `MoveTo` starts a new
+  subpath, `Close` draws a straight line back to the start of the current subpath.
+- Every segment except `Close` ends in an anchor point. Nodes are addressed by the index of the
+  segment that ends in them, so node 0 is the start point of the path.
+- The control points of the segment ending in a node and of the segment leaving it are the
+  "in" and "out" handles of the node. A node is smooth if both handles are on one line through
+  the anchor. Smoothness is not stored, it follows from the geometry.
+- Paths can be written and parsed as SVG path data (`M 10 10 L 20 20 C ... Z`), which is also the
+  format used when paths are saved.
+*/
+
+use std::fmt::Write;
+
+use iced::widget::canvas::path::{Builder, Path};
+use iced::{Point, Rectangle, Size, Vector};
+
+use crate::affine::Affine2;
+use crate::bezier::{cubic_point, flatten_cubic, flatten_quadratic, quadratic_point, split_cubic};
+use crate::hit_test::{FillRule, distance_to_segment, point_in_polygon};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    MoveTo(Point),
+    LineTo(Point),
+    /// Quadratic Bézier curve: control point, end point
+    QuadTo(Point, Point),
+    /// Cubic Bézier curve: first control point, second control point, end point
+    CubicTo(Point, Point, Point),
+    Close,
+}
+
+impl Segment {
+    /// The anchor point the segment ends in (None for `Close`).
+    pub fn end(&self) -> Option<Point> {
+        match self {
+            Segment::MoveTo(p) | Segment::LineTo(p) => Some(*p),
+            Segment::QuadTo(_, p) | Segment::CubicTo(_, _, p) => Some(*p),
+            Segment::Close => None,
+        }
+    }
+
+    fn end_mut(&mut self) -> Option<&mut Point> {
+        match self {
+            Segment::MoveTo(p) | Segment::LineTo(p) => Some(p),
+            Segment::QuadTo(_, p) | Segment::CubicTo(_, _, p) => Some(p),
+            Segment::Close => None,
+        }
+    }
+}
+
+/// A point of a path that can be dragged in the node tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathHandle {
+    /// The anchor of the node with the given index
+    Anchor(usize),
+    /// A control point: segment index and control point index (0 or 1)
+    Control(usize, usize),
+}
+
+/// Error returned when SVG path data can't be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError(pub String);
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid path data: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VectorPath {
+    pub segments: Vec<Segment>,
+}
+
+impl VectorPath {
+    pub fn new(segments: Vec<Segment>) -> Self {
+        Self { segments }
+    }
+
+    /// Creates an open polyline through the given points.
+    pub fn polyline(points: &[Point]) -> Self {
+        let segments = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                if i == 0 {
+                    Segment::MoveTo(*p)
+                } else {
+                    Segment::LineTo(*p)
+                }
+            })
+            .collect();
+        Self { segments }
+    }
+
+    /// Creates a closed polygon through the given points.
+    pub fn polygon(points: &[Point]) -> Self {
+        let mut path = Self::polyline(points);
+        if !path.segments.is_empty() {
+            path.segments.push(Segment::Close);
+        }
+        path
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self.segments.last(), Some(Segment::Close))
+    }
+
+    /// Closes the last subpath or opens it again.
+    pub fn set_closed(&mut self, closed: bool) {
+        if closed && !self.is_closed() && self.node_count() > 1 {
+            self.segments.push(Segment::Close);
+        } else if !closed && self.is_closed() {
+            self.segments.pop();
+        }
+    }
+
+    /// Number of nodes (segments with an anchor point).
+    pub fn node_count(&self) -> usize {
+        self.segments.iter().filter(|s| s.end().is_some()).count()
+    }
+
+    /// Iterates over all nodes as (segment index, anchor point).
+    pub fn anchors(&self) -> impl Iterator<Item = (usize, Point)> + '_ {
+        self.segments
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.end().map(|p| (i, p)))
+    }
+
+    /// Iterates over all control points as (handle, control point, anchor it belongs to).
+    pub fn controls(&self) -> impl Iterator<Item = (PathHandle, Point, Point)> + '_ {
+        self.segments
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, segment)| {
+                let start = self.start_of(i);
+                let controls: Vec<(PathHandle, Point, Point)> = match *segment {
+                    Segment::QuadTo(c, end) => vec![(PathHandle::Control(i, 0), c, end)],
+                    Segment::CubicTo(c0, c1, end) => vec![
+                        (PathHandle::Control(i, 0), c0, start.unwrap_or(c0)),
+                        (PathHandle::Control(i, 1), c1, end),
+                    ],
+                    _ => vec![],
+                };
+                controls
+            })
+    }
+
+    /// Returns the position of a handle.
+    pub fn handle_position(&self, handle: PathHandle) -> Option<Point> {
+        match handle {
+            PathHandle::Anchor(i) => self.segments.get(i)?.end(),
+            PathHandle::Control(i, c) => match (self.segments.get(i)?, c) {
+                (Segment::QuadTo(p, _), 0) => Some(*p),
+                (Segment::CubicTo(p, _, _), 0) => Some(*p),
+                (Segment::CubicTo(_, p, _), 1) => Some(*p),
+                _ => None,
+            },
+        }
+    }
+
+    /// Start point of the segment with the given index (the end of the previous segment).
+    pub fn start_of(&self, index: usize) -> Option<Point> {
+        if index == 0 {
+            return None;
+        }
+        match self.segments.get(index)? {
+            Segment::MoveTo(_) => None,
+            Segment::Close => self.segments[..index].iter().rev().find_map(|s| s.end()),
+            _ => self.segments[index - 1]
+                .end()
+                .or_else(|| self.subpath_start(index - 1)),
+        }
+    }
+
+    /// Start point of the subpath that contains the segment with the given index.
+    fn subpath_start(&self, index: usize) -> Option<Point> {
+        self.segments[..=index].iter().rev().find_map(|s| match s {
+            Segment::MoveTo(p) => Some(*p),
+            _ => None,
+        })
+    }
+
+    /// Builds a canvas path that can be filled or stroked with a `Frame`.
+    pub fn to_canvas_path(&self) -> Path {
+        Path::new(|builder| self.build(builder))
+    }
+
+    /// Adds the segments to a canvas path builder.
+    pub fn build(&self, builder: &mut Builder) {
+        for segment in &self.segments {
+            match *segment {
+                Segment::MoveTo(p) => builder.move_to(p),
+                Segment::LineTo(p) => builder.line_to(p),
+                Segment::QuadTo(c, p) => builder.quadratic_curve_to(c, p),
+                Segment::CubicTo(c0, c1, p) => builder.bezier_curve_to(c0, c1, p),
+                Segment::Close => builder.close(),
+            }
+        }
+    }
+
+    /// Flattens the path into one polyline per subpath. Closed subpaths end with their start
+    /// point.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec<Point>> {
+        let mut polylines: Vec<Vec<Point>> = Vec::new();
+        let mut current: Vec<Point> = Vec::new();
+        for segment in &self.segments {
+            let start = current.last().copied();
+            match *segment {
+                Segment::MoveTo(p) => {
+                    if current.len() > 1 {
+                        polylines.push(std::mem::take(&mut current));
+                    }
+                    current = vec![p];
+                }
+                Segment::LineTo(p) => current.push(p),
+                Segment::QuadTo(c, p) => {
+                    let start = start.unwrap_or(c);
+                    current.extend(
+                        flatten_quadratic(start, c, p, tolerance)
+                            .into_iter()
+                            .skip(1),
+                    );
+                }
+                Segment::CubicTo(c0, c1, p) => {
+                    let start = start.unwrap_or(c0);
+                    current.extend(
+                        flatten_cubic(start, c0, c1, p, tolerance)
+                            .into_iter()
+                            .skip(1),
+                    );
+                }
+                Segment::Close => {
+                    if let Some(first) = current.first().copied() {
+                        if current.last() != Some(&first) {
+                            current.push(first);
+                        }
+                        polylines.push(std::mem::take(&mut current));
+                        // a segment after close continues from the start of the closed subpath
+                        current.push(first);
+                    }
+                }
+            }
+        }
+        if current.len() > 1 {
+            polylines.push(current);
+        }
+        polylines
+    }
+
+    /// Bounding box of all anchor and control points (which contains the whole path).
+    pub fn control_bounds(&self) -> Rectangle {
+        let points: Vec<Point> = self
+            .anchors()
+            .map(|(_, p)| p)
+            .chain(self.controls().map(|(_, p, _)| p))
+            .collect();
+        bounding_box(&points)
+    }
+
+    /// Exact bounding box of the path (computed from a fine flattening).
+    pub fn bounds(&self) -> Rectangle {
+        let points: Vec<Point> = self.flatten(0.1).into_iter().flatten().collect();
+        bounding_box(&points)
+    }
+
+    /// Distance of `p` to the outline of the path.
+    pub fn distance_to(&self, p: Point, tolerance: f32) -> f32 {
+        self.flatten(tolerance)
+            .iter()
+            .flat_map(|polyline| polyline.windows(2))
+            .map(|w| distance_to_segment(p, w[0], w[1]))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Returns true if `p` lies inside the closed subpaths of the path.
+    pub fn contains(&self, p: Point, fill_rule: FillRule, tolerance: f32) -> bool {
+        let polylines = self.flatten(tolerance);
+        match fill_rule {
+            FillRule::EvenOdd => {
+                polylines
+                    .iter()
+                    .filter(|polyline| point_in_polygon(p, polyline, FillRule::EvenOdd))
+                    .count()
+                    % 2
+                    == 1
+            }
+            FillRule::NonZero => {
+                polylines
+                    .iter()
+                    .map(|polyline| crate::hit_test::winding_number(p, polyline))
+                    .sum::<i32>()
+                    != 0
+            }
+        }
+    }
+
+    /// Returns a copy of the path with all points transformed.
+    pub fn transformed(&self, transform: &Affine2) -> Self {
+        let t = |p: Point| transform.apply(p);
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| match *segment {
+                Segment::MoveTo(p) => Segment::MoveTo(t(p)),
+                Segment::LineTo(p) => Segment::LineTo(t(p)),
+                Segment::QuadTo(c, p) => Segment::QuadTo(t(c), t(p)),
+                Segment::CubicTo(c0, c1, p) => Segment::CubicTo(t(c0), t(c1), t(p)),
+                Segment::Close => Segment::Close,
+            })
+            .collect();
+        Self { segments }
+    }
+
+    // --- editing
+
+    /// Returns the handle closest to `p` within `tolerance`. Control points win over anchors,
+    /// because they are often on top of each other.
+    pub fn handle_at(&self, p: Point, tolerance: f32) -> Option<PathHandle> {
+        let closest = |candidates: Vec<(PathHandle, Point)>| {
+            candidates
+                .into_iter()
+                .map(|(handle, position)| (handle, position.distance(p)))
+                .filter(|(_, distance)| *distance <= tolerance)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(handle, _)| handle)
+        };
+        closest(self.controls().map(|(h, c, _)| (h, c)).collect()).or_else(|| {
+            closest(
+                self.anchors()
+                    .map(|(i, a)| (PathHandle::Anchor(i), a))
+                    .collect(),
+            )
+        })
+    }
+
+    /// Moves a handle to a new position.
+    ///
+    /// Moving an anchor moves its control points along. Moving a control point of a smooth node
+    /// rotates the opposite control point as well, so the node stays smooth.
+    pub fn move_handle(&mut self, handle: PathHandle, position: Point) {
+        match handle {
+            PathHandle::Anchor(i) => {
+                let Some(old) = self.segments.get(i).and_then(|s| s.end()) else {
+                    return;
+                };
+                let delta = position - old;
+                let next = self.next_segment(i);
+                if let Some(end) = self.segments[i].end_mut() {
+                    *end = position;
+                }
+                if let Segment::CubicTo(_, c1, _) = &mut self.segments[i] {
+                    *c1 = *c1 + delta;
+                }
+                if let Some(next) = next
+                    && let Segment::CubicTo(c0, _, _) = &mut self.segments[next]
+                {
+                    *c0 = *c0 + delta;
+                }
+                // moving the start of a closed subpath moves the end of the closing curve too
+                if let Some(last) = self.closing_segment(i) {
+                    if let Some(end) = self.segments[last].end_mut() {
+                        *end = position;
+                    }
+                    if let Segment::CubicTo(_, c1, _) = &mut self.segments[last] {
+                        *c1 = *c1 + delta;
+                    }
+                }
+            }
+            PathHandle::Control(i, c) => {
+                let opposite = self.opposite_control(i, c);
+                let was_smooth = opposite.is_some_and(|(anchor, opposite)| {
+                    self.handle_position(handle)
+                        .is_some_and(|own| is_collinear(own, anchor, opposite))
+                });
+                self.set_control(i, c, position);
+                // keep the node smooth: the opposite handle points away from the moved one
+                if was_smooth && let Some((anchor, opposite)) = opposite {
+                    let direction = anchor - position;
+                    let length = direction.x.hypot(direction.y);
+                    if length > f32::EPSILON {
+                        let opposite_length = opposite.distance(anchor);
+                        let mirrored = anchor + direction * (opposite_length / length);
+                        if let Some((j, k)) = self.opposite_control_handle(i, c) {
+                            self.set_control(j, k, mirrored);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the segment and the parameter `t` of the point on the path closest to `p`, if it
+    /// is within `tolerance`. Used to insert nodes by clicking on a segment.
+    pub fn segment_at(&self, p: Point, tolerance: f32) -> Option<(usize, f32)> {
+        const STEPS: usize = 64;
+        let mut best: Option<(usize, f32, f32)> = None;
+        for (i, segment) in self.segments.iter().enumerate() {
+            let Some(start) = self.start_of(i) else {
+                continue;
+            };
+            let point_at = |t: f32| match *segment {
+                Segment::LineTo(e) => lerp(start, e, t),
+                Segment::QuadTo(c, e) => quadratic_point(start, c, e, t),
+                Segment::CubicTo(c0, c1, e) => cubic_point(start, c0, c1, e, t),
+                Segment::Close => lerp(start, self.subpath_start(i).unwrap_or(start), t),
+                Segment::MoveTo(e) => e,
+            };
+            for step in 0..=STEPS {
+                let t = step as f32 / STEPS as f32;
+                let distance = point_at(t).distance(p);
+                if best.is_none_or(|(_, _, d)| distance < d) {
+                    best = Some((i, t, distance));
+                }
+            }
+        }
+        best.filter(|(_, _, distance)| *distance <= tolerance)
+            .map(|(i, t, _)| (i, t))
+    }
+
+    /// Splits the segment with the given index at parameter `t`. Returns the index of the new
+    /// node. The shape of the path doesn't change.
+    pub fn insert_node(&mut self, index: usize, t: f32) -> Option<usize> {
+        let start = self.start_of(index)?;
+        let t = t.clamp(0.0, 1.0);
+        match self.segments[index] {
+            Segment::LineTo(e) => {
+                self.segments
+                    .insert(index, Segment::LineTo(lerp(start, e, t)));
+            }
+            Segment::QuadTo(c, e) => {
+                let (c0, c1) = elevate_quadratic(start, c, e);
+                let (first, second) = split_cubic(start, c0, c1, e, t);
+                self.segments[index] = Segment::CubicTo(second[1], second[2], second[3]);
+                self.segments
+                    .insert(index, Segment::CubicTo(first[1], first[2], first[3]));
+            }
+            Segment::CubicTo(c0, c1, e) => {
+                let (first, second) = split_cubic(start, c0, c1, e, t);
+                self.segments[index] = Segment::CubicTo(second[1], second[2], second[3]);
+                self.segments
+                    .insert(index, Segment::CubicTo(first[1], first[2], first[3]));
+            }
+            Segment::Close => {
+                let end = self.subpath_start(index)?;
+                self.segments
+                    .insert(index, Segment::LineTo(lerp(start, end, t)));
+            }
+            Segment::MoveTo(_) => return None,
+        }
+        Some(index)
+    }
+
+    /// Removes a node. The segments before and after the node are merged into one. Removing
+    /// the start node makes the next node the new start.
+    pub fn delete_node(&mut self, index: usize) {
+        let Some(segment) = self.segments.get(index).copied() else {
+            return;
+        };
+        let next = self.next_segment(index);
+        match (segment, next) {
+            (Segment::Close, _) => {}
+            (Segment::MoveTo(_), Some(next)) => {
+                if let Some(end) = self.segments[next].end() {
+                    self.segments[next] = Segment::MoveTo(end);
+                }
+                self.segments.remove(index);
+            }
+            (_, Some(next)) => {
+                let start = self.start_of(index).unwrap_or(Point::ORIGIN);
+                let out_control = match segment {
+                    Segment::CubicTo(c0, _, _) => Some(c0),
+                    Segment::QuadTo(c, _) => Some(c),
+                    _ => None,
+                };
+                let merged = match self.segments[next] {
+                    Segment::LineTo(e) if out_control.is_none() => Segment::LineTo(e),
+                    Segment::LineTo(e) => Segment::CubicTo(out_control.unwrap_or(start), e, e),
+                    Segment::QuadTo(c, e) => Segment::CubicTo(out_control.unwrap_or(start), c, e),
+                    Segment::CubicTo(_, c1, e) => {
+                        Segment::CubicTo(out_control.unwrap_or(start), c1, e)
+                    }
+                    other => other,
+                };
+                self.segments[next] = merged;
+                self.segments.remove(index);
+            }
+            (_, None) => {
+                self.segments.remove(index);
+            }
+        }
+        // a Close directly after a MoveTo (or at the start) is meaningless
+        if self.node_count() < 2 {
+            self.segments.retain(|s| *s != Segment::Close);
+        }
+    }
+
+    /// Returns true if the node is smooth (its handles are on one line through the anchor).
+    pub fn is_smooth(&self, index: usize) -> bool {
+        let (Some(anchor), Some(incoming), Some(outgoing)) = (
+            self.segments.get(index).and_then(|s| s.end()),
+            self.in_control(index),
+            self.out_control(index),
+        ) else {
+            return false;
+        };
+        is_collinear(incoming, anchor, outgoing)
+    }
+
+    /// Turns a corner node into a smooth node and vice versa.
+    ///
+    /// A smooth node gets handles parallel to the line between its neighbours, which turns the
+    /// adjacent segments into cubic curves. A corner node gets its handles retracted into the
+    /// anchor.
+    pub fn toggle_smooth(&mut self, index: usize) {
+        let Some(anchor) = self.segments.get(index).and_then(|s| s.end()) else {
+            return;
+        };
+        let next = self.next_segment(index);
+        let previous_anchor = self.start_of(index);
+        let next_anchor = next.and_then(|n| self.segments[n].end());
+
+        if self.is_smooth(index) {
+            if let Segment::CubicTo(_, c1, _) = &mut self.segments[index] {
+                *c1 = anchor;
+            }
+            if let Some(next) = next
+                && let Segment::CubicTo(c0, _, _) = &mut self.segments[next]
+            {
+                *c0 = anchor;
+            }
+            return;
+        }
+
+        let (Some(previous_anchor), Some(next_anchor)) = (previous_anchor, next_anchor) else {
+            return;
+        };
+        let tangent = next_anchor - previous_anchor;
+        let tangent_length = tangent.x.hypot(tangent.y).max(f32::EPSILON);
+        let direction = tangent * (1.0 / tangent_length);
+        let in_length = anchor.distance(previous_anchor) / 3.0;
+        let out_length = anchor.distance(next_anchor) / 3.0;
+
+        self.make_cubic(index);
+        if let Some(next) = next {
+            self.make_cubic(next);
+        }
+        if let Segment::CubicTo(_, c1, _) = &mut self.segments[index] {
+            *c1 = anchor - direction * in_length;
+        }
+        if let Some(next) = next
+            && let Segment::CubicTo(c0, _, _) = &mut self.segments[next]
+        {
+            *c0 = anchor + direction * out_length;
+        }
+    }
+
+    /// Converts a line or quadratic segment into an equivalent cubic segment.
+    fn make_cubic(&mut self, index: usize) {
+        let Some(start) = self.start_of(index) else {
+            return;
+        };
+        self.segments[index] = match self.segments[index] {
+            Segment::LineTo(e) => {
+                Segment::CubicTo(lerp(start, e, 1.0 / 3.0), lerp(start, e, 2.0 / 3.0), e)
+            }
+            Segment::QuadTo(c, e) => {
+                let (c0, c1) = elevate_quadratic(start, c, e);
+                Segment::CubicTo(c0, c1, e)
+            }
+            other => other,
+        };
+    }
+
+    /// Index of the segment leaving the node (None at the end of an open subpath).
+    fn next_segment(&self, index: usize) -> Option<usize> {
+        match self.segments.get(index + 1)? {
+            Segment::MoveTo(_) => None,
+            Segment::Close => None,
+            _ => Some(index + 1),
+        }
+    }
+
+    /// If the node starts a closed subpath whose last segment ends in the same point, returns
+    /// that last segment.
+    fn closing_segment(&self, index: usize) -> Option<usize> {
+        if !matches!(self.segments.get(index)?, Segment::MoveTo(_)) {
+            return None;
+        }
+        let close = index
+            + self.segments[index..]
+                .iter()
+                .position(|s| *s == Segment::Close)?;
+        if self.segments[index + 1..close]
+            .iter()
+            .any(|s| matches!(s, Segment::MoveTo(_)))
+        {
+            return None;
+        }
+        let last = close - 1;
+        (last > index && self.segments[last].end() == self.segments[index].end()).then_some(last)
+    }
+
+    fn in_control(&self, index: usize) -> Option<Point> {
+        match self.segments.get(index)? {
+            Segment::QuadTo(c, _) | Segment::CubicTo(_, c, _) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn out_control(&self, index: usize) -> Option<Point> {
+        match self.segments.get(self.next_segment(index)?)? {
+            Segment::QuadTo(c, _) | Segment::CubicTo(c, _, _) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn set_control(&mut self, index: usize, control: usize, position: Point) {
+        match (self.segments.get_mut(index), control) {
+            (Some(Segment::QuadTo(c, _)), 0) => *c = position,
+            (Some(Segment::CubicTo(c, _, _)), 0) => *c = position,
+            (Some(Segment::CubicTo(_, c, _)), 1) => *c = position,
+            _ => {}
+        }
+    }
+
+    /// The control point on the other side of the anchor the given control point belongs to,
+    /// as (anchor, opposite control point).
+    fn opposite_control(&self, index: usize, control: usize) -> Option<(Point, Point)> {
+        let (j, k) = self.opposite_control_handle(index, control)?;
+        let anchor = if control == 0 {
+            self.start_of(index)?
+        } else {
+            self.segments[index].end()?
+        };
+        Some((anchor, self.handle_position(PathHandle::Control(j, k))?))
+    }
+
+    fn opposite_control_handle(&self, index: usize, control: usize) -> Option<(usize, usize)> {
+        match (self.segments.get(index)?, control) {
+            // first control point of a cubic: opposite is the last control of the previous segment
+            (Segment::CubicTo(..), 0) => {
+                let previous = index.checked_sub(1)?;
+                match self.segments[previous] {
+                    Segment::CubicTo(..) => Some((previous, 1)),
+                    _ => None,
+                }
+            }
+            (Segment::CubicTo(..), 1) => match self.segments.get(self.next_segment(index)?)? {
+                Segment::CubicTo(..) | Segment::QuadTo(..) => Some((index + 1, 0)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // --- SVG path data
+
+    /// Writes the path as SVG path data with absolute coordinates.
+    pub fn to_svg_data(&self) -> String {
+        let mut data = String::new();
+        for segment in &self.segments {
+            if !data.is_empty() {
+                data.push(' ');
+            }
+            let _ = match *segment {
+                Segment::MoveTo(p) => write!(data, "M {} {}", p.x, p.y),
+                Segment::LineTo(p) => write!(data, "L {} {}", p.x, p.y),
+                Segment::QuadTo(c, p) => write!(data, "Q {} {} {} {}", c.x, c.y, p.x, p.y),
+                Segment::CubicTo(c0, c1, p) => write!(
+                    data,
+                    "C {} {} {} {} {} {}",
+                    c0.x, c0.y, c1.x, c1.y, p.x, p.y
+                ),
+                Segment::Close => write!(data, "Z"),
+            };
+        }
+        data
+    }
+
+    /// Parses SVG path data. Supports the commands M, L, H, V, Q, C and Z in absolute (upper case)
+    /// and relative (lower case) form, including implicitly repeated commands.
+    pub fn from_svg_data(data: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(data)?;
+        let mut segments = Vec::new();
+        let mut position = 0;
+        let mut command: Option<char> = None;
+        let mut current = Point::ORIGIN;
+        let mut subpath_start = Point::ORIGIN;
+
+        while position < tokens.len() {
+            if let Token::Command(c) = tokens[position] {
+                command = Some(c);
+                position += 1;
+            }
+            let Some(c) = command else {
+                return Err(ParseError("path data must start with a command".into()));
+            };
+            let relative = c.is_ascii_lowercase();
+            let offset = if relative {
+                Vector::new(current.x, current.y)
+            } else {
+                Vector::new(0.0, 0.0)
+            };
+            let mut numbers = |count: usize| -> Result<Vec<f32>, ParseError> {
+                let mut values = Vec::with_capacity(count);
+                for _ in 0..count {
+                    match tokens.get(position) {
+                        Some(Token::Number(n)) => values.push(*n),
+                        _ => return Err(ParseError(format!("missing number after '{}'", c))),
+                    }
+                    position += 1;
+                }
+                Ok(values)
+            };
+            let point = |x: f32, y: f32| Point::new(x, y) + offset;
+
+            match c.to_ascii_uppercase() {
+                'M' => {
+                    let n = numbers(2)?;
+                    current = point(n[0], n[1]);
+                    subpath_start = current;
+                    segments.push(Segment::MoveTo(current));
+                    // further coordinate pairs are implicit line-to commands
+                    command = Some(if relative { 'l' } else { 'L' });
+                }
+                'L' => {
+                    let n = numbers(2)?;
+                    current = point(n[0], n[1]);
+                    segments.push(Segment::LineTo(current));
+                }
+                'H' => {
+                    let n = numbers(1)?;
+                    current = Point::new(n[0] + offset.x, current.y);
+                    segments.push(Segment::LineTo(current));
+                }
+                'V' => {
+                    let n = numbers(1)?;
+                    current = Point::new(current.x, n[0] + offset.y);
+                    segments.push(Segment::LineTo(current));
+                }
+                'Q' => {
+                    let n = numbers(4)?;
+                    let control = point(n[0], n[1]);
+                    current = point(n[2], n[3]);
+                    segments.push(Segment::QuadTo(control, current));
+                }
+                'C' => {
+                    let n = numbers(6)?;
+                    let c0 = point(n[0], n[1]);
+                    let c1 = point(n[2], n[3]);
+                    current = point(n[4], n[5]);
+                    segments.push(Segment::CubicTo(c0, c1, current));
+                }
+                'Z' => {
+                    segments.push(Segment::Close);
+                    current = subpath_start;
+                    command = None;
+                }
+                other => return Err(ParseError(format!("unsupported command '{}'", other))),
+            }
+        }
+        Ok(Self { segments })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+fn tokenize(data: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = data.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() || c == ',' {
+            i += 1;
+        } else if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            tokens.push(Token::Command(c));
+            i += 1;
+        } else {
+            // a number: optional sign, digits, optional fraction and exponent
+            let start = i;
+            if chars[i] == '-' || chars[i] == '+' {
+                i += 1;
+            }
+            let mut seen_dot = false;
+            while i < chars.len() {
+                match chars[i] {
+                    '0'..='9' => i += 1,
+                    '.' if !seen_dot => {
+                        seen_dot = true;
+                        i += 1;
+                    }
+                    'e' | 'E' => {
+                        i += 1;
+                        if i < chars.len() && (chars[i] == '-' || chars[i] == '+') {
+                            i += 1;
+                        }
+                    }
+                    _ => break,
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse()
+                .map_err(|_| ParseError(format!("invalid number '{}'", text)))?;
+            tokens.push(Token::Number(number));
+        }
+    }
+    Ok(tokens)
+}
+
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+    Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+/// Control points of the cubic curve that is identical to the quadratic one.
+fn elevate_quadratic(start: Point, control: Point, end: Point) -> (Point, Point) {
+    (
+        lerp(start, control, 2.0 / 3.0),
+        lerp(end, control, 2.0 / 3.0),
+    )
+}
+
+/// True if `anchor` lies on the line between `a` and `b` and the two handles point in opposite
+/// directions. Handles retracted into the anchor are never collinear.
+fn is_collinear(a: Point, anchor: Point, b: Point) -> bool {
+    let u = a - anchor;
+    let v = b - anchor;
+    let (lu, lv) = (u.x.hypot(u.y), v.x.hypot(v.y));
+    if lu <= 1e-3 || lv <= 1e-3 {
+        return false;
+    }
+    let cross = (u.x * v.y - u.y * v.x) / (lu * lv);
+    let dot = (u.x * v.x + u.y * v.y) / (lu * lv);
+    cross.abs() < 1e-2 && dot < 0.0
+}
+
+fn bounding_box(points: &[Point]) -> Rectangle {
+    let Some(first) = points.first() else {
+        return Rectangle::new(Point::ORIGIN, Size::ZERO);
+    };
+    let (mut min, mut max) = (*first, *first);
+    for p in points {
+        min = Point::new(min.x.min(p.x), min.y.min(p.y));
+        max = Point::new(max.x.max(p.x), max.y.max(p.y));
+    }
+    Rectangle::new(min, Size::new(max.x - min.x, max.y - min.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    /// A closed path with one segment of every kind.
+    const MIXED: &str = "M 0 0 L 100 0 Q 150 50 100 100 C 60 140 20 100 0 100 Z";
+
+    fn parse(data: &str) -> VectorPath {
+        VectorPath::from_svg_data(data).unwrap_or_else(|e| panic!("{data:?}: {e}"))
+    }
+
+    fn p(x: f32, y: f32) -> Point {
+        Point::new(x, y)
+    }
+
+    /// Asserts that both paths have (nearly) the same outline: every point of one flattened path
+    /// lies on the other one.
+    fn assert_same_outline(actual: &VectorPath, expected: &VectorPath) {
+        for (a, b) in [(actual, expected), (expected, actual)] {
+            for point in a.flatten(0.01).into_iter().flatten() {
+                let distance = b.distance_to(point, 0.01);
+                assert!(distance < 0.1, "{point:?} is {distance} away from {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn parses_absolute_and_relative_commands() {
+        let expected = vec![
+            Segment::MoveTo(p(10.0, 20.0)),
+            Segment::LineTo(p(30.0, 40.0)),
+            Segment::LineTo(p(50.0, 40.0)),
+            Segment::LineTo(p(50.0, 60.0)),
+            Segment::QuadTo(p(70.0, 80.0), p(90.0, 100.0)),
+            Segment::CubicTo(p(110.0, 120.0), p(130.0, 140.0), p(150.0, 160.0)),
+            Segment::Close,
+        ];
+        let absolute = "M 10 20 L 30 40 H 50 V 60 Q 70 80 90 100 C 110 120 130 140 150 160 Z";
+        let relative = "m 10 20 l 20 20 h 20 v 20 q 20 20 40 40 c 20 20 40 40 60 60 z";
+        assert_eq!(parse(absolute).segments, expected);
+        assert_eq!(parse(relative).segments, expected);
+    }
+
+    #[test]
+    fn parses_implicit_commands_and_compact_numbers() {
+        // coordinate pairs after a move-to are line-tos, a relative move after z starts at the
+        // start of the closed subpath
+        assert_eq!(
+            parse("M0 0 10 0 10,10 Z m 5 5 1 0 0 1z").segments,
+            vec![
+                Segment::MoveTo(p(0.0, 0.0)),
+                Segment::LineTo(p(10.0, 0.0)),
+                Segment::LineTo(p(10.0, 10.0)),
+                Segment::Close,
+                Segment::MoveTo(p(5.0, 5.0)),
+                Segment::LineTo(p(6.0, 5.0)),
+                Segment::LineTo(p(6.0, 6.0)),
+                Segment::Close,
+            ]
+        );
+        assert_eq!(
+            parse("M0 0C1 1 2 2 3 3 4 4 5 5 6 6h-1-1").segments,
+            vec![
+                Segment::MoveTo(p(0.0, 0.0)),
+                Segment::CubicTo(p(1.0, 1.0), p(2.0, 2.0), p(3.0, 3.0)),
+                Segment::CubicTo(p(4.0, 4.0), p(5.0, 5.0), p(6.0, 6.0)),
+                Segment::LineTo(p(5.0, 6.0)),
+                Segment::LineTo(p(4.0, 6.0)),
+            ]
+        );
+        assert_eq!(
+            parse("M-1.5-2e1L.5.5").segments,
+            vec![
+                Segment::MoveTo(p(-1.5, -20.0)),
+                Segment::LineTo(p(0.5, 0.5))
+            ]
+        );
+        assert_eq!(parse("").segments, vec![]);
+    }
+
+    #[test]
+    fn written_path_data_parses_to_the_same_path() {
+        for data in [
+            MIXED,
+            "m 10 20 l 20 20 h 20 v 20 q 20 20 40 40 c 20 20 40 40 60 60 z",
+            "M0 0 10 0 10,10 Z m 5 5 1 0 0 1z",
+            "M-1.5-2e1L.5.5",
+        ] {
+            let path = parse(data);
+            let written = path.to_svg_data();
+            assert_eq!(parse(&written), path, "{data:?} -> {written:?}");
+        }
+        // arbitrary coordinates are written without losing precision
+        let mut random = Random(11);
+        for _ in 0..100 {
+            let mut segments = vec![Segment::MoveTo(random.point(1.0e4))];
+            for _ in 0..10 {
+                segments.push(match random.range(0.0, 5.0) as u32 {
+                    0 => Segment::LineTo(random.point(1.0e4)),
+                    1 => Segment::QuadTo(random.point(1.0e4), random.point(1.0e-3)),
+                    2 => Segment::CubicTo(
+                        random.point(1.0e4),
+                        random.point(1.0),
+                        random.point(1.0e6),
+                    ),
+                    3 => Segment::Close,
+                    _ => Segment::MoveTo(random.point(1.0e4)),
+                });
+            }
+            let path = VectorPath::new(segments);
+            assert_eq!(parse(&path.to_svg_data()), path);
+        }
+    }
+
+    #[test]
+    fn malformed_path_data_is_an_error() {
+        for data in [
+            "10 10",
+            "M 10",
+            "M 10 10 L",
+            "M 10 10 L 20",
+            "M 10 10 X 5 5",
+            "M 10 10 A 5 5 0 0 1 20 20",
+            "M 1e 5",
+            "M --5 0",
+            "M . 0",
+            "M 10 10 Z 5 5",
+            "M 10 10 L 20 é",
+            "M NaN 0",
+        ] {
+            assert!(VectorPath::from_svg_data(data).is_err(), "{data:?}");
+        }
+    }
+
+    #[test]
+    fn inserted_nodes_keep_the_shape() {
+        let original = parse(MIXED);
+        // the line, the quadratic and the cubic curve, and the closing line
+        for index in 1..=4 {
+            for t in [0.25, 0.5, 0.8] {
+                let mut path = original.clone();
+                assert_eq!(path.insert_node(index, t), Some(index));
+                assert_eq!(path.node_count(), original.node_count() + 1);
+                assert_same_outline(&path, &original);
+                let node = path.segments[index].end().expect("a node");
+                assert!(original.distance_to(node, 0.01) < 0.1, "{node:?}");
+                // splitting a curve doesn't add a corner
+                if index == 2 || index == 3 {
+                    assert!(path.is_smooth(index), "{index} at {t}: {path:?}");
+                }
+            }
+        }
+        // a move-to has no segment that could be split
+        assert_eq!(original.clone().insert_node(0, 0.5), None);
+    }
+
+    #[test]
+    fn deleting_an_inserted_node_joins_the_segments_again() {
+        // a split line becomes the same line again
+        let original = VectorPath::polygon(&[p(0.0, 0.0), p(100.0, 0.0), p(100.0, 100.0)]);
+        let mut path = original.clone();
+        path.insert_node(1, 0.3);
+        path.delete_node(1);
+        assert_eq!(path, original);
+
+        // a split curve keeps its end points and the directions of its handles
+        let original = parse("M 0 0 C 0 50 50 100 100 100");
+        let mut path = original.clone();
+        path.insert_node(1, 0.4);
+        path.delete_node(1);
+        let (Segment::CubicTo(c0, c1, end), Segment::CubicTo(o0, o1, original_end)) =
+            (path.segments[1], original.segments[1])
+        else {
+            panic!("{path:?}");
+        };
+        assert_eq!(end, original_end);
+        let same_direction = |a: Vector, b: Vector| {
+            (a.x * b.y - a.y * b.x).abs() < 1e-3 * a.x.hypot(a.y) * b.x.hypot(b.y)
+                && a.x * b.x + a.y * b.y > 0.0
+        };
+        assert!(same_direction(c0 - Point::ORIGIN, o0 - Point::ORIGIN));
+        assert!(same_direction(c1 - end, o1 - end));
+    }
+
+    #[test]
+    fn deleting_the_start_node_starts_at_the_next_one() {
+        let mut path = parse("M 0 0 L 10 0 L 10 10 Z");
+        path.delete_node(0);
+        assert_eq!(
+            path.segments,
+            vec![
+                Segment::MoveTo(p(10.0, 0.0)),
+                Segment::LineTo(p(10.0, 10.0)),
+                Segment::Close,
+            ]
+        );
+        // a single node can't be closed
+        path.delete_node(1);
+        assert_eq!(path.segments, vec![Segment::MoveTo(p(10.0, 0.0))]);
+    }
+
+    #[test]
+    fn toggle_smooth_keeps_the_node_in_place() {
+        let mut path = parse("M 0 0 L 100 0 L 100 100 L 0 100 Z");
+        assert!(!path.is_smooth(1));
+        path.toggle_smooth(1);
+        assert!(path.is_smooth(1));
+        let anchors: Vec<Point> = path.anchors().map(|(_, anchor)| anchor).collect();
+        assert_eq!(
+            anchors,
+            [p(0.0, 0.0), p(100.0, 0.0), p(100.0, 100.0), p(0.0, 100.0)]
+        );
+        // the handles are parallel to the line between the neighbours
+        let handle = path.out_control(1).expect("a handle") - p(100.0, 0.0);
+        assert!((handle.x - handle.y).abs() < 1e-3, "{handle:?}");
+
+        path.toggle_smooth(1);
+        assert!(!path.is_smooth(1));
+        assert_eq!(path.in_control(1), Some(p(100.0, 0.0)));
+        assert_eq!(path.out_control(1), Some(p(100.0, 0.0)));
+        assert_eq!(path.segments[1].end(), Some(p(100.0, 0.0)));
+
+        // the node of a split curve is smooth, a corner there still goes through the split point
+        let mut path = parse("M 0 0 C 0 50 50 100 100 100");
+        path.insert_node(1, 0.5);
+        let node = path.segments[1].end();
+        path.toggle_smooth(1);
+        assert!(!path.is_smooth(1));
+        assert_eq!(path.segments[1].end(), node);
+        path.toggle_smooth(1);
+        assert!(path.is_smooth(1));
+        assert_eq!(path.segments[1].end(), node);
+    }
+
+    #[test]
+    fn moving_handles_keeps_smooth_nodes_smooth() {
+        let mut path = parse("M 0 0 C 0 50 50 100 100 100");
+        path.insert_node(1, 0.5);
+        // moving the anchor takes its handles along
+        let (anchor, in_control, out_control) = (
+            path.segments[1].end().expect("a node"),
+            path.in_control(1).expect("a handle"),
+            path.out_control(1).expect("a handle"),
+        );
+        let delta = Vector::new(5.0, -7.0);
+        path.move_handle(PathHandle::Anchor(1), anchor + delta);
+        assert_eq!(path.in_control(1), Some(in_control + delta));
+        assert_eq!(path.out_control(1), Some(out_control + delta));
+
+        // rotating one handle rotates the other one as well
+        path.move_handle(
+            PathHandle::Control(1, 1),
+            anchor + delta + Vector::new(-30.0, 0.0),
+        );
+        assert!(path.is_smooth(1));
+        let out_control = path.out_control(1).expect("a handle");
+        assert!((out_control.y - (anchor.y + delta.y)).abs() < 1e-3);
+        assert!(out_control.x > anchor.x + delta.x);
+    }
+}
@@ -0,0 +1,362 @@
+/*!
+The view onto an infinite world: pan, zoom and inertial panning.
+
+Concept:
+- A point is shown at `screen = world * scale + translation`. Screen coordinates are pixels
+  relative to the upper left corner of the canvas.
+- Panning is given in screen pixels, so the content follows the cursor one to one at every zoom
+  level. Mouse positions must be converted with `to_world` only for hit-testing, never for
+  panning.
+- Zooming keeps a screen point (usually the cursor) above the same world point. The scale is
+  clamped to `MIN_SCALE..=MAX_SCALE`, so the transform can always be inverted.
+- Inertia: while the view is dragged, the pan speed is tracked (in pixels per second). After the
+  release the view glides on and slows down exponentially until it is slower than
+  `MIN_GLIDE_SPEED`. A drag that stopped before the release doesn't glide.
+*/
+
+use std::time::Instant;
+
+use iced::{Point, Rectangle, Size, Vector};
+
+use crate::affine::Affine2;
+
+/// Smallest and largest zoom factor the view can reach.
+pub const MIN_SCALE: f32 = 0.1;
+pub const MAX_SCALE: f32 = 10.0;
+
+/// Rate (per second) at which a gliding view slows down: the speed drops to 1/e in 1/DECAY s.
+const GLIDE_DECAY: f32 = 5.0;
+/// Gliding stops below this speed in pixels per second.
+const MIN_GLIDE_SPEED: f32 = 20.0;
+/// A release later than this (in seconds) after the last movement doesn't glide.
+const RELEASE_TIMEOUT: f32 = 0.1;
+/// Time constant (in seconds) of the averaged drag speed.
+const SPEED_SMOOTHING: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Screen position of the world origin
+    pub translation: Vector,
+    /// Screen pixels per world unit
+    pub scale: f32,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            translation: Vector::new(0.0, 0.0),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn new(translation: Vector, scale: f32) -> Self {
+        Self {
+            translation,
+            scale: scale.clamp(MIN_SCALE, MAX_SCALE),
+        }
+    }
+
+    /// Transform from world coordinates to screen (canvas) coordinates.
+    pub fn world_to_screen(&self) -> Affine2 {
+        Affine2::scale(self.scale).then(Affine2::translate(self.translation))
+    }
+
+    /// Transform from screen (canvas) coordinates to world coordinates.
+    pub fn screen_to_world(&self) -> Affine2 {
+        Affine2::translate(Vector::new(-self.translation.x, -self.translation.y))
+            .then(Affine2::scale(1.0 / self.scale))
+    }
+
+    pub fn to_screen(&self, world: Point) -> Point {
+        Point::new(
+            world.x * self.scale + self.translation.x,
+            world.y * self.scale + self.translation.y,
+        )
+    }
+
+    pub fn to_world(&self, screen: Point) -> Point {
+        Point::new(
+            (screen.x - self.translation.x) / self.scale,
+            (screen.y - self.translation.y) / self.scale,
+        )
+    }
+
+    /// Moves the content by the given distance in screen pixels.
+    pub fn pan(&mut self, screen_delta: Vector) {
+        self.translation = self.translation + screen_delta;
+    }
+
+    /// Sets the zoom factor (clamped to `MIN_SCALE..=MAX_SCALE`) while keeping the world point
+    /// below the given screen point in place. Returns true if the scale changed.
+    pub fn zoom_about(&mut self, screen_anchor: Point, new_scale: f32) -> bool {
+        let new_scale = new_scale.clamp(MIN_SCALE, MAX_SCALE);
+        if self.scale == new_scale {
+            return false;
+        }
+        // the anchor is `factor` times as far from the world origin on the screen as before
+        let factor = new_scale / self.scale;
+        let anchor = Vector::new(screen_anchor.x, screen_anchor.y);
+        self.translation = anchor + (self.translation - anchor) * factor;
+        self.scale = new_scale;
+        true
+    }
+
+    /// Returns the world point shown in the center of a viewport of the given size.
+    pub fn center(&self, viewport_size: Size) -> Point {
+        self.to_world(Point::new(
+            viewport_size.width / 2.0,
+            viewport_size.height / 2.0,
+        ))
+    }
+
+    /// Returns the part of the world that is visible in a viewport of the given size.
+    pub fn visible_world_rect(&self, viewport_size: Size) -> Rectangle {
+        Rectangle::new(
+            self.to_world(Point::ORIGIN),
+            Size::new(
+                viewport_size.width / self.scale,
+                viewport_size.height / self.scale,
+            ),
+        )
+    }
+
+    /// Returns a view that shows the given world point in the center of the viewport at the
+    /// given scale.
+    pub fn centered_on(center: Point, scale: f32, viewport_size: Size) -> Self {
+        let scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+        Self {
+            translation: Vector::new(
+                viewport_size.width / 2.0 - center.x * scale,
+                viewport_size.height / 2.0 - center.y * scale,
+            ),
+            scale,
+        }
+    }
+
+    /// Returns a view that fits the given world rectangle into the viewport, leaving `margin`
+    /// pixels free on each side.
+    pub fn fitting(world_rect: Rectangle, viewport_size: Size, margin: f32) -> Self {
+        let available = Size::new(
+            (viewport_size.width - 2.0 * margin).max(1.0),
+            (viewport_size.height - 2.0 * margin).max(1.0),
+        );
+        let scale = (available.width / world_rect.width.max(1.0))
+            .min(available.height / world_rect.height.max(1.0));
+        Self::centered_on(world_rect.center(), scale, viewport_size)
+    }
+}
+
+/// The speed of a dragged view and the glide after the release.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Inertia {
+    /// Pixels per second
+    velocity: Vector,
+    /// Time of the last drag movement or glide step
+    last: Option<Instant>,
+    gliding: bool,
+}
+
+impl Inertia {
+    /// Records a drag movement of `screen_delta` pixels at the given time.
+    pub fn track(&mut self, screen_delta: Vector, now: Instant) {
+        self.gliding = false;
+        if let Some(last) = self.last {
+            let elapsed = now.saturating_duration_since(last).as_secs_f32();
+            if elapsed > 0.0 {
+                // exponential average, independent of the event rate
+                let weight = 1.0 - (-elapsed / SPEED_SMOOTHING).exp();
+                self.velocity =
+                    self.velocity + (screen_delta * (1.0 / elapsed) - self.velocity) * weight;
+            }
+        }
+        self.last = Some(now);
+    }
+
+    /// Ends the drag. Returns true if the view glides on.
+    pub fn release(&mut self, now: Instant) -> bool {
+        let recent = self.last.is_some_and(|last| {
+            now.saturating_duration_since(last).as_secs_f32() <= RELEASE_TIMEOUT
+        });
+        self.gliding = recent && length(self.velocity) >= MIN_GLIDE_SPEED;
+        if self.gliding {
+            self.last = Some(now);
+        } else {
+            self.stop();
+        }
+        self.gliding
+    }
+
+    /// Stops tracking and gliding.
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_gliding(&self) -> bool {
+        self.gliding
+    }
+
+    /// Advances the glide to the given time. Returns the distance in screen pixels the view
+    /// moves, `None` once the glide is over.
+    pub fn step(&mut self, now: Instant) -> Option<Vector> {
+        if !self.gliding {
+            return None;
+        }
+        let last = self.last.unwrap_or(now);
+        let elapsed = now.saturating_duration_since(last).as_secs_f32();
+        self.last = Some(now);
+        // the exact distance of an exponentially slowing motion during `elapsed`
+        let decay = (-GLIDE_DECAY * elapsed).exp();
+        let delta = self.velocity * ((1.0 - decay) / GLIDE_DECAY);
+        self.velocity = self.velocity * decay;
+        if length(self.velocity) < MIN_GLIDE_SPEED {
+            self.stop();
+        }
+        Some(delta)
+    }
+}
+
+fn length(v: Vector) -> f32 {
+    (v.x * v.x + v.y * v.y).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use std::time::Duration;
+
+    /// Scales from `MIN_SCALE` to `MAX_SCALE` in equal zoom steps.
+    fn scales() -> impl Iterator<Item = f32> {
+        const STEPS: i32 = 40;
+        (0..=STEPS).map(|i| MIN_SCALE * (MAX_SCALE / MIN_SCALE).powf(i as f32 / STEPS as f32))
+    }
+
+    /// Compares points with an error relative to the largest coordinate involved (the point or
+    /// the translation of the view), f32 has about 7 digits.
+    fn assert_close(actual: Point, expected: Point, viewport: &Viewport) {
+        let size = [
+            expected.x,
+            expected.y,
+            viewport.translation.x,
+            viewport.translation.y,
+        ]
+        .into_iter()
+        .fold(1.0_f32, |size, coordinate| size.max(coordinate.abs()));
+        assert!(
+            actual.distance(expected) <= 1e-5 * size,
+            "{actual:?} != {expected:?} for {viewport:?}"
+        );
+    }
+
+    #[test]
+    fn to_world_inverts_to_screen_at_every_scale() {
+        let mut random = Random(1);
+        for scale in scales() {
+            let viewport = Viewport::new(random.vector(1000.0), scale);
+            assert_eq!(viewport.scale, scale);
+            for _ in 0..20 {
+                let world = random.point(10000.0);
+                assert_close(
+                    viewport.to_world(viewport.to_screen(world)),
+                    world,
+                    &viewport,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_about_keeps_the_anchor_in_place() {
+        let mut random = Random(2);
+        for scale in scales() {
+            let mut viewport = Viewport::new(random.vector(1000.0), scale);
+            let anchor = random.point(1000.0);
+            let world = viewport.to_world(anchor);
+            let new_scale = random.range(MIN_SCALE, MAX_SCALE);
+            assert!(viewport.zoom_about(anchor, new_scale), "{viewport:?}");
+            assert_eq!(viewport.scale, new_scale);
+            assert_close(viewport.to_screen(world), anchor, &viewport);
+        }
+    }
+
+    #[test]
+    fn zoom_about_clamps_the_scale() {
+        let anchor = Point::new(100.0, 50.0);
+        let mut viewport = Viewport::default();
+        assert!(viewport.zoom_about(anchor, MAX_SCALE * 2.0));
+        assert_eq!(viewport.scale, MAX_SCALE);
+        // already at the limit: nothing changes
+        let before = viewport;
+        assert!(!viewport.zoom_about(anchor, MAX_SCALE * 4.0));
+        assert_eq!(viewport, before);
+
+        assert!(viewport.zoom_about(anchor, 0.0));
+        assert_eq!(viewport.scale, MIN_SCALE);
+        assert_close(
+            viewport.to_screen(Point::ORIGIN),
+            Point::new(90.0, 45.0),
+            &viewport,
+        );
+    }
+
+    #[test]
+    fn pan_moves_the_content_by_the_screen_delta() {
+        let mut random = Random(3);
+        for scale in scales() {
+            let mut viewport = Viewport::new(random.vector(1000.0), scale);
+            let world = random.point(10000.0);
+            let before = viewport.to_screen(world);
+            let delta = random.vector(500.0);
+            viewport.pan(delta);
+            assert_eq!(viewport.scale, scale);
+            assert_close(viewport.to_screen(world), before + delta, &viewport);
+        }
+    }
+
+    #[test]
+    fn glide_slows_down_to_a_stop() {
+        let start = Instant::now();
+        let frame = Duration::from_millis(10);
+        let mut inertia = Inertia::default();
+        // 1000 pixels per second to the right
+        for i in 0..10 {
+            inertia.track(Vector::new(10.0, 0.0), start + frame * i);
+        }
+        let mut now = start + frame * 10;
+        assert!(inertia.release(now));
+        assert!(inertia.is_gliding());
+
+        let mut deltas = Vec::new();
+        while let Some(delta) = inertia.step(now) {
+            deltas.push(delta);
+            assert!(deltas.len() < 1000, "the glide doesn't stop");
+            now += Duration::from_millis(16);
+        }
+        assert!(!inertia.is_gliding());
+        assert_eq!(inertia.step(now + frame), None);
+
+        // the first step at the release time doesn't move, every later one moves less
+        assert_eq!(deltas[0], Vector::new(0.0, 0.0));
+        for pair in deltas[1..].windows(2) {
+            assert!(pair[1].x < pair[0].x && pair[1].x > 0.0, "{pair:?}");
+            assert_eq!(pair[1].y, 0.0);
+        }
+        // the glide is shorter than an endless one at the full drag speed
+        let total: f32 = deltas.iter().map(|delta| delta.x).sum();
+        assert!(total > 0.0 && total < 1000.0 / GLIDE_DECAY, "{total}");
+    }
+
+    #[test]
+    fn late_release_does_not_glide() {
+        let start = Instant::now();
+        let mut inertia = Inertia::default();
+        inertia.track(Vector::new(10.0, 0.0), start);
+        inertia.track(Vector::new(10.0, 0.0), start + Duration::from_millis(10));
+        let release = start + Duration::from_secs_f32(0.01 + RELEASE_TIMEOUT * 2.0);
+        assert!(!inertia.release(release));
+        assert!(!inertia.is_gliding());
+        assert_eq!(inertia.step(release), None);
+    }
+}